@@ -0,0 +1,39 @@
+use crate::{
+    docker::network::{DockerNetwork, NetworkEndpoint},
+    traits::Callback,
+};
+use async_trait::async_trait;
+use color_eyre::eyre::Result;
+
+#[derive(Debug)]
+pub struct DisconnectNetwork {
+    docker: bollard::Docker,
+    network: DockerNetwork,
+    endpoint: NetworkEndpoint,
+    force: bool,
+}
+
+impl DisconnectNetwork {
+    pub fn new(
+        docker: bollard::Docker,
+        network: DockerNetwork,
+        endpoint: NetworkEndpoint,
+        force: bool,
+    ) -> Self {
+        Self {
+            docker,
+            network,
+            endpoint,
+            force,
+        }
+    }
+}
+
+#[async_trait]
+impl Callback for DisconnectNetwork {
+    async fn call(&self) -> Result<()> {
+        self.network
+            .disconnect(&self.docker, &self.endpoint.container_id, self.force)
+            .await
+    }
+}
@@ -2,7 +2,10 @@ pub mod delete_container;
 pub mod delete_image;
 pub mod delete_network;
 pub mod delete_volume;
+pub mod disconnect_network;
 pub mod empty_callable;
+pub mod restore_volume;
 pub use delete_container::DeleteContainer;
 pub mod delete_all_containers;
 pub mod prune_networks;
+pub mod prune_resources;
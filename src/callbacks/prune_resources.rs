@@ -0,0 +1,65 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    docker::prune::{PruneConfig, PruneReport, prune},
+    events::{Key, Message, Transition},
+    traits::Callback,
+};
+use async_trait::async_trait;
+use color_eyre::eyre::Result;
+use tokio::sync::mpsc::Sender;
+
+/// Progress of a prune, shared with the page which started it so that the
+/// report can be shown once the prune finishes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PruneStatus {
+    #[default]
+    Idle,
+    Running,
+    Finished(PruneReport),
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct PruneResources {
+    docker: bollard::Docker,
+    config: PruneConfig,
+    status: Arc<Mutex<PruneStatus>>,
+    tx: Sender<Message<Key, Transition>>,
+}
+
+impl PruneResources {
+    pub fn new(
+        docker: bollard::Docker,
+        config: PruneConfig,
+        status: Arc<Mutex<PruneStatus>>,
+        tx: Sender<Message<Key, Transition>>,
+    ) -> Self {
+        Self {
+            docker,
+            config,
+            status,
+            tx,
+        }
+    }
+}
+
+#[async_trait]
+impl Callback for PruneResources {
+    async fn call(&self) -> Result<()> {
+        let docker = self.docker.clone();
+        let config = self.config.clone();
+        let status = self.status.clone();
+        let tx = self.tx.clone();
+
+        *status.lock().unwrap() = PruneStatus::Running;
+        tokio::spawn(async move {
+            *status.lock().unwrap() = match prune(&docker, &config).await {
+                Ok(report) => PruneStatus::Finished(report),
+                Err(err) => PruneStatus::Failed(format!("{err:#}")),
+            };
+            let _ = tx.send(Message::Tick).await;
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use crate::{
+    docker::{transfer::Transfers, volume::DockerVolume},
+    events::{Key, Message, Transition},
+    traits::Callback,
+};
+use async_trait::async_trait;
+use color_eyre::eyre::Result;
+use tokio::sync::mpsc::Sender;
+
+/// Restores a volume from a local tarball in the background
+#[derive(Debug)]
+pub struct RestoreVolume {
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    transfers: Transfers,
+    volume: DockerVolume,
+    path: PathBuf,
+}
+
+impl RestoreVolume {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        transfers: Transfers,
+        volume: DockerVolume,
+        path: PathBuf,
+    ) -> Self {
+        Self {
+            docker,
+            tx,
+            transfers,
+            volume,
+            path,
+        }
+    }
+
+    pub fn volume_name(&self) -> &str {
+        &self.volume.name
+    }
+}
+
+#[async_trait]
+impl Callback for RestoreVolume {
+    async fn call(&self) -> Result<()> {
+        let docker = self.docker.clone();
+        let volume = self.volume.clone();
+        let path = self.path.clone();
+
+        let description = format!("Restoring {} from {}", volume.name, path.display());
+        self.transfers
+            .spawn(self.tx.clone(), description, move |transfer| async move {
+                let checksum = volume.restore(&docker, &path, transfer).await?;
+                Ok(format!(
+                    "Restored {} from {} (sha256 {checksum})",
+                    volume.name,
+                    path.display()
+                ))
+            });
+        Ok(())
+    }
+}
@@ -24,6 +24,14 @@ const VOLUME: &str = "volume";
 const VOLUMES: &str = "volumes";
 const NETWORK: &str = "network";
 const NETWORKS: &str = "networks";
+const TOP: &str = "top";
+const SYSTEM: &str = "system";
+const DF: &str = "df";
+const PRUNE: &str = "prune";
+const EVENTS: &str = "events";
+const PULL: &str = "pull";
+const PUSH: &str = "push";
+const BUILD: &str = "build";
 const HELP: &str = "help";
 const H: &str = "h";
 const QUESTION_MARK: &str = "?";
@@ -53,6 +61,14 @@ impl CommandInput {
                 VOLUMES,
                 NETWORK,
                 NETWORKS,
+                TOP,
+                SYSTEM,
+                DF,
+                PRUNE,
+                EVENTS,
+                PULL,
+                PUSH,
+                BUILD,
                 HELP,
                 H,
                 QUESTION_MARK,
@@ -112,12 +128,34 @@ impl CommandInput {
     }
 
     async fn submit(&mut self) -> Result<()> {
-        let transition = match &*self.text_input.get_value() {
+        let value = self.text_input.get_value();
+        let (command, argument) = match value.trim().split_once(' ') {
+            Some((command, argument)) => (command, Some(argument.trim().to_string())),
+            None => (value.trim(), None),
+        };
+
+        let transition = match command {
             Q | QUIT => Some(Transition::Quit),
             IMAGE | IMAGES => Some(Transition::ToImagePage(AppContext::default())),
             CONTAINER | CONTAINERS => Some(Transition::ToContainerPage(AppContext::default())),
             VOLUME | VOLUMES => Some(Transition::ToVolumePage(AppContext::default())),
             NETWORK | NETWORKS => Some(Transition::ToNetworkPage(AppContext::default())),
+            TOP => Some(Transition::ToTopPage(AppContext::default())),
+            SYSTEM | DF => Some(Transition::ToSystemPage(AppContext::default())),
+            PRUNE => Some(Transition::ToPrunePage(AppContext::default())),
+            EVENTS => Some(Transition::ToEventsPage(AppContext::default())),
+            PULL if argument.is_some() => Some(Transition::ToPullPage(AppContext {
+                image_reference: argument,
+                ..Default::default()
+            })),
+            PUSH if argument.is_some() => Some(Transition::ToPushPage(AppContext {
+                image_reference: argument,
+                ..Default::default()
+            })),
+            BUILD if argument.is_some() => Some(Transition::ToBuildPage(AppContext {
+                build_context: argument,
+                ..Default::default()
+            })),
             HELP | H | QUESTION_MARK => Some(Transition::ToHelpPage(AppContext::default())),
             _ => None,
         };
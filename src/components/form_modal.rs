@@ -0,0 +1,371 @@
+use std::{fmt::Debug, sync::Arc};
+
+use color_eyre::eyre::Result;
+use itertools::Itertools;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Wrap},
+};
+use ratatui_macros::{horizontal, vertical};
+
+use crate::{
+    config::Config,
+    events::{Key, message::MessageResponse},
+    traits::{Component, ModalComponent},
+};
+
+const WIDTH: u16 = 90;
+const LABEL_WIDTH: u16 = 30;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub enum ModalState {
+    #[default]
+    Closed,
+    Open(String),
+}
+
+/// Validates the raw value of a text field, returning a human readable error
+/// when the value is invalid
+pub type Validator = fn(&str) -> Result<()>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    Text(String),
+    Toggle(bool),
+    Choice { options: Vec<String>, idx: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct FormField {
+    key: &'static str,
+    label: String,
+    value: FieldValue,
+    validator: Option<Validator>,
+    /// Whether the value is masked when displayed, eg for passwords
+    secret: bool,
+}
+
+impl FormField {
+    /// A free text field
+    pub fn text(key: &'static str, label: &str) -> Self {
+        Self {
+            key,
+            label: label.into(),
+            value: FieldValue::Text(String::new()),
+            validator: None,
+            secret: false,
+        }
+    }
+
+    /// A free text field whose value is masked, eg for a password
+    pub fn secret(key: &'static str, label: &str) -> Self {
+        Self {
+            secret: true,
+            ..Self::text(key, label)
+        }
+    }
+
+    /// A boolean field, toggled with the space bar
+    pub fn toggle(key: &'static str, label: &str, default: bool) -> Self {
+        Self {
+            key,
+            label: label.into(),
+            value: FieldValue::Toggle(default),
+            validator: None,
+            secret: false,
+        }
+    }
+
+    /// A field with a fixed set of options, cycled with the left and right keys;
+    /// the first option is the default
+    pub fn choice(key: &'static str, label: &str, options: Vec<String>) -> Self {
+        Self {
+            key,
+            label: label.into(),
+            value: FieldValue::Choice { options, idx: 0 },
+            validator: None,
+            secret: false,
+        }
+    }
+
+    /// Sets the initial value of a text field, or selects the matching option
+    /// of a choice field
+    pub fn with_value(mut self, value: &str) -> Self {
+        match &mut self.value {
+            FieldValue::Text(v) => *v = value.into(),
+            FieldValue::Choice { options, idx } => {
+                if let Some(i) = options.iter().position(|o| o == value) {
+                    *idx = i;
+                }
+            }
+            FieldValue::Toggle(_) => {}
+        }
+        self
+    }
+
+    /// Sets a validator to be run against a text field before the form can be submitted
+    pub fn with_validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        match (&self.value, self.validator) {
+            (FieldValue::Text(v), Some(validator)) => validator(v),
+            _ => Ok(()),
+        }
+    }
+
+    fn display_value(&self, focused: bool) -> String {
+        match &self.value {
+            FieldValue::Text(v) if self.secret && focused => {
+                format!("{}█", "*".repeat(v.chars().count()))
+            }
+            FieldValue::Text(v) if self.secret => "*".repeat(v.chars().count()),
+            FieldValue::Text(v) if focused => format!("{v}█"),
+            FieldValue::Text(v) => v.clone(),
+            FieldValue::Toggle(true) => "[x]".into(),
+            FieldValue::Toggle(false) => "[ ]".into(),
+            FieldValue::Choice { options, idx } => {
+                format!("< {} >", options.get(*idx).cloned().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// The values of a submitted form, retrieved by field key
+#[derive(Debug, Clone, Default)]
+pub struct FormValues {
+    fields: Vec<FormField>,
+}
+
+impl FormValues {
+    fn get(&self, key: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|f| f.key == key).map(|f| &f.value)
+    }
+
+    /// Gets the trimmed value of a text field, or the selected value of a choice field
+    pub fn text(&self, key: &str) -> String {
+        match self.get(key) {
+            Some(FieldValue::Text(v)) => v.trim().to_string(),
+            Some(FieldValue::Choice { options, idx }) => {
+                options.get(*idx).cloned().unwrap_or_default()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Gets the trimmed value of a text field, or None where it is empty
+    pub fn text_opt(&self, key: &str) -> Option<String> {
+        let value = self.text(key);
+        if value.is_empty() { None } else { Some(value) }
+    }
+
+    /// Gets the value of a toggle field
+    pub fn toggle(&self, key: &str) -> bool {
+        matches!(self.get(key), Some(FieldValue::Toggle(true)))
+    }
+}
+
+/// Modal presenting a set of fields for the user to fill in.
+///
+/// Fields are navigated with the up/down or tab keys and the form is submitted
+/// with enter, at which point each field's validator is run. Like the
+/// `SelectModal`, once the form is submitted the modal closes and the values
+/// can be retrieved with `take_submitted`.
+#[derive(Debug)]
+pub struct FormModal<P> {
+    pub discriminator: P,
+    pub state: ModalState,
+    title: String,
+    config: Arc<Config>,
+    fields: Vec<FormField>,
+    focused: usize,
+    error: Option<String>,
+    submitted: Option<FormValues>,
+}
+
+impl<P> FormModal<P> {
+    pub fn new(title: String, discriminator: P, config: Arc<Config>) -> Self {
+        Self {
+            discriminator,
+            state: ModalState::default(),
+            title,
+            config,
+            fields: vec![],
+            focused: 0,
+            error: None,
+            submitted: None,
+        }
+    }
+
+    pub fn initialise(&mut self, message: String, fields: Vec<FormField>) {
+        self.fields = fields;
+        self.focused = 0;
+        self.error = None;
+        self.submitted = None;
+        self.state = ModalState::Open(message)
+    }
+
+    pub fn reset(&mut self) {
+        self.fields = vec![];
+        self.error = None;
+        self.state = ModalState::Closed
+    }
+
+    /// Returns the values of the form, if it has been submitted since the last call
+    pub fn take_submitted(&mut self) -> Option<FormValues> {
+        self.submitted.take()
+    }
+
+    fn submit(&mut self) {
+        for field in &self.fields {
+            if let Err(e) = field.validate() {
+                self.error = Some(format!("{}: {e}", field.label));
+                return;
+            }
+        }
+        self.submitted = Some(FormValues {
+            fields: self.fields.clone(),
+        });
+        self.reset();
+    }
+
+    fn focus_next(&mut self) {
+        self.focused = (self.focused + 1).min(self.fields.len().saturating_sub(1));
+    }
+
+    fn focus_previous(&mut self) {
+        self.focused = self.focused.saturating_sub(1);
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> ModalComponent for FormModal<P>
+where
+    P: Debug + Send,
+{
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        match message {
+            Key::Esc => self.reset(),
+            Key::Enter => self.submit(),
+            Key::Up => self.focus_previous(),
+            Key::Down | Key::Tab => self.focus_next(),
+            // Ctrl keys are left for the application, eg to quit
+            Key::Ctrl(_) => return Ok(MessageResponse::NotConsumed),
+            _ => {
+                let Some(field) = self.fields.get_mut(self.focused) else {
+                    return Ok(MessageResponse::Consumed);
+                };
+                match (&mut field.value, message) {
+                    (FieldValue::Text(v), Key::Char(c)) => v.push(c),
+                    (FieldValue::Text(v), Key::Backspace) => {
+                        v.pop();
+                    }
+                    (FieldValue::Toggle(v), Key::Char(' ')) => *v = !*v,
+                    (FieldValue::Choice { options, idx }, Key::Right | Key::Char(' ')) => {
+                        *idx = (*idx + 1) % options.len().max(1)
+                    }
+                    (FieldValue::Choice { options, idx }, Key::Left) => {
+                        *idx = (*idx + options.len().max(1) - 1) % options.len().max(1)
+                    }
+                    _ => {}
+                }
+                self.error = None;
+            }
+        }
+        // All other input is swallowed so that typing into the form can't
+        // trigger page actions
+        Ok(MessageResponse::Consumed)
+    }
+}
+
+impl<P> Component for FormModal<P>
+where
+    P: Debug,
+{
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let message: String = match &self.state {
+            ModalState::Open(v) => v.clone(),
+            _ => return,
+        };
+
+        let height = self.fields.len() as u16 + 8;
+
+        let [_, area, _] = horizontal![>=0, ==WIDTH, >=0].areas(area);
+        let [_, area, _] = vertical![>=0, ==height, >=0].areas(area);
+
+        let title = Line::from(format!("< {} >", self.title.clone())).centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+
+        let [_, prompt_area, _, fields_area, error_area, opts_area] =
+            vertical![==1, ==1, ==1, >=0, ==1, ==1].areas(inner);
+
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let prompt = Paragraph::new(message).wrap(Wrap { trim: true }).centered();
+        f.render_widget(prompt, prompt_area);
+
+        for (idx, field) in self.fields.iter().enumerate() {
+            let row = Rect {
+                y: fields_area.y + idx as u16,
+                height: 1,
+                ..fields_area
+            };
+            if row.y >= fields_area.bottom() {
+                break;
+            }
+            let [label_area, _, value_area] = horizontal![==LABEL_WIDTH, ==2, >=0].areas(row);
+
+            let focused = idx == self.focused;
+            let label_style = if focused {
+                Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::new().add_modifier(Modifier::BOLD)
+            };
+
+            f.render_widget(
+                Line::from(Span::styled(field.label.clone(), label_style)).right_aligned(),
+                label_area,
+            );
+            f.render_widget(Line::from(field.display_value(focused)), value_area);
+        }
+
+        if let Some(error) = &self.error {
+            f.render_widget(
+                Line::from(Span::styled(
+                    error.clone(),
+                    Style::new().fg(self.config.theme.error()),
+                ))
+                .centered(),
+                error_area,
+            );
+        }
+
+        let spans = [
+            ("↑/↓", "Move"),
+            ("Space/←/→", "Change"),
+            ("Enter", "Submit"),
+            ("Esc", "Cancel"),
+        ]
+        .iter()
+        .flat_map(|(key, desc)| {
+            let key = Span::styled(
+                format!(" <{key}> = "),
+                Style::new().add_modifier(Modifier::ITALIC),
+            );
+            let desc = Span::styled(
+                format!("{desc} "),
+                Style::new().add_modifier(Modifier::ITALIC),
+            );
+            [key, desc]
+        })
+        .collect_vec();
+        f.render_widget(Line::from(spans).centered(), opts_area);
+    }
+}
@@ -0,0 +1,787 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use color_eyre::eyre::Result;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph},
+};
+use ratatui_macros::vertical;
+use serde_json::Value;
+
+use crate::{
+    components::text_input_wrapper::TextInputWrapper,
+    config::Config,
+    events::{Key, message::MessageResponse},
+    traits::Component,
+};
+
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const SPACE_BAR: Key = Key::Char(' ');
+const SLASH_KEY: Key = Key::Char('/');
+const N_KEY: Key = Key::Char('n');
+const SHIFT_N_KEY: Key = Key::Char('N');
+
+/// Suffix given to the IDs of lines which close a JSON object or array
+const CLOSING_SUFFIX: &str = "#end";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InspectFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl InspectFormat {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Json => Self::Yaml,
+            Self::Yaml => Self::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Key,
+    String,
+    Number,
+    Bool,
+    Null,
+    Punctuation,
+}
+
+#[derive(Debug, Clone)]
+struct DocumentLine {
+    /// JSON pointer to the value the line shows, used to keep track of a line
+    /// when the document is re-rendered
+    id: String,
+    /// JSON pointer to the object or array folded when the line is toggled
+    fold: String,
+    indent: usize,
+    tokens: Vec<(Token, String)>,
+}
+
+impl DocumentLine {
+    fn text(&self) -> String {
+        let tokens: String = self.tokens.iter().map(|(_, t)| t.as_str()).collect();
+        format!("{}{tokens}", " ".repeat(self.indent))
+    }
+}
+
+/// Renders a document into lines, skipping the contents of folded objects and
+/// arrays; where no folds are given the whole document is rendered
+struct Renderer<'a> {
+    folded: Option<&'a HashSet<String>>,
+    lines: Vec<DocumentLine>,
+}
+
+impl<'a> Renderer<'a> {
+    fn render(
+        document: &Value,
+        format: InspectFormat,
+        folded: Option<&'a HashSet<String>>,
+    ) -> Vec<DocumentLine> {
+        let mut renderer = Self {
+            folded,
+            lines: vec![],
+        };
+        match format {
+            InspectFormat::Json => renderer.json("", "", None, document, 0, false),
+            InspectFormat::Yaml => renderer.yaml_root(document),
+        }
+        renderer.lines
+    }
+
+    fn is_folded(&self, pointer: &str) -> bool {
+        self.folded.is_some_and(|f| f.contains(pointer))
+    }
+
+    fn push(&mut self, id: &str, fold: &str, indent: usize, tokens: Vec<(Token, String)>) {
+        self.lines.push(DocumentLine {
+            id: id.into(),
+            fold: fold.into(),
+            indent,
+            tokens,
+        });
+    }
+
+    fn json(
+        &mut self,
+        pointer: &str,
+        parent: &str,
+        key: Option<&str>,
+        value: &Value,
+        indent: usize,
+        comma: bool,
+    ) {
+        let mut tokens = vec![];
+        if let Some(key) = key {
+            tokens.push((Token::Key, json_string(key)));
+            tokens.push((Token::Punctuation, ": ".into()));
+        }
+        let comma = if comma { "," } else { "" };
+
+        let (open, close) = match value {
+            Value::Object(o) if !o.is_empty() => ("{", "}"),
+            Value::Array(a) if !a.is_empty() => ("[", "]"),
+            _ => {
+                tokens.push(json_scalar(value));
+                tokens.push((Token::Punctuation, comma.into()));
+                self.push(pointer, parent, indent, tokens);
+                return;
+            }
+        };
+
+        if self.is_folded(pointer) {
+            tokens.push((Token::Punctuation, format!("{open}…{close}{comma}")));
+            self.push(pointer, pointer, indent, tokens);
+            return;
+        }
+
+        tokens.push((Token::Punctuation, open.into()));
+        self.push(pointer, pointer, indent, tokens);
+
+        let children = children(pointer, value);
+        let count = children.len();
+        for (idx, (child_pointer, child_key, child)) in children.into_iter().enumerate() {
+            self.json(
+                &child_pointer,
+                pointer,
+                child_key,
+                child,
+                indent + 2,
+                idx + 1 < count,
+            );
+        }
+
+        self.push(
+            &format!("{pointer}{CLOSING_SUFFIX}"),
+            pointer,
+            indent,
+            vec![(Token::Punctuation, format!("{close}{comma}"))],
+        );
+    }
+
+    fn yaml_root(&mut self, document: &Value) {
+        if is_empty_or_scalar(document) {
+            self.push("", "", 0, vec![yaml_scalar(document)]);
+        } else if self.is_folded("") {
+            self.push("", "", 0, vec![folded_token(document)]);
+        } else {
+            self.yaml_children("", document, 0);
+        }
+    }
+
+    fn yaml_children(&mut self, pointer: &str, value: &Value, indent: usize) {
+        for (child_pointer, child_key, child) in children(pointer, value) {
+            match child_key {
+                Some(key) => self.yaml_entry(&child_pointer, pointer, key, child, indent),
+                None => self.yaml_item(&child_pointer, pointer, child, indent),
+            }
+        }
+    }
+
+    fn yaml_entry(&mut self, pointer: &str, parent: &str, key: &str, value: &Value, indent: usize) {
+        let mut tokens = vec![
+            (Token::Key, yaml_string(key)),
+            (Token::Punctuation, ":".into()),
+        ];
+
+        if is_empty_or_scalar(value) {
+            tokens.push((Token::Punctuation, " ".into()));
+            tokens.push(yaml_scalar(value));
+            self.push(pointer, parent, indent, tokens);
+        } else if self.is_folded(pointer) {
+            tokens.push((Token::Punctuation, " ".into()));
+            tokens.push(folded_token(value));
+            self.push(pointer, pointer, indent, tokens);
+        } else {
+            self.push(pointer, pointer, indent, tokens);
+            self.yaml_children(pointer, value, indent + 2);
+        }
+    }
+
+    fn yaml_item(&mut self, pointer: &str, parent: &str, value: &Value, indent: usize) {
+        let dash = (Token::Punctuation, "- ".to_string());
+
+        if is_empty_or_scalar(value) {
+            self.push(pointer, parent, indent, vec![dash, yaml_scalar(value)]);
+        } else if self.is_folded(pointer) {
+            self.push(pointer, pointer, indent, vec![dash, folded_token(value)]);
+        } else {
+            // The first line of the item is shown on the same line as its dash
+            let start = self.lines.len();
+            self.yaml_children(pointer, value, indent + 2);
+            let first = &mut self.lines[start];
+            first.indent = indent;
+            first.tokens.insert(0, dash);
+        }
+    }
+}
+
+/// The children of an object or array, along with their JSON pointers and,
+/// for objects, their keys
+fn children<'v>(pointer: &str, value: &'v Value) -> Vec<(String, Option<&'v str>, &'v Value)> {
+    match value {
+        Value::Object(o) => o
+            .iter()
+            .map(|(k, v)| {
+                let escaped = k.replace('~', "~0").replace('/', "~1");
+                (format!("{pointer}/{escaped}"), Some(k.as_str()), v)
+            })
+            .collect(),
+        Value::Array(a) => a
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| (format!("{pointer}/{idx}"), None, v))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn is_empty_or_scalar(value: &Value) -> bool {
+    match value {
+        Value::Object(o) => o.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        _ => true,
+    }
+}
+
+fn folded_token(value: &Value) -> (Token, String) {
+    let folded = match value {
+        Value::Array(a) => format!("[…] # {} items", a.len()),
+        Value::Object(o) => format!("{{…}} # {} keys", o.len()),
+        _ => String::new(),
+    };
+    (Token::Punctuation, folded)
+}
+
+fn json_string(s: &str) -> String {
+    Value::String(s.into()).to_string()
+}
+
+fn json_scalar(value: &Value) -> (Token, String) {
+    let token = match value {
+        Value::String(_) => Token::String,
+        Value::Number(_) => Token::Number,
+        Value::Bool(_) => Token::Bool,
+        Value::Null => Token::Null,
+        _ => Token::Punctuation,
+    };
+    (token, value.to_string())
+}
+
+/// Quotes a string where required for it to be read back as the same string;
+/// multi-line strings are double quoted rather than written as block scalars
+fn yaml_string(s: &str) -> String {
+    if s.contains('\n') {
+        return json_string(s);
+    }
+    match serde_yml::to_string(s) {
+        Ok(s) => s.trim_end_matches('\n').to_string(),
+        Err(_) => json_string(s),
+    }
+}
+
+fn yaml_scalar(value: &Value) -> (Token, String) {
+    match value {
+        Value::String(s) => (Token::String, yaml_string(s)),
+        Value::Object(_) => (Token::Punctuation, "{}".into()),
+        Value::Array(_) => (Token::Punctuation, "[]".into()),
+        _ => json_scalar(value),
+    }
+}
+
+/// Whether the folded object or array at `pointer` contains the value at `id`
+fn is_ancestor(pointer: &str, id: &str) -> bool {
+    pointer != id && id.starts_with(&format!("{pointer}/"))
+}
+
+/// Scrollable view of a complete JSON document, eg the output of a docker inspect
+/// endpoint, as either JSON or YAML.
+///
+/// Objects and arrays can be folded, and the document can be searched; matches
+/// within folded values are unfolded when jumped to.
+#[derive(Debug)]
+pub struct InspectView {
+    config: Arc<Config>,
+    document: Option<Value>,
+    format: InspectFormat,
+    folded: HashSet<String>,
+    lines: Vec<DocumentLine>,
+    list_state: ListState,
+    height: u16,
+    search_input: TextInputWrapper,
+    searching: bool,
+    search: Option<String>,
+    match_count: usize,
+}
+
+impl InspectView {
+    pub fn new(config: Arc<Config>) -> Self {
+        Self {
+            config,
+            document: None,
+            format: InspectFormat::default(),
+            folded: HashSet::new(),
+            lines: vec![],
+            list_state: ListState::default(),
+            height: 0,
+            search_input: TextInputWrapper::new("/".into(), None),
+            searching: false,
+            search: None,
+            match_count: 0,
+        }
+    }
+
+    pub fn has_document(&self) -> bool {
+        self.document.is_some()
+    }
+
+    pub fn set_document(&mut self, document: Value) {
+        self.document = Some(document);
+        self.folded.clear();
+        self.search = None;
+        self.searching = false;
+        self.match_count = 0;
+        self.render();
+        self.list_state.select_first();
+    }
+
+    /// Switches between JSON and YAML, keeping the selected line where possible
+    pub fn toggle_format(&mut self) {
+        let selected = self.selected_id();
+        self.format = self.format.toggle();
+        self.render();
+        if let Some(id) = selected
+            && !self.select_id(&id)
+        {
+            // Closing lines only exist in JSON, so go to the start of the value instead
+            self.select_id(id.trim_end_matches(CLOSING_SUFFIX));
+        }
+        if self.search.is_some() {
+            self.count_matches();
+        }
+    }
+
+    fn render(&mut self) {
+        self.lines = match &self.document {
+            Some(document) => Renderer::render(document, self.format, Some(&self.folded)),
+            None => vec![],
+        };
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        self.list_state
+            .selected()
+            .and_then(|idx| self.lines.get(idx))
+            .map(|l| l.id.clone())
+    }
+
+    fn select_id(&mut self, id: &str) -> bool {
+        match self.lines.iter().position(|l| l.id == id) {
+            Some(idx) => {
+                self.list_state.select(Some(idx));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn toggle_fold(&mut self) {
+        let Some(line) = self
+            .list_state
+            .selected()
+            .and_then(|idx| self.lines.get(idx))
+        else {
+            return;
+        };
+        let pointer = line.fold.clone();
+        let id = line.id.clone();
+
+        if !self.folded.remove(&pointer) {
+            self.folded.insert(pointer.clone());
+        }
+        self.render();
+
+        if !self.select_id(&pointer) && !self.select_id(&id) {
+            let last = self.lines.len().saturating_sub(1);
+            let selected = self.list_state.selected().unwrap_or_default().min(last);
+            self.list_state.select(Some(selected));
+        }
+    }
+
+    fn is_match(line: &DocumentLine, search: &str) -> bool {
+        !line.id.ends_with(CLOSING_SUFFIX) && line.text().to_lowercase().contains(search)
+    }
+
+    fn count_matches(&mut self) {
+        let (Some(document), Some(search)) = (&self.document, &self.search) else {
+            self.match_count = 0;
+            return;
+        };
+        self.match_count = Renderer::render(document, self.format, None)
+            .iter()
+            .filter(|l| Self::is_match(l, search))
+            .count();
+    }
+
+    /// Jumps to the next match in the given direction, unfolding any values it is
+    /// hidden within
+    fn find(&mut self, forward: bool, include_current: bool) {
+        let (Some(document), Some(search)) = (&self.document, &self.search) else {
+            return;
+        };
+
+        let lines = Renderer::render(document, self.format, None);
+        if lines.is_empty() {
+            return;
+        }
+        let current = self.selected_id().unwrap_or_default();
+        let start = lines.iter().position(|l| l.id == current).unwrap_or(0);
+        let count = lines.len();
+        let offset = if include_current { 0 } else { 1 };
+
+        let found = (offset..count + offset)
+            .map(|i| {
+                if forward {
+                    (start + i) % count
+                } else {
+                    (start + count - i) % count
+                }
+            })
+            .find(|&i| Self::is_match(&lines[i], search));
+
+        if let Some(idx) = found {
+            let id = lines[idx].id.clone();
+            self.folded.retain(|f| !is_ancestor(f, &id));
+            self.render();
+            self.select_id(&id);
+        }
+    }
+
+    fn update_search(&mut self, message: Key) -> Result<MessageResponse> {
+        match message {
+            Key::Esc => {
+                self.searching = false;
+            }
+            Key::Enter => {
+                let search = self.search_input.get_value().to_lowercase();
+                self.searching = false;
+                self.search = if search.is_empty() {
+                    None
+                } else {
+                    Some(search)
+                };
+                self.count_matches();
+                self.find(true, true);
+            }
+            _ => return self.search_input.update(message),
+        }
+        Ok(MessageResponse::Consumed)
+    }
+
+    fn down(&mut self, amount: u16) {
+        let last = self.lines.len().saturating_sub(1);
+        let selected = self.list_state.selected().unwrap_or_default();
+        self.list_state
+            .select(Some((selected + amount as usize).min(last)));
+    }
+
+    fn up(&mut self, amount: u16) {
+        let selected = self.list_state.selected().unwrap_or_default();
+        self.list_state
+            .select(Some(selected.saturating_sub(amount as usize)));
+    }
+
+    pub fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        if self.searching {
+            return self.update_search(message);
+        }
+
+        match message {
+            UP_KEY | K_KEY => self.up(1),
+            DOWN_KEY | J_KEY => self.down(1),
+            PAGE_UP_KEY => self.up(self.height),
+            PAGE_DOWN_KEY => self.down(self.height),
+            G_KEY => self.list_state.select_first(),
+            SHIFT_G_KEY => self
+                .list_state
+                .select(Some(self.lines.len().saturating_sub(1))),
+            Key::Enter | SPACE_BAR => self.toggle_fold(),
+            SLASH_KEY => {
+                self.search_input.reset();
+                self.searching = true;
+            }
+            N_KEY => self.find(true, false),
+            SHIFT_N_KEY => self.find(false, false),
+            _ => return Ok(MessageResponse::NotConsumed),
+        }
+        Ok(MessageResponse::Consumed)
+    }
+
+    fn token_style(&self, token: Token) -> Style {
+        let theme = &self.config.theme;
+        let style = Style::default();
+        match token {
+            Token::Key => style.fg(theme.footer()),
+            Token::String => style.fg(theme.success()),
+            Token::Number => style.fg(theme.paused_highlight()),
+            Token::Bool => style.fg(theme.negative_highlight()),
+            Token::Null => style.fg(theme.help()),
+            Token::Punctuation => style,
+        }
+    }
+}
+
+impl Component for InspectView {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [document_area, search_area] = if self.searching {
+            vertical![>=0, ==3].areas(area)
+        } else if self.search.is_some() {
+            vertical![>=0, ==1].areas(area)
+        } else {
+            vertical![>=0, ==0].areas(area)
+        };
+        self.height = document_area.height.saturating_sub(1);
+
+        let items: Vec<ListItem> = self
+            .lines
+            .iter()
+            .map(|line| {
+                let mut spans = vec![Span::from(" ".repeat(line.indent))];
+                spans.extend(
+                    line.tokens
+                        .iter()
+                        .map(|(token, text)| Span::styled(text.clone(), self.token_style(*token))),
+                );
+
+                let mut line_style = Style::default();
+                if let Some(search) = &self.search
+                    && Self::is_match(line, search)
+                {
+                    line_style = line_style.add_modifier(Modifier::REVERSED);
+                }
+                ListItem::new(Line::from(spans).style(line_style))
+            })
+            .collect();
+
+        let list = List::new(items).highlight_style(
+            Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, document_area, &mut self.list_state);
+
+        if self.searching {
+            self.search_input.draw(f, search_area);
+        } else if let Some(search) = &self.search {
+            let status = Paragraph::new(format!(
+                "/{search} ({} matches, n/N to jump)",
+                self.match_count
+            ))
+            .style(Style::default().fg(self.config.theme.footer()));
+            f.render_widget(status, search_area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "Id": "abc",
+            "Config": {"Tty": true, "Env": ["A=1", "B=2"]},
+            "Mounts": [{"Name": "data"}],
+        })
+    }
+
+    fn texts(lines: &[DocumentLine]) -> Vec<String> {
+        lines.iter().map(DocumentLine::text).collect()
+    }
+
+    fn view() -> InspectView {
+        let mut view = InspectView::new(Arc::new(Config::default()));
+        view.set_document(document());
+        view
+    }
+
+    fn selected_text(view: &InspectView) -> String {
+        view.lines[view.list_state.selected().unwrap()].text()
+    }
+
+    fn search(view: &mut InspectView, search: &str) {
+        view.update(SLASH_KEY).unwrap();
+        for c in search.chars() {
+            view.update(Key::Char(c)).unwrap();
+        }
+        view.update(Key::Enter).unwrap();
+    }
+
+    #[test]
+    fn test_render_json() {
+        let lines = Renderer::render(&json!({"a": [1, null], "b": {}}), InspectFormat::Json, None);
+        assert_eq!(
+            texts(&lines),
+            vec![
+                "{",
+                "  \"a\": [",
+                "    1,",
+                "    null",
+                "  ],",
+                "  \"b\": {}",
+                "}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_yaml() {
+        let lines = Renderer::render(&document(), InspectFormat::Yaml, None);
+        assert_eq!(
+            texts(&lines),
+            vec![
+                "Config:",
+                "  Env:",
+                "    - A=1",
+                "    - B=2",
+                "  Tty: true",
+                "Id: abc",
+                "Mounts:",
+                "  - Name: data",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_folded() {
+        let folded = HashSet::from(["/Config".to_string(), "/Mounts".to_string()]);
+
+        let lines = Renderer::render(&document(), InspectFormat::Json, Some(&folded));
+        assert_eq!(
+            texts(&lines),
+            vec![
+                "{",
+                "  \"Config\": {…},",
+                "  \"Id\": \"abc\",",
+                "  \"Mounts\": […]",
+                "}"
+            ]
+        );
+
+        let lines = Renderer::render(&document(), InspectFormat::Yaml, Some(&folded));
+        assert_eq!(
+            texts(&lines),
+            vec!["Config: {…} # 2 keys", "Id: abc", "Mounts: […] # 1 items"]
+        );
+    }
+
+    #[test]
+    fn test_toggle_fold() {
+        let mut view = view();
+        view.update(DOWN_KEY).unwrap();
+        assert_eq!(selected_text(&view), "  \"Config\": {");
+
+        view.update(SPACE_BAR).unwrap();
+        assert_eq!(selected_text(&view), "  \"Config\": {…},");
+        assert_eq!(view.lines.len(), 9);
+
+        // Toggling a line within a value folds the value containing it
+        view.update(SPACE_BAR).unwrap();
+        view.update(DOWN_KEY).unwrap();
+        view.update(DOWN_KEY).unwrap();
+        assert_eq!(selected_text(&view), "      \"A=1\",");
+        view.update(Key::Enter).unwrap();
+        assert_eq!(selected_text(&view), "    \"Env\": […],");
+    }
+
+    #[test]
+    fn test_toggle_format() {
+        let mut view = view();
+        view.update(DOWN_KEY).unwrap();
+        view.update(DOWN_KEY).unwrap();
+        assert_eq!(selected_text(&view), "    \"Env\": [");
+
+        view.toggle_format();
+        assert_eq!(view.format, InspectFormat::Yaml);
+        assert_eq!(selected_text(&view), "  Env:");
+
+        view.toggle_format();
+        assert_eq!(view.format, InspectFormat::Json);
+        assert_eq!(selected_text(&view), "    \"Env\": [");
+    }
+
+    #[test]
+    fn test_toggle_format_from_closing_line() {
+        let mut view = view();
+        view.update(SHIFT_G_KEY).unwrap();
+        view.update(UP_KEY).unwrap();
+        assert_eq!(selected_text(&view), "  ]");
+
+        // Closing lines only exist in JSON, so the start of the value is selected
+        view.toggle_format();
+        assert_eq!(selected_text(&view), "Mounts:");
+    }
+
+    #[test]
+    fn test_search() {
+        let mut view = view();
+        search(&mut view, "NAME");
+
+        assert_eq!(view.match_count, 1);
+        assert_eq!(selected_text(&view), "      \"Name\": \"data\"");
+    }
+
+    #[test]
+    fn test_search_wraps_around() {
+        let mut view = view();
+        search(&mut view, "=");
+        assert_eq!(view.match_count, 2);
+        assert_eq!(selected_text(&view), "      \"A=1\",");
+
+        view.update(N_KEY).unwrap();
+        assert_eq!(selected_text(&view), "      \"B=2\"");
+        view.update(N_KEY).unwrap();
+        assert_eq!(selected_text(&view), "      \"A=1\",");
+        view.update(SHIFT_N_KEY).unwrap();
+        assert_eq!(selected_text(&view), "      \"B=2\"");
+    }
+
+    #[test]
+    fn test_search_unfolds_matches() {
+        let mut view = view();
+        view.update(DOWN_KEY).unwrap();
+        view.update(SPACE_BAR).unwrap();
+        assert!(view.folded.contains("/Config"));
+
+        search(&mut view, "b=2");
+        assert!(view.folded.is_empty());
+        assert_eq!(selected_text(&view), "      \"B=2\"");
+    }
+
+    #[test]
+    fn test_search_without_matches() {
+        let mut view = view();
+        view.update(DOWN_KEY).unwrap();
+        search(&mut view, "missing");
+
+        assert_eq!(view.match_count, 0);
+        assert_eq!(selected_text(&view), "  \"Config\": {");
+    }
+}
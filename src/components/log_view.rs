@@ -0,0 +1,144 @@
+use std::sync::{Arc, Mutex};
+
+use ansi_to_tui::IntoText;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Text,
+    widgets::{List, ListState},
+};
+
+use crate::{
+    events::{Key, message::MessageResponse},
+    traits::Component,
+};
+
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const SPACE_BAR: Key = Key::Char(' ');
+
+/// Scrollable list of streamed output lines, which may contain ANSI escape
+/// codes.  The latest line is followed while auto-scroll is enabled; scrolling
+/// disables it and the space bar re-enables it.
+#[derive(Debug)]
+pub struct LogView {
+    lines: Arc<Mutex<Vec<String>>>,
+    list_state: ListState,
+    auto_scroll: bool,
+    height: u16,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(vec![])),
+            list_state: ListState::default(),
+            auto_scroll: true,
+            height: 0,
+        }
+    }
+}
+
+impl LogView {
+    /// The lines shown, shared so that they can be appended to by a background task
+    pub fn lines(&self) -> Arc<Mutex<Vec<String>>> {
+        self.lines.clone()
+    }
+
+    /// Replaces the lines shown with a new empty set, so that any task still
+    /// appending to the old lines no longer affects the view
+    pub fn reset(&mut self) {
+        self.lines = Arc::new(Mutex::new(vec![]));
+        self.list_state = ListState::default();
+        self.auto_scroll = true;
+    }
+
+    pub fn auto_scroll(&self) -> bool {
+        self.auto_scroll
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let len = self.lines.lock().unwrap().len();
+        if len == 0 {
+            self.list_state.select(Some(0));
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = (current + amount).min(len - 1);
+        self.list_state.select(Some(next));
+        self.auto_scroll = false;
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = current.saturating_sub(amount);
+        self.list_state.select(Some(next));
+        self.auto_scroll = false;
+    }
+
+    pub fn update(&mut self, message: Key) -> MessageResponse {
+        let res = match message {
+            G_KEY => {
+                self.list_state.select_first();
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state.select_last();
+                MessageResponse::Consumed
+            }
+            J_KEY | DOWN_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.height.into());
+                MessageResponse::Consumed
+            }
+            K_KEY | UP_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.height.into());
+                MessageResponse::Consumed
+            }
+            SPACE_BAR => {
+                self.auto_scroll = true;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+
+        if self.auto_scroll {
+            self.list_state.select_last();
+        }
+        res
+    }
+}
+
+impl Component for LogView {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        self.height = area.height.saturating_sub(1);
+        let lines: Vec<Text> = self
+            .lines
+            .lock()
+            .unwrap()
+            .clone()
+            .into_iter()
+            .map(|s| s.into_text().unwrap())
+            .collect();
+        let mut list = List::new(lines);
+
+        if !self.auto_scroll {
+            list = list.highlight_symbol("> ");
+        }
+
+        f.render_stateful_widget(list, area, &mut self.list_state)
+    }
+}
@@ -2,8 +2,12 @@ pub mod alert_modal;
 pub mod boolean_modal;
 pub mod command_input;
 pub mod footer;
+pub mod form_modal;
 pub mod header;
 pub mod help;
+pub mod inspect_view;
+pub mod log_view;
 pub mod resize_notice;
+pub mod select_modal;
 pub mod text_input_wrapper;
 pub mod version;
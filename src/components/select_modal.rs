@@ -0,0 +1,229 @@
+use std::fmt::Debug;
+
+use color_eyre::eyre::Result;
+use itertools::Itertools;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph, Wrap},
+};
+use ratatui_macros::{horizontal, vertical};
+
+use crate::{
+    events::{Key, message::MessageResponse},
+    traits::{Component, ModalComponent},
+};
+
+const WIDTH: u16 = 60;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub enum ModalState {
+    #[default]
+    Closed,
+    Open(String),
+}
+
+/// Modal which allows the user to pick a single value from a list of options.
+///
+/// Unlike the `BooleanModal` no callback is invoked; once the user has made a
+/// choice the modal closes and the value can be retrieved with `take_selected`.
+#[derive(Debug)]
+pub struct SelectModal<P, T> {
+    pub discriminator: P,
+    pub state: ModalState,
+    title: String,
+    options: Vec<(String, T)>,
+    list_state: ListState,
+    selected: Option<T>,
+}
+
+impl<P, T> SelectModal<P, T>
+where
+    T: Clone,
+{
+    pub fn new(title: String, discriminator: P) -> Self {
+        Self {
+            discriminator,
+            state: ModalState::default(),
+            title,
+            options: vec![],
+            list_state: ListState::default(),
+            selected: None,
+        }
+    }
+
+    /// Opens the modal with the given message and set of `(label, value)` options
+    pub fn initialise(&mut self, message: String, options: Vec<(String, T)>) {
+        self.options = options;
+        self.list_state.select(Some(0));
+        self.selected = None;
+        self.state = ModalState::Open(message)
+    }
+
+    pub fn reset(&mut self) {
+        self.options = vec![];
+        self.state = ModalState::Closed
+    }
+
+    /// Returns the value chosen by the user, if one has been chosen since the
+    /// last call
+    pub fn take_selected(&mut self) -> Option<T> {
+        self.selected.take()
+    }
+}
+
+#[async_trait::async_trait]
+impl<P, T> ModalComponent for SelectModal<P, T>
+where
+    P: Debug + Send,
+    T: Debug + Clone + Send,
+{
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        match message {
+            Key::Esc => {
+                self.reset();
+                Ok(MessageResponse::Consumed)
+            }
+            Key::Up | Key::Char('k') => {
+                self.list_state.select_previous();
+                Ok(MessageResponse::Consumed)
+            }
+            Key::Down | Key::Char('j') => {
+                let last = self.options.len().saturating_sub(1);
+                let next = self.list_state.selected().map_or(0, |i| (i + 1).min(last));
+                self.list_state.select(Some(next));
+                Ok(MessageResponse::Consumed)
+            }
+            Key::Enter => {
+                if let Some(idx) = self.list_state.selected() {
+                    self.selected = self.options.get(idx).map(|(_, v)| v.clone());
+                }
+                self.reset();
+                Ok(MessageResponse::Consumed)
+            }
+            // We don't want Q to be able to quit here
+            Key::Char('Q') | Key::Char('q') => Ok(MessageResponse::Consumed),
+            _ => Ok(MessageResponse::NotConsumed),
+        }
+    }
+}
+
+impl<P, T> Component for SelectModal<P, T>
+where
+    P: Debug,
+    T: Debug,
+{
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let message: String = match &self.state {
+            ModalState::Open(v) => v.clone(),
+            _ => return,
+        };
+
+        let height = self.options.len() as u16 + 7;
+
+        let [_, area, _] = horizontal![>=0, ==WIDTH, >=0].areas(area);
+        let [_, area, _] = vertical![>=0, ==height, >=0].areas(area);
+
+        let title = Line::from(format!("< {} >", self.title.clone())).centered();
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+
+        let [_, prompt_area, _, list_area, opts_area] =
+            vertical![==1, ==1, ==1, >=0, ==1].areas(inner);
+
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let prompt = Paragraph::new(message).wrap(Wrap { trim: true }).centered();
+        f.render_widget(prompt, prompt_area);
+
+        let list = List::new(self.options.iter().map(|(label, _)| label.clone()))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        let [_, list_area, _] = horizontal![==2, >=0, ==2].areas(list_area);
+        f.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let spans = [("Enter", "Select"), ("Esc", "Cancel")]
+            .iter()
+            .flat_map(|(key, desc)| {
+                let key = Span::styled(
+                    format!(" <{key}> = "),
+                    Style::new().add_modifier(Modifier::ITALIC),
+                );
+                let desc = Span::styled(
+                    format!("{desc} "),
+                    Style::new().add_modifier(Modifier::ITALIC),
+                );
+                [key, desc]
+            })
+            .collect_vec();
+        f.render_widget(Line::from(spans).centered(), opts_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal_modal() -> SelectModal<(), String> {
+        let mut modal = SelectModal::new("Kill".into(), ());
+        let options = ["SIGKILL", "SIGTERM", "SIGINT"]
+            .iter()
+            .map(|s| (s.to_string(), s.to_string()))
+            .collect();
+        modal.initialise("Select a signal".into(), options);
+        modal
+    }
+
+    #[tokio::test]
+    async fn test_enter_selects_first_option_by_default() {
+        let mut modal = signal_modal();
+        modal.update(Key::Enter).await.unwrap();
+
+        assert_eq!(modal.state, ModalState::Closed);
+        assert_eq!(modal.take_selected(), Some("SIGKILL".to_string()));
+        assert_eq!(modal.take_selected(), None);
+    }
+
+    #[tokio::test]
+    async fn test_navigation_is_clamped() {
+        let mut modal = signal_modal();
+        for _ in 0..5 {
+            modal.update(Key::Down).await.unwrap();
+        }
+        modal.update(Key::Enter).await.unwrap();
+        assert_eq!(modal.take_selected(), Some("SIGINT".to_string()));
+
+        let mut modal = signal_modal();
+        modal.update(Key::Char('j')).await.unwrap();
+        modal.update(Key::Char('k')).await.unwrap();
+        modal.update(Key::Up).await.unwrap();
+        modal.update(Key::Enter).await.unwrap();
+        assert_eq!(modal.take_selected(), Some("SIGKILL".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_escape_selects_nothing() {
+        let mut modal = signal_modal();
+        modal.update(Key::Down).await.unwrap();
+        modal.update(Key::Esc).await.unwrap();
+
+        assert_eq!(modal.state, ModalState::Closed);
+        assert_eq!(modal.take_selected(), None);
+    }
+
+    #[tokio::test]
+    async fn test_quit_keys_are_consumed() {
+        let mut modal = signal_modal();
+        let res = modal.update(Key::Char('q')).await.unwrap();
+
+        assert_eq!(res, MessageResponse::Consumed);
+        assert!(matches!(modal.state, ModalState::Open(_)));
+        assert_eq!(
+            modal.update(Key::Char('x')).await.unwrap(),
+            MessageResponse::NotConsumed
+        );
+    }
+}
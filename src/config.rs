@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 
 use color_eyre::eyre::{Context, Result, bail};
 
+use crate::events::key::parse_key_sequence;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
@@ -16,6 +18,12 @@ pub struct Config {
     #[serde(default = "default_exec")]
     pub default_exec: String,
 
+    #[serde(default = "default_exec_escape_keys")]
+    pub exec_escape_keys: String,
+
+    #[serde(default = "default_attach_detach_keys")]
+    pub attach_detach_keys: String,
+
     #[serde(default = "default_docker_path")]
     pub docker_path: String,
 
@@ -51,6 +59,9 @@ impl Config {
             config = Config::default()
         }
 
+        parse_key_sequence(&config.exec_escape_keys).context("invalid exec_escape_keys")?;
+        parse_key_sequence(&config.attach_detach_keys).context("invalid attach_detach_keys")?;
+
         if let Some(p) = docker_path {
             config.docker_path = p;
         }
@@ -71,6 +82,14 @@ fn default_exec() -> String {
     "/bin/bash".into()
 }
 
+fn default_exec_escape_keys() -> String {
+    "ctrl-p,ctrl-q".into()
+}
+
+fn default_attach_detach_keys() -> String {
+    "ctrl-p,ctrl-q".into()
+}
+
 fn default_docker_path() -> String {
     #[cfg(unix)]
     return "unix:///var/run/docker.sock".into();
@@ -96,6 +115,8 @@ impl Default for Config {
         Self {
             prompt: default_prompt(),
             default_exec: default_exec(),
+            exec_escape_keys: default_exec_escape_keys(),
+            attach_detach_keys: default_attach_detach_keys(),
             docker_path: default_docker_path(),
             docker_host: None,
             check_for_update: default_check_update(),
@@ -134,6 +155,9 @@ pub struct Theme {
 
     #[serde(default = "default_negative_highlight_colour")]
     negative_highlight: Color,
+
+    #[serde(default = "default_paused_highlight_colour")]
+    paused_highlight: Color,
 }
 
 impl Theme {
@@ -193,6 +217,13 @@ impl Theme {
             Color::Magenta
         }
     }
+    pub fn paused_highlight(&self) -> Color {
+        if self.use_theme {
+            self.paused_highlight
+        } else {
+            Color::Yellow
+        }
+    }
 }
 
 fn default_title_colour() -> Color {
@@ -219,6 +250,9 @@ fn default_positive_highlight_colour() -> Color {
 fn default_negative_highlight_colour() -> Color {
     Color::from_str("#ff00aa").unwrap()
 }
+fn default_paused_highlight_colour() -> Color {
+    Color::from_str("#ffd866").unwrap()
+}
 
 impl Default for Theme {
     fn default() -> Self {
@@ -232,6 +266,7 @@ impl Default for Theme {
             error: default_error_colour(),
             positive_highlight: default_positive_highlight_colour(),
             negative_highlight: default_negative_highlight_colour(),
+            paused_highlight: default_paused_highlight_colour(),
         }
     }
 }
@@ -20,6 +20,8 @@ pub struct AppContext {
     pub docker_volume: Option<DockerVolume>,
     pub docker_network: Option<DockerNetwork>,
     pub describable: Option<Box<dyn Describe>>,
+    pub image_reference: Option<String>,
+    pub build_context: Option<String>,
 }
 
 impl AppContext {
@@ -42,27 +44,22 @@ impl PartialEq for AppContext {
             return false;
         }
 
-        // Describe doesn't have derived PartialEqual trait
-        // We can assume that if both offer the same description,
-        // then they are equal
-        if (self.describable.is_some() && other.describable.is_none())
-            || (self.describable.is_none() && other.describable.is_some())
-        {
+        if self.image_reference != other.image_reference {
             return false;
         }
 
-        if self.describable.is_some() && other.describable.is_some() {
-            match (
-                self.describable.clone().unwrap().describe(),
-                other.describable.clone().unwrap().describe(),
-            ) {
-                (Ok(s), Ok(o)) => return s == o,
-                (Err(s), Err(o)) => return format!("{s}") == format!("{o}"),
-                (_, _) => return false,
-            }
+        if self.build_context != other.build_context {
+            return false;
         }
 
-        true
+        // Describe doesn't have derived PartialEqual trait
+        // We can assume that if both describe the same resource,
+        // then they are equal
+        match (&self.describable, &other.describable) {
+            (Some(s), Some(o)) => s.get_id() == o.get_id() && s.get_name() == o.get_name(),
+            (None, None) => true,
+            (_, _) => false,
+        }
     }
 }
 
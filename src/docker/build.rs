@@ -0,0 +1,474 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bollard::query_parameters::{BuildImageOptions, BuildImageOptionsBuilder};
+use bollard::secret::BuildInfo;
+use color_eyre::eyre::{Context, Result, bail};
+use futures::{Stream, StreamExt};
+
+use super::files::expand_local_path;
+use super::registry::validate_reference;
+use super::util::parse_key_value_map;
+
+pub const DEFAULT_DOCKERFILE: &str = "Dockerfile";
+
+const DOCKERIGNORE: &str = ".dockerignore";
+
+/// Configuration used to build an image from a local directory, mirroring the
+/// most commonly used flags of `docker build`.
+///
+/// Build args are provided as a comma separated list, eg `VERSION=1.2,DEBUG=1`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageBuildConfig {
+    pub context: PathBuf,
+    /// Path of the Dockerfile within the context
+    pub dockerfile: Option<String>,
+    pub tag: Option<String>,
+    pub build_args: Option<String>,
+    pub target: Option<String>,
+    pub no_cache: bool,
+}
+
+impl ImageBuildConfig {
+    pub fn dockerfile(&self) -> &str {
+        self.dockerfile.as_deref().unwrap_or(DEFAULT_DOCKERFILE)
+    }
+
+    /// Builds the options for bollard's build_image, validating any user
+    /// provided values along the way
+    pub fn to_options(&self) -> Result<BuildImageOptions> {
+        let mut builder = BuildImageOptionsBuilder::default()
+            .dockerfile(self.dockerfile())
+            .nocache(self.no_cache)
+            .rm(true);
+
+        if let Some(tag) = &self.tag {
+            validate_reference(tag)?;
+            builder = builder.t(tag);
+        }
+        if let Some(build_args) = &self.build_args {
+            builder = builder.buildargs(&parse_key_value_map(build_args)?);
+        }
+        if let Some(target) = &self.target {
+            builder = builder.target(target);
+        }
+        Ok(builder.build())
+    }
+
+    /// Creates a tar archive of the build context, leaving out any paths
+    /// excluded by its `.dockerignore`.  As with the docker cli, the
+    /// Dockerfile and `.dockerignore` are always sent.
+    pub fn archive_context(&self) -> Result<Vec<u8>> {
+        let dockerfile = clean_path(self.dockerfile());
+        if !self.context.join(&dockerfile).is_file() {
+            bail!(
+                "{} not found in {}",
+                self.dockerfile(),
+                self.context.display()
+            );
+        }
+
+        let ignore = DockerIgnore::load(&self.context)?;
+        let mut archive = ContextArchive {
+            root: &self.context,
+            ignore,
+            always_include: vec![dockerfile, DOCKERIGNORE.to_string()],
+            builder: tar::Builder::new(vec![]),
+        };
+        archive.builder.follow_symlinks(false);
+        archive.add_dir("")?;
+
+        archive
+            .builder
+            .into_inner()
+            .with_context(|| format!("unable to archive {}", self.context.display()))
+    }
+}
+
+/// Builds an image from an archived build context, returning a stream of the
+/// daemon's output
+pub fn build(
+    docker: &bollard::Docker,
+    options: BuildImageOptions,
+    context: Vec<u8>,
+) -> impl Stream<Item = Result<BuildInfo>> + '_ {
+    docker
+        .build_image(options, None, Some(bollard::body_full(context.into())))
+        .map(|info| info.context("unable to build image"))
+}
+
+/// Converts a message from the daemon into the lines of output it contains,
+/// along with the id of the image built once the build has completed
+pub fn parse_output(info: BuildInfo) -> Result<(Vec<String>, Option<String>)> {
+    if let Some(detail) = info.error_detail {
+        bail!("{}", detail.message.unwrap_or_default());
+    }
+
+    let mut lines = vec![];
+    if let Some(stream) = info.stream {
+        lines.extend(stream.trim_end_matches('\n').split('\n').map(String::from));
+    }
+    // Download progress of base images is reported many times a second, so
+    // only the changes of status are shown
+    let downloading = info
+        .progress_detail
+        .is_some_and(|d| d.total.is_some_and(|t| t > 0));
+    if let Some(status) = info.status.filter(|_| !downloading) {
+        match info.id {
+            Some(id) => lines.push(format!("{id}: {status}")),
+            None => lines.push(status),
+        }
+    }
+
+    Ok((lines, info.aux.and_then(|aux| aux.id)))
+}
+
+/// Validates a local directory to be used as a build context
+pub fn validate_context(path: &str) -> Result<()> {
+    if path.trim().is_empty() {
+        bail!("a path is required");
+    }
+    let path = expand_local_path(path.trim());
+    if !path.is_dir() {
+        bail!("{} is not a directory", path.display());
+    }
+    Ok(())
+}
+
+/// Validates an optional image reference to tag the built image with
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.trim().is_empty() {
+        return Ok(());
+    }
+    validate_reference(tag)
+}
+
+/// Validates a comma separated list of build args in the form `KEY=value`
+pub fn validate_build_args(build_args: &str) -> Result<()> {
+    parse_key_value_map(build_args).map(|_| ())
+}
+
+/// Normalises a path within the build context to `/` separated components
+/// without any leading `/` or `.` components, as used by `.dockerignore`
+fn clean_path(path: &str) -> String {
+    path.split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+struct ContextArchive<'a> {
+    root: &'a Path,
+    ignore: DockerIgnore,
+    always_include: Vec<String>,
+    builder: tar::Builder<Vec<u8>>,
+}
+
+impl ContextArchive<'_> {
+    fn is_excluded(&self, path: &str) -> bool {
+        self.ignore.is_excluded(path) && !self.always_include.iter().any(|p| p == path)
+    }
+
+    /// Whether an excluded directory may contain paths which are included,
+    /// and so must still be walked
+    fn may_include_children(&self, dir: &str) -> bool {
+        self.ignore.has_exceptions()
+            || self
+                .always_include
+                .iter()
+                .any(|p| p.starts_with(&format!("{dir}/")))
+    }
+
+    fn add_dir(&mut self, dir: &str) -> Result<()> {
+        let full_dir = self.root.join(dir);
+        let mut entries = fs::read_dir(&full_dir)
+            .with_context(|| format!("unable to read {}", full_dir.display()))?
+            .collect::<std::io::Result<Vec<_>>>()
+            .with_context(|| format!("unable to read {}", full_dir.display()))?;
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = if dir.is_empty() {
+                name
+            } else {
+                format!("{dir}/{name}")
+            };
+            let excluded = self.is_excluded(&path);
+            let file_type = entry
+                .file_type()
+                .with_context(|| format!("unable to read {}", entry.path().display()))?;
+
+            if file_type.is_dir() {
+                if excluded && !self.may_include_children(&path) {
+                    continue;
+                }
+                if !excluded {
+                    self.builder
+                        .append_dir(&path, entry.path())
+                        .with_context(|| format!("unable to archive {path}"))?;
+                }
+                self.add_dir(&path)?;
+            } else if !excluded {
+                self.builder
+                    .append_path_with_name(entry.path(), &path)
+                    .with_context(|| format!("unable to archive {path}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IgnoreRule {
+    segments: Vec<String>,
+    /// Rules starting with `!` re-include paths excluded by earlier rules
+    exception: bool,
+}
+
+/// The patterns of a `.dockerignore` file, which follow Go's `filepath.Match`
+/// rules with the addition of `**` to match any number of directories
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DockerIgnore {
+    rules: Vec<IgnoreRule>,
+}
+
+impl DockerIgnore {
+    fn load(context: &Path) -> Result<Self> {
+        let path = context.join(DOCKERIGNORE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("unable to read {}", path.display()))?;
+        Ok(Self::parse(&contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|line| {
+                let (pattern, exception) = match line.strip_prefix('!') {
+                    Some(pattern) => (pattern.trim(), true),
+                    None => (line, false),
+                };
+                let segments = clean_path(pattern)
+                    .split('/')
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                (!segments.is_empty()).then_some(IgnoreRule {
+                    segments,
+                    exception,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    fn has_exceptions(&self) -> bool {
+        self.rules.iter().any(|r| r.exception)
+    }
+
+    /// Whether a path is excluded; a rule matching any of the path's parent
+    /// directories also applies to the path, and the last matching rule wins
+    fn is_excluded(&self, path: &str) -> bool {
+        let segments = path.split('/').collect::<Vec<_>>();
+        let mut excluded = false;
+        for rule in &self.rules {
+            let matched =
+                (1..=segments.len()).any(|n| match_segments(&rule.segments, &segments[..n]));
+            if matched {
+                excluded = !rule.exception;
+            }
+        }
+        excluded
+    }
+}
+
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                let pattern = first.chars().collect::<Vec<_>>();
+                let name = name.chars().collect::<Vec<_>>();
+                match_glob(&pattern, &name) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Matches a single path component against a pattern supporting `*`, `?`,
+/// character classes such as `[a-z]` and `\` escapes
+fn match_glob(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_glob(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_glob(rest, &name[1..]),
+        Some(('[', rest)) => {
+            let Some((c, name_rest)) = name.split_first() else {
+                return false;
+            };
+            match match_class(rest, *c) {
+                Some((true, pattern_rest)) => match_glob(pattern_rest, name_rest),
+                _ => false,
+            }
+        }
+        Some(('\\', rest)) if !rest.is_empty() => {
+            name.first() == Some(&rest[0]) && match_glob(&rest[1..], &name[1..])
+        }
+        Some((p, rest)) => name.first() == Some(p) && match_glob(rest, &name[1..]),
+    }
+}
+
+/// Matches a character against the class at the start of the pattern (after
+/// the opening `[`), returning whether it matched and the rest of the pattern
+fn match_class(pattern: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, mut pattern) = match pattern.split_first() {
+        Some(('^' | '!', rest)) => (true, rest),
+        _ => (false, pattern),
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match pattern {
+            [] => return None,
+            [']', rest @ ..] if !first => return Some((matched != negated, rest)),
+            [low, '-', high, rest @ ..] if *high != ']' => {
+                matched |= (*low..=*high).contains(&c);
+                pattern = rest;
+            }
+            [x, rest @ ..] => {
+                matched |= *x == c;
+                pattern = rest;
+            }
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, name: &str) -> bool {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        let name = name.chars().collect::<Vec<_>>();
+        match_glob(&pattern, &name)
+    }
+
+    #[test]
+    fn test_match_glob() {
+        assert!(glob("main.rs", "main.rs"));
+        assert!(!glob("main.rs", "main.rsx"));
+        assert!(glob("*.rs", "main.rs"));
+        assert!(glob("*", ""));
+        assert!(glob("*.*", "a.b"));
+        assert!(!glob("*.rs", "main.go"));
+        assert!(glob("?.log", "a.log"));
+        assert!(!glob("?.log", ".log"));
+        assert!(!glob("?.log", "ab.log"));
+    }
+
+    #[test]
+    fn test_match_glob_escapes() {
+        assert!(glob("\\*.rs", "*.rs"));
+        assert!(!glob("\\*.rs", "main.rs"));
+        assert!(glob("a\\?", "a?"));
+        assert!(!glob("a\\?", "ab"));
+    }
+
+    #[test]
+    fn test_match_class() {
+        assert!(glob("[abc].txt", "b.txt"));
+        assert!(!glob("[abc].txt", "d.txt"));
+        assert!(glob("file[0-9]", "file7"));
+        assert!(!glob("file[0-9]", "filex"));
+        assert!(glob("[^0-9]x", "ax"));
+        assert!(glob("[!0-9]x", "ax"));
+        assert!(!glob("[^0-9]x", "1x"));
+        // A `]` straight after the opening bracket is part of the class
+        assert!(glob("[]a]", "]"));
+        // A `-` before the closing bracket is literal
+        assert!(glob("[a-]", "-"));
+        // An unterminated class never matches
+        assert!(!glob("[abc", "a"));
+        assert_eq!(match_class(&['a', ']', 'x'], 'a'), Some((true, &['x'][..])));
+    }
+
+    #[test]
+    fn test_match_segments() {
+        let pattern = |p: &str| p.split('/').map(String::from).collect::<Vec<_>>();
+        assert!(match_segments(&pattern("src/*.rs"), &["src", "main.rs"]));
+        assert!(!match_segments(
+            &pattern("src/*.rs"),
+            &["src", "bin", "main.rs"]
+        ));
+        assert!(match_segments(&pattern("**/*.rs"), &["main.rs"]));
+        assert!(match_segments(
+            &pattern("**/*.rs"),
+            &["src", "bin", "main.rs"]
+        ));
+        assert!(match_segments(&pattern("src/**/test"), &["src", "test"]));
+        assert!(match_segments(
+            &pattern("src/**/test"),
+            &["src", "a", "b", "test"]
+        ));
+        assert!(!match_segments(&pattern("src/**/test"), &["lib", "test"]));
+        assert!(match_segments(&pattern("**"), &["any", "path"]));
+    }
+
+    #[test]
+    fn test_docker_ignore_parse() {
+        let ignore = DockerIgnore::parse("# comment\n\n ./target/ \n!target/keep\n/\n");
+        assert_eq!(
+            ignore.rules,
+            vec![
+                IgnoreRule {
+                    segments: vec!["target".into()],
+                    exception: false,
+                },
+                IgnoreRule {
+                    segments: vec!["target".into(), "keep".into()],
+                    exception: true,
+                },
+            ]
+        );
+        assert!(ignore.has_exceptions());
+    }
+
+    #[test]
+    fn test_docker_ignore_is_excluded() {
+        let ignore = DockerIgnore::parse("target\n*.log\n**/node_modules\n");
+        assert!(ignore.is_excluded("target"));
+        // Rules matching a parent directory apply to everything within it
+        assert!(ignore.is_excluded("target/debug/ducker"));
+        assert!(ignore.is_excluded("build.log"));
+        // Patterns are anchored to the root of the context
+        assert!(!ignore.is_excluded("logs/build.log"));
+        assert!(ignore.is_excluded("node_modules/lib.js"));
+        assert!(ignore.is_excluded("web/node_modules/lib.js"));
+        assert!(!ignore.is_excluded("src/main.rs"));
+    }
+
+    #[test]
+    fn test_docker_ignore_exceptions() {
+        let ignore = DockerIgnore::parse("*.md\n!README.md\ndocs\n!docs/index.md\n");
+        assert!(ignore.is_excluded("CHANGELOG.md"));
+        assert!(!ignore.is_excluded("README.md"));
+        assert!(ignore.is_excluded("docs/guide.md"));
+        assert!(!ignore.is_excluded("docs/index.md"));
+
+        // The last matching rule wins
+        let ignore = DockerIgnore::parse("!README.md\n*.md\n");
+        assert!(ignore.is_excluded("README.md"));
+    }
+}
@@ -0,0 +1,74 @@
+use bollard::secret::ChangeType;
+use color_eyre::eyre::{Context, Result};
+
+use super::container::DockerContainer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 3] = [Self::Added, Self::Modified, Self::Deleted];
+
+    fn from(kind: ChangeType) -> Self {
+        match kind {
+            ChangeType::_0 => Self::Modified,
+            ChangeType::_1 => Self::Added,
+            ChangeType::_2 => Self::Deleted,
+        }
+    }
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Added => "Added",
+            Self::Modified => "Modified",
+            Self::Deleted => "Deleted",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A change made to a container's filesystem relative to its image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+impl FileChange {
+    /// Lists the files and directories which have been added, modified or
+    /// deleted in the container since it was created, sorted by path
+    pub async fn list(docker: &bollard::Docker, container: &DockerContainer) -> Result<Vec<Self>> {
+        let mut changes: Vec<Self> = docker
+            .container_changes(&container.id)
+            .await
+            .with_context(|| format!("unable to retrieve changes for {}", container.names))?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| Self {
+                path: c.path,
+                kind: ChangeKind::from(c.kind),
+            })
+            .collect();
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_kind_from() {
+        assert_eq!(ChangeKind::from(ChangeType::_0), ChangeKind::Modified);
+        assert_eq!(ChangeKind::from(ChangeType::_1), ChangeKind::Added);
+        assert_eq!(ChangeKind::from(ChangeType::_2), ChangeKind::Deleted);
+    }
+}
@@ -0,0 +1,243 @@
+use bollard::query_parameters::{CommitContainerOptions, CommitContainerOptionsBuilder};
+use color_eyre::eyre::{Result, bail};
+
+pub const DEFAULT_TAG: &str = "latest";
+
+/// Dockerfile instructions which the daemon allows to be applied when
+/// committing a container
+const COMMIT_INSTRUCTIONS: [&str; 9] = [
+    "CMD",
+    "ENTRYPOINT",
+    "ENV",
+    "EXPOSE",
+    "LABEL",
+    "ONBUILD",
+    "USER",
+    "VOLUME",
+    "WORKDIR",
+];
+
+/// Configuration used to commit a container to a new image, mirroring the
+/// flags of `docker commit`.
+///
+/// Changes are provided as a `;` separated list of Dockerfile instructions,
+/// eg `CMD ["nginx", "-g", "daemon off;"]; EXPOSE 80`.  A `;` which isn't
+/// followed by an instruction is treated as part of the previous instruction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerCommitConfig {
+    pub repository: String,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    pub message: Option<String>,
+    pub pause: bool,
+    pub changes: Option<String>,
+}
+
+impl ContainerCommitConfig {
+    /// The reference the new image will be tagged with
+    pub fn reference(&self) -> String {
+        format!(
+            "{}:{}",
+            self.repository,
+            self.tag.as_deref().unwrap_or(DEFAULT_TAG)
+        )
+    }
+
+    /// Builds the options for bollard's commit_container, validating any user
+    /// provided values along the way
+    pub fn to_options(&self, container_id: &str) -> Result<CommitContainerOptions> {
+        validate_repository(&self.repository)?;
+
+        let tag = self.tag.as_deref().unwrap_or(DEFAULT_TAG);
+        validate_tag(tag)?;
+
+        let mut builder = CommitContainerOptionsBuilder::default()
+            .container(container_id)
+            .repo(&self.repository)
+            .tag(tag)
+            .pause(self.pause);
+
+        if let Some(author) = &self.author {
+            builder = builder.author(author);
+        }
+        if let Some(message) = &self.message {
+            builder = builder.comment(message);
+        }
+        if let Some(changes) = &self.changes {
+            // The daemon parses the changes as the lines of a Dockerfile
+            builder = builder.changes(&parse_changes(changes)?.join("\n"));
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// Validates an image repository, eg `my-registry:5000/team/app`
+pub fn validate_repository(repository: &str) -> Result<()> {
+    if repository.is_empty() {
+        bail!("a repository is required");
+    }
+    if let Some(c) = repository
+        .chars()
+        .find(|c| !matches!(c, 'a'..='z' | '0'..='9' | '.' | '_' | '-' | '/' | ':'))
+    {
+        bail!("invalid character \"{c}\"; repositories must be lowercase");
+    }
+    if repository.split('/').any(str::is_empty) {
+        bail!("repositories may not have empty path components");
+    }
+    // A colon is only valid before a registry's port, eg `localhost:5000/app`;
+    // elsewhere it would be read as the start of a tag
+    let path = match repository.split_once('/') {
+        Some((registry, path)) => {
+            if let Some((_, port)) = registry.split_once(':')
+                && (port.is_empty() || !port.chars().all(|c| c.is_ascii_digit()))
+            {
+                bail!("invalid registry port \"{port}\"");
+            }
+            path
+        }
+        None => repository,
+    };
+    if path.contains(':') {
+        bail!("repositories may not include a tag; enter it separately");
+    }
+    Ok(())
+}
+
+/// Validates an image tag, which may contain up to 128 letters, digits,
+/// underscores, periods and dashes
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.len() > 128 {
+        bail!("tags may be at most 128 characters");
+    }
+    if tag.starts_with(['.', '-']) {
+        bail!("tags may not start with a period or dash");
+    }
+    if let Some(c) = tag
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')))
+    {
+        bail!("invalid character \"{c}\"");
+    }
+    Ok(())
+}
+
+/// Validates a `;` separated list of Dockerfile instructions
+pub fn validate_changes(changes: &str) -> Result<()> {
+    parse_changes(changes).map(|_| ())
+}
+
+fn parse_changes(changes: &str) -> Result<Vec<String>> {
+    let mut parsed: Vec<String> = vec![];
+
+    for part in changes.split(';') {
+        if part.trim().is_empty() {
+            continue;
+        }
+        let instruction = part
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+
+        if COMMIT_INSTRUCTIONS.contains(&instruction.as_str()) {
+            parsed.push(part.trim().to_string());
+        } else if let Some(previous) = parsed.last_mut() {
+            // Not the start of an instruction, so the `;` was part of the
+            // previous one, eg `CMD ["sh", "-c", "a; b"]`
+            previous.push(';');
+            previous.push_str(part);
+        } else {
+            bail!(
+                "unsupported instruction \"{instruction}\", expected one of {}",
+                COMMIT_INSTRUCTIONS.join(", ")
+            );
+        }
+    }
+
+    for change in &parsed {
+        let mut words = change.split_whitespace();
+        if let (Some(instruction), None) = (words.next(), words.next()) {
+            bail!("{} requires an argument", instruction.to_uppercase());
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_repository() {
+        for repository in [
+            "app",
+            "team/app",
+            "my-registry:5000/team/app",
+            "localhost:5000/app",
+            "registry.example.com/a_b/c.d-e",
+        ] {
+            assert!(
+                validate_repository(repository).is_ok(),
+                "{repository} should be valid"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_repository_invalid() {
+        for repository in [
+            "",
+            "App",
+            "app:1.0",
+            "team/app:1.0",
+            "localhost:5000/app:1.0",
+            "team:x/app",
+            "team//app",
+            "/app",
+            "app/",
+            "app@sha256",
+        ] {
+            assert!(
+                validate_repository(repository).is_err(),
+                "{repository} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_tag() {
+        assert!(validate_tag("1.27-alpine_3").is_ok());
+        assert!(validate_tag(&"a".repeat(128)).is_ok());
+        assert!(validate_tag(&"a".repeat(129)).is_err());
+        for tag in [".1", "-1", "1:2", "a/b"] {
+            assert!(validate_tag(tag).is_err(), "{tag} should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_parse_changes() {
+        assert_eq!(
+            parse_changes("cmd [\"nginx\"]; EXPOSE 80 ;; env A=1").unwrap(),
+            vec!["cmd [\"nginx\"]", "EXPOSE 80", "env A=1"]
+        );
+    }
+
+    #[test]
+    fn test_parse_changes_keeps_semicolons_within_instructions() {
+        assert_eq!(
+            parse_changes(r#"CMD ["sh", "-c", "a; b;c"]; USER nobody"#).unwrap(),
+            vec![r#"CMD ["sh", "-c", "a; b;c"]"#, "USER nobody"]
+        );
+    }
+
+    #[test]
+    fn test_parse_changes_invalid() {
+        assert!(parse_changes("RUN make").is_err());
+        assert!(parse_changes("echo; CMD a").is_err());
+        assert!(parse_changes("EXPOSE").is_err());
+        assert!(parse_changes("CMD a; USER ").is_err());
+        assert!(parse_changes("").unwrap().is_empty());
+    }
+}
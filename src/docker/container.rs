@@ -1,21 +1,38 @@
+use bollard::container::LogOutput;
 use bollard::query_parameters::{
-    ListContainersOptionsBuilder, RemoveContainerOptionsBuilder, StartContainerOptions,
-    StopContainerOptions,
+    AttachContainerOptionsBuilder, CreateContainerOptionsBuilder, InspectContainerOptions,
+    InspectContainerOptionsBuilder, KillContainerOptionsBuilder, ListContainersOptionsBuilder,
+    RemoveContainerOptionsBuilder, ResizeContainerTTYOptionsBuilder, RestartContainerOptions,
+    StartContainerOptions, StopContainerOptions,
 };
 use chrono::Local;
 use chrono::prelude::DateTime;
-use color_eyre::eyre::{Context, Result, bail};
+use color_eyre::eyre::{Context, ContextCompat, Result};
+use futures::StreamExt;
 use std::{
     collections::HashMap,
+    path::Path,
     time::{Duration, UNIX_EPOCH},
 };
-use tokio::process::Command;
 
-use bollard::secret::ContainerSummary;
+use bollard::secret::{
+    ContainerConfig, ContainerInspectResponse, ContainerState, ContainerSummary, EndpointSettings,
+    HealthConfig, HostConfig, MountPoint, MountPointTypeEnum, PortMap,
+};
+use tokio::sync::mpsc::Sender;
 
 use crate::docker::traits::DescribeSection;
-
-use super::traits::Describe;
+use crate::events::{Key, Message, Transition};
+
+use super::{
+    commit::ContainerCommitConfig,
+    image::DockerImage,
+    run::ContainerRunConfig,
+    terminal::{TerminalSession, TtyResize},
+    traits::Describe,
+    transfer::{Transfer, write_to_file},
+    util::format_bytes,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct DockerContainer {
@@ -28,10 +45,11 @@ pub struct DockerContainer {
     pub ports: String,
     pub names: String,
     pub running: bool,
-    read_write_size: String,
-    root_fs_size: String,
-    labels: Option<HashMap<String, String>>,
-    network_mode: Option<String>,
+    pub paused: bool,
+    /// Names of the volumes mounted into the container
+    pub volumes: Vec<String>,
+    /// IDs of the networks the container is connected to
+    pub networks: Vec<String>,
 }
 
 impl DockerContainer {
@@ -66,6 +84,8 @@ impl DockerContainer {
         .to_string();
 
         let running = matches!(c.state.as_ref(), Some(state) if state.to_string().to_lowercase() == "running");
+        let paused =
+            matches!(c.state.as_ref(), Some(state) if state.to_string().to_lowercase() == "paused");
 
         let names = c
             .names
@@ -76,20 +96,37 @@ impl DockerContainer {
             .collect::<Vec<String>>()
             .join(", ");
 
+        let volumes = c
+            .mounts
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|m| m.typ == Some(MountPointTypeEnum::VOLUME))
+            .filter_map(|m| m.name)
+            .collect();
+
+        let networks = c
+            .network_settings
+            .clone()
+            .and_then(|n| n.networks)
+            .unwrap_or_default()
+            .into_values()
+            .filter_map(|n| n.network_id)
+            .collect();
+
         Self {
             id: c.id.clone().unwrap_or_default(),
             image: c.image.clone().unwrap_or_default(),
-            image_id: c.image.clone().unwrap_or_default(),
+            image_id: c.image_id.clone().unwrap_or_default(),
             command: c.command.clone().unwrap_or_default(),
             created: datetime,
             status: c.status.clone().unwrap_or_default(),
             ports,
             names,
             running,
-            read_write_size: String::new(),
-            root_fs_size: String::new(),
-            labels: None,
-            network_mode: None,
+            paused,
+            volumes,
+            networks,
         }
     }
 
@@ -110,6 +147,82 @@ impl DockerContainer {
         Ok(containers)
     }
 
+    /// Retrieves a single container by its ID
+    pub async fn get(docker: &bollard::Docker, id: &str) -> Result<Self> {
+        let filters = HashMap::from([("id", vec![id])]);
+        let opts = ListContainersOptionsBuilder::default()
+            .all(true)
+            .filters(&filters)
+            .build();
+        docker
+            .list_containers(Some(opts))
+            .await
+            .context("unable to retrieve container")?
+            .into_iter()
+            .map(Self::from)
+            .next()
+            .with_context(|| format!("no container found with id {id}"))
+    }
+
+    /// Inspects the container, including its size on disk
+    async fn inspect_response(&self, docker: &bollard::Docker) -> Result<ContainerInspectResponse> {
+        let opts = InspectContainerOptionsBuilder::default().size(true).build();
+        docker
+            .inspect_container(&self.id, Some(opts))
+            .await
+            .context("unable to inspect container")
+    }
+
+    /// Creates a new container from the given config and starts it, returning
+    /// the newly created container
+    pub async fn run(docker: &bollard::Docker, config: &ContainerRunConfig) -> Result<Self> {
+        let body = config.to_create_body()?;
+        let opts = config
+            .name
+            .as_ref()
+            .map(|name| CreateContainerOptionsBuilder::default().name(name).build());
+
+        let response = docker
+            .create_container(opts, body)
+            .await
+            .context("unable to create container")?;
+
+        let container = Self::get(docker, &response.id).await?;
+        container.start(docker).await?;
+
+        // Refetch so that the running state is reflected; an auto-removed container
+        // may already have exited and been removed, in which case it is returned as created
+        Ok(Self::get(docker, &response.id).await.unwrap_or(container))
+    }
+
+    /// Commits the container's filesystem and config to a new image, returning
+    /// the newly created image
+    pub async fn commit(
+        &self,
+        docker: &bollard::Docker,
+        config: &ContainerCommitConfig,
+    ) -> Result<DockerImage> {
+        let opts = config.to_options(&self.id)?;
+        docker
+            .commit_container(opts, ContainerConfig::default())
+            .await
+            .context("unable to commit container")?;
+
+        DockerImage::get(docker, &config.reference()).await
+    }
+
+    /// Exports the container's filesystem as a tarball to a local file
+    pub async fn export(
+        &self,
+        docker: &bollard::Docker,
+        path: &Path,
+        transfer: &Transfer,
+    ) -> Result<()> {
+        write_to_file(docker.export_container(&self.id), path, transfer)
+            .await
+            .context("unable to export container")
+    }
+
     /// Delete the container from the relevant docker daemon
     pub async fn delete(&self, docker: &bollard::Docker, force: bool) -> Result<()> {
         let opt = RemoveContainerOptionsBuilder::default()
@@ -140,32 +253,125 @@ impl DockerContainer {
         Ok(())
     }
 
-    /// Exec into the container with the given command
-    pub async fn attach(&self, cmd: &str) -> Result<()> {
-        Command::new("clear").spawn()?.wait().await?;
-
-        let parts: Vec<String> = cmd.split_whitespace().map(String::from).collect();
-
-        let mut command = Command::new("docker");
+    /// Restart the container
+    pub async fn restart(&self, docker: &bollard::Docker) -> Result<()> {
+        let opts = RestartContainerOptions::default();
+        docker
+            .restart_container(&self.id, Some(opts))
+            .await
+            .context("unable to restart container")?;
+        Ok(())
+    }
 
-        let mut arged_commands = command.arg("exec").arg("-it").arg(&self.names);
+    /// Pause all processes within the container
+    pub async fn pause(&self, docker: &bollard::Docker) -> Result<()> {
+        docker
+            .pause_container(&self.id)
+            .await
+            .context("unable to pause container")?;
+        Ok(())
+    }
 
-        for part in parts {
-            arged_commands = arged_commands.arg(part);
-        }
+    /// Resume all processes within a paused container
+    pub async fn unpause(&self, docker: &bollard::Docker) -> Result<()> {
+        docker
+            .unpause_container(&self.id)
+            .await
+            .context("unable to unpause container")?;
+        Ok(())
+    }
 
-        let exit_status = arged_commands.spawn()?.wait().await?;
+    /// Send a signal to the container's main process, eg `SIGKILL`
+    pub async fn kill(&self, docker: &bollard::Docker, signal: &str) -> Result<()> {
+        let opts = KillContainerOptionsBuilder::default()
+            .signal(signal)
+            .build();
+        docker
+            .kill_container(&self.id, Some(opts))
+            .await
+            .with_context(|| format!("unable to send {signal} to container"))?;
+        Ok(())
+    }
 
-        Command::new("clear").spawn()?.wait().await?;
+    /// Attach to the stdin, stdout and stderr of the container's main process, in
+    /// the same way as `docker attach`.
+    ///
+    /// Only output written after attaching is shown, as with `docker attach`.
+    /// The daemon ends the session when it receives the detach key sequence (eg
+    /// `ctrl-p,ctrl-q`), leaving the process running.
+    pub async fn attach(
+        &self,
+        docker: &bollard::Docker,
+        detach_keys: &str,
+        tx: Sender<Message<Key, Transition>>,
+    ) -> Result<TerminalSession> {
+        let tty = docker
+            .inspect_container(&self.id, None::<InspectContainerOptions>)
+            .await
+            .context("unable to inspect container")?
+            .config
+            .and_then(|c| c.tty)
+            .unwrap_or_default();
+
+        let opts = AttachContainerOptionsBuilder::default()
+            .stdin(true)
+            .stdout(true)
+            .stderr(true)
+            .stream(true)
+            .detach_keys(detach_keys)
+            .build();
 
-        if !exit_status.success() {
-            bail!("error in connecting to or interacting with container")
+        let attached = docker
+            .attach_container(&self.id, Some(opts))
+            .await
+            .context("unable to attach to container")?;
+
+        if !tty {
+            let output = Box::pin(
+                attached
+                    .output
+                    .map(|chunk| chunk.map(with_carriage_returns)),
+            );
+            return Ok(TerminalSession::without_tty(output, attached.input, tx));
         }
 
-        Ok(())
+        let id = self.id.clone();
+        let docker = docker.clone();
+        let resize: TtyResize = Box::new(move |cols, rows| {
+            let id = id.clone();
+            let docker = docker.clone();
+            Box::pin(async move {
+                let opts = ResizeContainerTTYOptionsBuilder::default()
+                    .w(cols.into())
+                    .h(rows.into())
+                    .build();
+                docker
+                    .resize_container_tty(&id, opts)
+                    .await
+                    .context("unable to resize container tty")
+            })
+        });
+
+        Ok(TerminalSession::new(
+            attached.output,
+            attached.input,
+            resize,
+            tx,
+        ))
+    }
+}
+
+/// Without a TTY the process only writes line feeds, which would otherwise
+/// leave the cursor in the same column on each new line
+fn with_carriage_returns(chunk: LogOutput) -> LogOutput {
+    LogOutput::Console {
+        message: String::from_utf8_lossy(chunk.as_ref())
+            .replace('\n', "\r\n")
+            .into(),
     }
 }
 
+#[async_trait::async_trait]
 impl Describe for DockerContainer {
     fn get_id(&self) -> String {
         self.id.clone()
@@ -173,19 +379,408 @@ impl Describe for DockerContainer {
     fn get_name(&self) -> String {
         format!("container: {}", self.names)
     }
-    fn describe(&self) -> Result<Vec<DescribeSection>> {
+    async fn describe(&self, docker: &bollard::Docker) -> Result<Vec<DescribeSection>> {
+        let inspect = self.inspect_response(docker).await?;
+
         let mut summary = DescribeSection::new("Summary");
         summary
-            .item("ID", &self.id)
+            .item_opt("ID", inspect.id)
+            .item_opt(
+                "Name",
+                inspect.name.map(|n| n.trim_start_matches('/').to_string()),
+            )
             .item("Image", &self.image)
-            .item("Image ID", &self.image_id)
-            .item("Command", &self.command)
-            .item("Created", &self.created)
-            .item("Status", &self.status)
-            .item("Ports", &self.ports)
-            .item("Names", &self.names)
-            .item("Running", self.running);
-        Ok(vec![summary])
+            .item_opt("Image ID", inspect.image)
+            .item_opt("Created", inspect.created)
+            .item_opt("Path", inspect.path)
+            .item_opt("Args", inspect.args.map(|a| a.join(" ")))
+            .item_opt("Restart Count", inspect.restart_count)
+            .item_opt("Platform", inspect.platform)
+            .item_opt("Driver", inspect.driver)
+            .item_opt("Size (rw)", inspect.size_rw.and_then(format_bytes))
+            .item_opt(
+                "Size (root fs)",
+                inspect.size_root_fs.and_then(format_bytes),
+            );
+        let mut result = vec![summary];
+
+        if let Some(state) = inspect.state {
+            result.push(state_section(state));
+        }
+
+        let host_config = inspect.host_config.unwrap_or_default();
+        if let Some(config) = inspect.config {
+            result.push(config_section(&config));
+
+            let env = env_section(config.env.unwrap_or_default());
+            if !env.is_empty() {
+                result.push(env);
+            }
+
+            let labels = DescribeSection::from_map("Labels", &config.labels.unwrap_or_default());
+            if !labels.is_empty() {
+                result.push(labels);
+            }
+        }
+
+        let mounts = mounts_section(inspect.mounts.unwrap_or_default());
+        if !mounts.is_empty() {
+            result.push(mounts);
+        }
+
+        let network_settings = inspect.network_settings.unwrap_or_default();
+        let mut networks = DescribeSection::new("Networks");
+        networks.item_opt("Network Mode", host_config.network_mode.as_ref());
+        let mut endpoints: Vec<_> = network_settings
+            .networks
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        endpoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, endpoint) in endpoints {
+            networks.section(endpoint_section(name, endpoint));
+        }
+        result.push(networks);
+
+        let ports = ports_section(network_settings.ports.unwrap_or_default());
+        if !ports.is_empty() {
+            result.push(ports);
+        }
+
+        let mut restart_policy = DescribeSection::new("Restart Policy");
+        let policy = host_config.restart_policy.clone().unwrap_or_default();
+        restart_policy
+            .item_opt("Name", policy.name)
+            .item_opt("Maximum Retry Count", policy.maximum_retry_count)
+            .item_opt("Auto Remove", host_config.auto_remove);
+        result.push(restart_policy);
+
+        result.push(resources_section(&host_config));
+
+        Ok(result)
+    }
+
+    async fn inspect(&self, docker: &bollard::Docker) -> Result<serde_json::Value> {
+        let inspect = self.inspect_response(docker).await?;
+        Ok(serde_json::to_value(inspect)?)
+    }
+}
+
+fn state_section(state: ContainerState) -> DescribeSection {
+    let mut section = DescribeSection::new("State");
+    section
+        .item_opt("Status", state.status)
+        .item_opt("Running", state.running)
+        .item_opt("Paused", state.paused)
+        .item_opt("Restarting", state.restarting)
+        .item_opt("OOM Killed", state.oom_killed)
+        .item_opt("Dead", state.dead)
+        .item_opt("PID", state.pid)
+        .item_opt("Exit Code", state.exit_code)
+        .item_opt("Error", state.error.filter(|e| !e.is_empty()))
+        .item_opt("Started At", state.started_at)
+        .item_opt("Finished At", state.finished_at);
+
+    if let Some(health) = state.health {
+        let mut health_section = DescribeSection::new("Health");
+        health_section
+            .item_opt("Status", health.status)
+            .item_opt("Failing Streak", health.failing_streak);
+
+        // The daemon only keeps the most recent results, so all of them are shown
+        for (idx, check) in health.log.unwrap_or_default().into_iter().enumerate() {
+            let mut check_section = DescribeSection::new(format!("Check {}", idx + 1));
+            check_section
+                .item_opt("Start", check.start)
+                .item_opt("End", check.end)
+                .item_opt("Exit Code", check.exit_code)
+                .item_opt("Output", check.output.map(|o| o.trim().to_string()));
+            health_section.section(check_section);
+        }
+        section.section(health_section);
+    }
+
+    section
+}
+
+fn config_section(config: &ContainerConfig) -> DescribeSection {
+    let mut section = DescribeSection::new("Config");
+    section
+        .item_opt("Hostname", config.hostname.as_ref())
+        .item_opt("User", config.user.as_ref().filter(|u| !u.is_empty()))
+        .item_opt("Working Dir", config.working_dir.as_ref())
+        .item_opt(
+            "Entrypoint",
+            config.entrypoint.as_ref().map(|e| e.join(" ")),
+        )
+        .item_opt("Command", config.cmd.as_ref().map(|c| c.join(" ")))
+        .item_opt("TTY", config.tty)
+        .item_opt("Open Stdin", config.open_stdin)
+        .item_opt("Stop Signal", config.stop_signal.as_ref())
+        .item_opt("Stop Timeout", config.stop_timeout)
+        .item_opt(
+            "Exposed Ports",
+            config.exposed_ports.as_ref().map(|p| p.join(", ")),
+        )
+        .item_opt("Volumes", config.volumes.as_ref().map(|v| v.join(", ")));
+
+    if let Some(healthcheck) = &config.healthcheck {
+        section.section(healthcheck_section(healthcheck));
+    }
+
+    section
+}
+
+pub(super) fn healthcheck_section(healthcheck: &HealthConfig) -> DescribeSection {
+    // Durations are reported in nanoseconds
+    let duration = |d: Option<i64>| {
+        d.and_then(|d| u64::try_from(d).ok())
+            .map(|d| format!("{:?}", Duration::from_nanos(d)))
+    };
+
+    let mut section = DescribeSection::new("Healthcheck");
+    section
+        .item_opt("Test", healthcheck.test.as_ref().map(|t| t.join(" ")))
+        .item_opt("Interval", duration(healthcheck.interval))
+        .item_opt("Timeout", duration(healthcheck.timeout))
+        .item_opt("Start Period", duration(healthcheck.start_period))
+        .item_opt("Retries", healthcheck.retries);
+    section
+}
+
+pub(super) fn env_section(env: Vec<String>) -> DescribeSection {
+    let mut section = DescribeSection::new("Env");
+    for var in env {
+        match var.split_once('=') {
+            Some((key, value)) => section.item(key, value),
+            None => section.item(var, ""),
+        };
+    }
+    section
+}
+
+fn mounts_section(mounts: Vec<MountPoint>) -> DescribeSection {
+    let mut section = DescribeSection::new("Mounts");
+    for mount in mounts {
+        let mut mount_section = DescribeSection::new(mount.destination.clone().unwrap_or_default());
+        mount_section
+            .item_opt("Type", mount.typ)
+            .item_opt("Name", mount.name)
+            .item_opt("Source", mount.source)
+            .item_opt("Destination", mount.destination)
+            .item_opt("Driver", mount.driver)
+            .item_opt("Mode", mount.mode)
+            .item_opt("Read Write", mount.rw)
+            .item_opt("Propagation", mount.propagation);
+        section.section(mount_section);
+    }
+    section
+}
+
+fn endpoint_section(name: String, endpoint: EndpointSettings) -> DescribeSection {
+    let mut section = DescribeSection::new(name);
+    section
+        .item_opt("Network ID", endpoint.network_id)
+        .item_opt("Endpoint ID", endpoint.endpoint_id)
+        .item_opt("IP Address", endpoint.ip_address)
+        .item_opt("IP Prefix Length", endpoint.ip_prefix_len)
+        .item_opt("Gateway", endpoint.gateway)
+        .item_opt("IPv6 Address", endpoint.global_ipv6_address)
+        .item_opt("IPv6 Gateway", endpoint.ipv6_gateway)
+        .item_opt("MAC Address", endpoint.mac_address)
+        .item_opt("Aliases", endpoint.aliases.map(|a| a.join(", ")))
+        .item_opt("DNS Names", endpoint.dns_names.map(|d| d.join(", ")));
+    section
+}
+
+fn ports_section(ports: PortMap) -> DescribeSection {
+    let mut ports: Vec<_> = ports.into_iter().collect();
+    ports.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut section = DescribeSection::new("Ports");
+    for (port, bindings) in ports {
+        let bindings = bindings
+            .unwrap_or_default()
+            .into_iter()
+            .map(|b| {
+                format!(
+                    "{}:{}",
+                    b.host_ip.unwrap_or_default(),
+                    b.host_port.unwrap_or_default()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        section.item(port, bindings);
+    }
+    section
+}
+
+fn resources_section(host_config: &HostConfig) -> DescribeSection {
+    // The daemon uses zero to mean no limit has been set
+    let limit = |l: Option<i64>| l.filter(|l| *l > 0);
+
+    let mut section = DescribeSection::new("Resources");
+    section
+        .item_opt("Memory", limit(host_config.memory).and_then(format_bytes))
+        .item_opt(
+            "Memory Reservation",
+            limit(host_config.memory_reservation).and_then(format_bytes),
+        )
+        .item_opt(
+            "Memory Swap",
+            limit(host_config.memory_swap).and_then(format_bytes),
+        )
+        .item_opt(
+            "CPUs",
+            limit(host_config.nano_cpus).map(|n| n as f64 / 1_000_000_000.0),
+        )
+        .item_opt("CPU Shares", limit(host_config.cpu_shares))
+        .item_opt("CPU Period", limit(host_config.cpu_period))
+        .item_opt("CPU Quota", limit(host_config.cpu_quota))
+        .item_opt(
+            "CPU Set",
+            host_config.cpuset_cpus.as_ref().filter(|c| !c.is_empty()),
+        )
+        .item_opt("PIDs Limit", limit(host_config.pids_limit))
+        .item_opt(
+            "Shared Memory",
+            limit(host_config.shm_size).and_then(format_bytes),
+        )
+        .item_opt("Privileged", host_config.privileged)
+        .item_opt("Read Only Root FS", host_config.readonly_rootfs);
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use bollard::secret::{Health, HealthcheckResult, PortBinding};
+
+    use super::*;
+
+    #[test]
+    fn test_with_carriage_returns() {
+        let chunk = LogOutput::StdOut {
+            message: "one\ntwo\n".into(),
+        };
+        assert_eq!(with_carriage_returns(chunk).to_string(), "one\r\ntwo\r\n");
+    }
+
+    fn value<'a>(section: &'a DescribeSection, name: &str) -> &'a str {
+        &section
+            .items
+            .iter()
+            .find(|i| i.name == name)
+            .unwrap_or_else(|| panic!("no item {name}"))
+            .value
+    }
+
+    #[test]
+    fn test_env_section() {
+        let section = env_section(vec!["A=1".into(), "B=x=y".into(), "BARE".into()]);
+        assert_eq!(value(&section, "A"), "1");
+        assert_eq!(value(&section, "B"), "x=y");
+        assert_eq!(value(&section, "BARE"), "");
+    }
+
+    #[test]
+    fn test_ports_section() {
+        let binding = |ip: &str, port: &str| PortBinding {
+            host_ip: Some(ip.into()),
+            host_port: Some(port.into()),
+        };
+        let ports = PortMap::from([
+            (
+                "80/tcp".to_string(),
+                Some(vec![binding("0.0.0.0", "8080"), binding("::", "8080")]),
+            ),
+            ("443/tcp".to_string(), None),
+        ]);
+
+        let section = ports_section(ports);
+        let names: Vec<_> = section.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["443/tcp", "80/tcp"]);
+        assert_eq!(value(&section, "80/tcp"), "0.0.0.0:8080, :::8080");
+        assert_eq!(value(&section, "443/tcp"), "");
+    }
+
+    #[test]
+    fn test_resources_section() {
+        let section = resources_section(&HostConfig {
+            memory: Some(512 * 1024 * 1024),
+            nano_cpus: Some(1_500_000_000),
+            cpu_shares: Some(0),
+            pids_limit: Some(-1),
+            ..Default::default()
+        });
+        assert_eq!(value(&section, "Memory"), "512.00 MiB");
+        assert_eq!(value(&section, "CPUs"), "1.5");
+        // Zero and negative values mean no limit has been set
+        assert_eq!(value(&section, "CPU Shares"), "");
+        assert_eq!(value(&section, "PIDs Limit"), "");
+    }
+
+    #[test]
+    fn test_healthcheck_section() {
+        let section = healthcheck_section(&HealthConfig {
+            test: Some(vec!["CMD".into(), "true".into()]),
+            interval: Some(30_000_000_000),
+            timeout: Some(-1),
+            ..Default::default()
+        });
+        assert_eq!(value(&section, "Test"), "CMD true");
+        assert_eq!(value(&section, "Interval"), "30s");
+        assert_eq!(value(&section, "Timeout"), "");
+    }
+
+    #[test]
+    fn test_state_section() {
+        let state = ContainerState {
+            running: Some(false),
+            exit_code: Some(137),
+            error: Some(String::new()),
+            health: Some(Health {
+                failing_streak: Some(2),
+                log: Some(vec![
+                    HealthcheckResult {
+                        exit_code: Some(1),
+                        output: Some("down\n".into()),
+                        ..Default::default()
+                    },
+                    HealthcheckResult::default(),
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let section = state_section(state);
+        assert_eq!(value(&section, "Running"), "false");
+        assert_eq!(value(&section, "Exit Code"), "137");
+        assert_eq!(value(&section, "Error"), "");
+
+        let health = &section.sections[0];
+        assert_eq!(health.name, "Health");
+        assert_eq!(value(health, "Failing Streak"), "2");
+        let checks: Vec<_> = health.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(checks, vec!["Check 1", "Check 2"]);
+        assert_eq!(value(&health.sections[0], "Output"), "down");
+    }
+
+    #[test]
+    fn test_mounts_section() {
+        let section = mounts_section(vec![MountPoint {
+            typ: Some(MountPointTypeEnum::VOLUME),
+            name: Some("data".into()),
+            destination: Some("/data".into()),
+            rw: Some(true),
+            ..Default::default()
+        }]);
+        let mount = &section.sections[0];
+        assert_eq!(mount.name, "/data");
+        assert_eq!(value(mount, "Type"), "volume");
+        assert_eq!(value(mount, "Name"), "data");
+        assert_eq!(value(mount, "Read Write"), "true");
     }
 }
 
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use bollard::secret::{Ipam, IpamConfig, NetworkCreateRequest, VolumeCreateRequest};
+use color_eyre::eyre::{Result, bail};
+
+use super::util::parse_key_value_map;
+
+pub const DEFAULT_VOLUME_DRIVER: &str = "local";
+
+pub const NETWORK_DRIVERS: [&str; 4] = ["bridge", "overlay", "macvlan", "ipvlan"];
+
+/// Configuration used to create a new volume, mirroring the flags of
+/// `docker volume create`.
+///
+/// Driver options and labels are provided as comma separated lists in the form
+/// `KEY=value`, eg `type=nfs,device=:/exports`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VolumeCreateConfig {
+    /// The daemon generates a name when none is given
+    pub name: Option<String>,
+    pub driver: Option<String>,
+    pub driver_opts: Option<String>,
+    pub labels: Option<String>,
+}
+
+impl VolumeCreateConfig {
+    /// Builds the request for bollard's create_volume, validating any user
+    /// provided values along the way
+    pub fn to_request(&self) -> Result<VolumeCreateRequest> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+
+        Ok(VolumeCreateRequest {
+            name: self.name.clone(),
+            driver: Some(
+                self.driver
+                    .clone()
+                    .unwrap_or(DEFAULT_VOLUME_DRIVER.to_string()),
+            ),
+            driver_opts: parse_key_values_opt(self.driver_opts.as_deref())?,
+            labels: parse_key_values_opt(self.labels.as_deref())?,
+            ..Default::default()
+        })
+    }
+}
+
+/// Configuration used to create a new network, mirroring the most commonly
+/// used flags of `docker network create`.
+///
+/// Labels are provided as a comma separated list in the form `KEY=value`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkCreateConfig {
+    pub name: String,
+    pub driver: Option<String>,
+    /// Subnet in CIDR notation, eg `172.28.0.0/16`
+    pub subnet: Option<String>,
+    pub gateway: Option<String>,
+    pub internal: bool,
+    pub attachable: bool,
+    pub labels: Option<String>,
+}
+
+impl NetworkCreateConfig {
+    /// Builds the request for bollard's create_network, validating any user
+    /// provided values along the way
+    pub fn to_request(&self) -> Result<NetworkCreateRequest> {
+        if self.name.is_empty() {
+            bail!("a name is required");
+        }
+        validate_name(&self.name)?;
+
+        let mut ipv6 = false;
+        let ipam = match (&self.subnet, &self.gateway) {
+            (None, None) => None,
+            (None, Some(_)) => bail!("a subnet is required to set the gateway"),
+            (Some(subnet), gateway) => {
+                let (network, prefix) = parse_subnet(subnet)?;
+                if let Some(gateway) = gateway {
+                    let gateway = parse_ip(gateway)?;
+                    if !subnet_contains(network, prefix, gateway) {
+                        bail!("gateway {gateway} is not within subnet {subnet}");
+                    }
+                }
+                ipv6 = network.is_ipv6();
+                Some(Ipam {
+                    config: Some(vec![IpamConfig {
+                        subnet: Some(subnet.clone()),
+                        gateway: gateway.clone(),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                })
+            }
+        };
+
+        Ok(NetworkCreateRequest {
+            name: self.name.clone(),
+            driver: self.driver.clone(),
+            internal: Some(self.internal),
+            attachable: Some(self.attachable),
+            ipam,
+            // The daemon only assigns IPv6 addresses where it is enabled
+            enable_ipv6: ipv6.then_some(true),
+            labels: parse_key_values_opt(self.labels.as_deref())?,
+            ..Default::default()
+        })
+    }
+}
+
+/// Validates the name of a volume or network, which the daemon restricts to
+/// `[a-zA-Z0-9][a-zA-Z0-9_.-]*`; an empty name is left for the form to require
+pub fn validate_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    if let Some(first) = chars.next()
+        && !first.is_ascii_alphanumeric()
+    {
+        bail!("the name must start with a letter or digit");
+    }
+    if let Some(c) = chars.find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))) {
+        bail!("invalid character \"{c}\" in name");
+    }
+    Ok(())
+}
+
+/// Validates a required network name
+pub fn validate_network_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("a name is required");
+    }
+    validate_name(name)
+}
+
+/// Validates a comma separated list of options or labels in the form `KEY=value`
+pub fn validate_key_values(list: &str) -> Result<()> {
+    parse_key_value_map(list).map(|_| ())
+}
+
+/// Validates an optional subnet in CIDR notation, eg `172.28.0.0/16`
+pub fn validate_subnet(subnet: &str) -> Result<()> {
+    if subnet.trim().is_empty() {
+        return Ok(());
+    }
+    parse_subnet(subnet.trim()).map(|_| ())
+}
+
+/// Validates an optional IP address
+pub fn validate_ip(ip: &str) -> Result<()> {
+    if ip.trim().is_empty() {
+        return Ok(());
+    }
+    parse_ip(ip.trim()).map(|_| ())
+}
+
+fn parse_key_values_opt(list: Option<&str>) -> Result<Option<HashMap<String, String>>> {
+    list.map(parse_key_value_map).transpose()
+}
+
+fn parse_ip(ip: &str) -> Result<IpAddr> {
+    match ip.parse() {
+        Ok(ip) => Ok(ip),
+        Err(_) => bail!("invalid IP address \"{ip}\""),
+    }
+}
+
+fn parse_subnet(subnet: &str) -> Result<(IpAddr, u8)> {
+    let Some((ip, prefix)) = subnet.split_once('/') else {
+        bail!("expected a subnet in CIDR notation, eg 172.28.0.0/16");
+    };
+    let ip = parse_ip(ip)?;
+    let max_prefix = if ip.is_ipv4() { 32 } else { 128 };
+    match prefix.parse::<u8>() {
+        Ok(prefix) if prefix <= max_prefix => Ok((ip, prefix)),
+        _ => bail!("invalid prefix length \"{prefix}\""),
+    }
+}
+
+fn subnet_contains(network: IpAddr, prefix: u8, ip: IpAddr) -> bool {
+    let (network, ip, bits) = match (network, ip) {
+        (IpAddr::V4(n), IpAddr::V4(i)) => (u32::from(n) as u128, u32::from(i) as u128, 32),
+        (IpAddr::V6(n), IpAddr::V6(i)) => (u128::from(n), u128::from(i), 128),
+        _ => return false,
+    };
+    let shift = bits - u32::from(prefix);
+    shift >= bits || network >> shift == ip >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subnet() {
+        assert_eq!(
+            parse_subnet("172.28.0.0/16").unwrap(),
+            ("172.28.0.0".parse().unwrap(), 16)
+        );
+        assert_eq!(
+            parse_subnet("fd00::/64").unwrap(),
+            ("fd00::".parse().unwrap(), 64)
+        );
+        assert!(parse_subnet("172.28.0.0").is_err());
+        assert!(parse_subnet("172.28.0.0/33").is_err());
+        assert!(parse_subnet("fd00::/129").is_err());
+        assert!(parse_subnet("172.28.0/16").is_err());
+    }
+
+    #[test]
+    fn test_subnet_contains() {
+        let (network, prefix) = parse_subnet("172.28.0.0/16").unwrap();
+        assert!(subnet_contains(
+            network,
+            prefix,
+            "172.28.5.1".parse().unwrap()
+        ));
+        assert!(!subnet_contains(
+            network,
+            prefix,
+            "172.29.0.1".parse().unwrap()
+        ));
+        assert!(!subnet_contains(
+            network,
+            prefix,
+            "fd00::1".parse().unwrap()
+        ));
+
+        let (network, prefix) = parse_subnet("fd00::/64").unwrap();
+        assert!(subnet_contains(network, prefix, "fd00::1".parse().unwrap()));
+        assert!(!subnet_contains(
+            network,
+            prefix,
+            "fd00:0:0:1::1".parse().unwrap()
+        ));
+
+        let (network, prefix) = parse_subnet("0.0.0.0/0").unwrap();
+        assert!(subnet_contains(
+            network,
+            prefix,
+            "10.0.0.1".parse().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_network_to_request() {
+        let config = NetworkCreateConfig {
+            name: "backend".into(),
+            subnet: Some("172.28.0.0/16".into()),
+            gateway: Some("172.28.0.1".into()),
+            labels: Some("team=web".into()),
+            ..Default::default()
+        };
+        let request = config.to_request().unwrap();
+        assert_eq!(request.enable_ipv6, None);
+        assert_eq!(
+            request.labels,
+            Some(HashMap::from([("team".to_string(), "web".to_string())]))
+        );
+
+        let config = NetworkCreateConfig {
+            gateway: Some("172.29.0.1".into()),
+            ..config
+        };
+        assert!(config.to_request().is_err());
+    }
+
+    #[test]
+    fn test_network_to_request_ipv6() {
+        let config = NetworkCreateConfig {
+            name: "backend".into(),
+            subnet: Some("fd00::/64".into()),
+            gateway: Some("fd00::1".into()),
+            ..Default::default()
+        };
+        assert_eq!(config.to_request().unwrap().enable_ipv6, Some(true));
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bollard::auth::DockerCredentials;
+use color_eyre::eyre::{Context, ContextCompat, Result, bail};
+use serde::Deserialize;
+
+/// The server docker hub credentials are stored against
+const DOCKER_HUB_SERVER: &str = "https://index.docker.io/v1/";
+
+/// Username credential helpers return when the secret is an identity token
+const TOKEN_USERNAME: &str = "<token>";
+
+/// The parts of the docker CLI's `config.json` relating to registry credentials
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigFile {
+    #[serde(default)]
+    auths: HashMap<String, AuthEntry>,
+    creds_store: Option<String>,
+    #[serde(default)]
+    cred_helpers: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AuthEntry {
+    /// Base64 encoded `username:password`
+    auth: Option<String>,
+    identitytoken: Option<String>,
+}
+
+/// Output of `docker-credential-<helper> get`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HelperCredentials {
+    username: String,
+    secret: String,
+}
+
+/// The server an image reference's credentials are stored against; references
+/// without a registry host belong to docker hub
+pub fn registry_server(reference: &str) -> String {
+    match reference.split_once('/') {
+        Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => match host {
+            "docker.io" | "index.docker.io" | "registry-1.docker.io" => {
+                DOCKER_HUB_SERVER.to_string()
+            }
+            _ => host.to_string(),
+        },
+        _ => DOCKER_HUB_SERVER.to_string(),
+    }
+}
+
+/// Credentials entered by the user for a registry
+pub fn from_login(server: &str, username: &str, password: &str) -> DockerCredentials {
+    DockerCredentials {
+        username: Some(username.to_string()),
+        password: Some(password.to_string()),
+        serveraddress: Some(server.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Looks up the credentials for the registry an image reference belongs to,
+/// in the same way as the docker CLI; a credential helper configured for the
+/// registry (or for all registries) takes precedence over `auths`
+pub fn lookup(reference: &str) -> Result<Option<DockerCredentials>> {
+    let Some(path) = config_path().filter(|p| p.is_file()) else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("unable to read {}", path.display()))?;
+    let config: ConfigFile = serde_json::from_str(&contents)
+        .with_context(|| format!("unable to parse {}", path.display()))?;
+
+    let server = registry_server(reference);
+    let host = server_host(&server);
+
+    let helper = config
+        .cred_helpers
+        .iter()
+        .find(|(registry, _)| server_host(registry) == host)
+        .map(|(_, helper)| helper)
+        .or(config.creds_store.as_ref());
+    if let Some(helper) = helper
+        && let Some(credentials) = from_helper(helper, &server)?
+    {
+        return Ok(Some(credentials));
+    }
+
+    let entry = config
+        .auths
+        .iter()
+        .find(|(registry, _)| server_host(registry) == host)
+        .map(|(_, entry)| entry);
+    match entry {
+        Some(entry) => from_auth_entry(entry, &server).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// The directory holding the docker CLI's configuration, which may be
+/// overridden with `DOCKER_CONFIG`
+fn config_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("DOCKER_CONFIG") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs_next::home_dir()?.join(".docker"),
+    };
+    Some(dir.join("config.json"))
+}
+
+/// Strips the scheme and any path from a server address, as entries may be
+/// stored as either `https://host/v1/` or `host`
+fn server_host(server: &str) -> &str {
+    let server = server
+        .strip_prefix("https://")
+        .or_else(|| server.strip_prefix("http://"))
+        .unwrap_or(server);
+    let host = server.split('/').next().unwrap_or_default();
+    match host {
+        "docker.io" | "registry-1.docker.io" => "index.docker.io",
+        _ => host,
+    }
+}
+
+fn from_auth_entry(entry: &AuthEntry, server: &str) -> Result<DockerCredentials> {
+    let mut credentials = DockerCredentials {
+        serveraddress: Some(server.to_string()),
+        identitytoken: entry.identitytoken.clone().filter(|t| !t.is_empty()),
+        ..Default::default()
+    };
+
+    if let Some(auth) = entry.auth.as_deref().filter(|a| !a.is_empty()) {
+        let decoded = STANDARD
+            .decode(auth)
+            .ok()
+            .and_then(|d| String::from_utf8(d).ok())
+            .with_context(|| format!("invalid stored credentials for {server}"))?;
+        let Some((username, password)) = decoded.split_once(':') else {
+            bail!("invalid stored credentials for {server}");
+        };
+        credentials.username = Some(username.to_string());
+        credentials.password = Some(password.to_string());
+    }
+    Ok(credentials)
+}
+
+/// Runs `docker-credential-<helper> get`, which reads the server from stdin and
+/// writes the credentials as JSON; a failure is treated as there being no
+/// credentials stored for the server
+fn from_helper(helper: &str, server: &str) -> Result<Option<DockerCredentials>> {
+    let program = format!("docker-credential-{helper}");
+    let mut child = Command::new(&program)
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("unable to run {program}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(server.as_bytes())
+            .with_context(|| format!("unable to write to {program}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("unable to run {program}"))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let found: HelperCredentials = serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("unable to parse the output of {program}"))?;
+    let credentials = if found.username == TOKEN_USERNAME {
+        DockerCredentials {
+            identitytoken: Some(found.secret),
+            serveraddress: Some(server.to_string()),
+            ..Default::default()
+        }
+    } else {
+        from_login(server, &found.username, &found.secret)
+    };
+    Ok(Some(credentials))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_server() {
+        assert_eq!(registry_server("nginx"), DOCKER_HUB_SERVER);
+        assert_eq!(registry_server("library/nginx:1.27"), DOCKER_HUB_SERVER);
+        assert_eq!(
+            registry_server("docker.io/library/nginx"),
+            DOCKER_HUB_SERVER
+        );
+        assert_eq!(
+            registry_server("index.docker.io/org/app"),
+            DOCKER_HUB_SERVER
+        );
+        assert_eq!(registry_server("ghcr.io/org/app:v2"), "ghcr.io");
+        assert_eq!(registry_server("localhost/app"), "localhost");
+        assert_eq!(registry_server("localhost:5000/app:1.0"), "localhost:5000");
+    }
+
+    #[test]
+    fn test_server_host() {
+        assert_eq!(server_host(DOCKER_HUB_SERVER), "index.docker.io");
+        assert_eq!(server_host("docker.io"), "index.docker.io");
+        assert_eq!(server_host("https://ghcr.io"), "ghcr.io");
+        assert_eq!(server_host("localhost:5000"), "localhost:5000");
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bollard::query_parameters::EventsOptionsBuilder;
+use bollard::secret::EventMessage;
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{Context, ContextCompat, Result, bail};
+use futures::{Stream, StreamExt};
+
+use super::{
+    container::DockerContainer,
+    image::DockerImage,
+    network::DockerNetwork,
+    util::{matches_labels, parse_labels, short_id},
+    volume::DockerVolume,
+};
+
+/// Types of event which can be filtered on, in the order they are offered;
+/// the daemon reports a few more, eg for swarm services, which are only shown
+/// unfiltered
+pub const EVENT_TYPES: [&str; 7] = [
+    "container",
+    "image",
+    "volume",
+    "network",
+    "daemon",
+    "plugin",
+    "builder",
+];
+
+/// How far back to replay events from when subscribing, so that a restart loop
+/// which started before the events were opened can still be seen.  The daemon
+/// only keeps its most recent events, so this may return fewer.
+const REPLAY_SECONDS: u64 = 15 * 60;
+
+/// Attributes worth showing alongside an event, eg why a container stopped
+const DETAIL_ATTRIBUTES: [&str; 3] = ["exitCode", "signal", "container"];
+
+/// A single event reported by the daemon, eg a container starting or dying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerEvent {
+    pub typ: String,
+    pub action: String,
+    /// ID of the affected resource; for volumes this is the name, and for some
+    /// image events the reference used
+    pub id: String,
+    pub name: String,
+    /// Nanoseconds since the unix epoch
+    pub time: i64,
+    /// Attributes of the resource, which for containers include its labels
+    pub attributes: HashMap<String, String>,
+}
+
+/// The resource affected by an event, used to show it on its own page
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventResource {
+    Container(DockerContainer),
+    Image(DockerImage),
+    Volume(DockerVolume),
+    Network(DockerNetwork),
+}
+
+/// Filters applied to the events shown, leaving the stream itself unfiltered
+/// so that changing them doesn't lose any events
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    pub typ: Option<String>,
+    /// Comma separated labels in the form `KEY` or `KEY=value`
+    pub labels: Option<String>,
+}
+
+impl EventFilter {
+    pub fn is_empty(&self) -> bool {
+        self.typ.is_none() && self.labels.is_none()
+    }
+}
+
+impl DockerEvent {
+    pub fn from(e: EventMessage) -> Self {
+        let (id, attributes) = match e.actor {
+            Some(actor) => (
+                actor.id.unwrap_or_default(),
+                actor.attributes.unwrap_or_default(),
+            ),
+            None => (String::new(), HashMap::new()),
+        };
+        let name = match attributes.get("name") {
+            Some(name) => name.clone(),
+            None => short_id(&id),
+        };
+        let time = e
+            .time_nano
+            .or(e.time.map(|t| t * 1_000_000_000))
+            .unwrap_or_default();
+
+        Self {
+            typ: e.typ.map(|t| t.to_string()).unwrap_or_default(),
+            action: e.action.unwrap_or_default(),
+            id,
+            name,
+            time,
+            attributes,
+        }
+    }
+
+    /// Streams events from the daemon, starting with those from the last few
+    /// minutes, until the stream is dropped
+    pub fn get_event_stream(
+        docker: &bollard::Docker,
+    ) -> impl Stream<Item = Result<Self>> + 'static {
+        let since = SystemTime::now()
+            .checked_sub(Duration::from_secs(REPLAY_SECONDS))
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let opts = EventsOptionsBuilder::default()
+            .since(&since.to_string())
+            .build();
+        let event_stream = docker.events(Some(opts)).map(|res| {
+            res.map(Self::from)
+                .context("unable to retrieve docker events")
+        });
+
+        Box::pin(event_stream)
+    }
+
+    /// The local time of the event, eg `2024-01-31 12:34:56`
+    pub fn time(&self) -> String {
+        let nanos = u64::try_from(self.time).unwrap_or_default();
+        DateTime::<Local>::from(UNIX_EPOCH + Duration::from_nanos(nanos))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+
+    /// The attributes which explain the event, eg `exitCode=137`
+    pub fn detail(&self) -> String {
+        DETAIL_ATTRIBUTES
+            .iter()
+            .filter_map(|key| {
+                self.attributes.get(*key).map(|v| match *key {
+                    // Network events give the ID of the container connected
+                    "container" => format!("{key}={}", short_id(v)),
+                    _ => format!("{key}={v}"),
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn matches(&self, filter: &EventFilter) -> bool {
+        if let Some(typ) = &filter.typ
+            && &self.typ != typ
+        {
+            return false;
+        }
+        match filter.labels.as_deref().map(parse_labels) {
+            Some(Ok(labels)) => matches_labels(Some(&self.attributes), &labels),
+            Some(Err(_)) => false,
+            None => true,
+        }
+    }
+
+    /// Retrieves the resource affected by the event, failing where the
+    /// resource no longer exists or has no page of its own
+    pub async fn resource(&self, docker: &bollard::Docker) -> Result<EventResource> {
+        let resource = match self.typ.as_str() {
+            "container" => EventResource::Container(DockerContainer::get(docker, &self.id).await?),
+            "image" => {
+                // Some image events are reported by the reference used, eg a
+                // pull, so the reference is resolved to the image's ID
+                let id = docker
+                    .inspect_image(&self.id)
+                    .await
+                    .with_context(|| format!("no image found with id {}", self.id))?
+                    .id
+                    .unwrap_or_default();
+                // An image has an entry per tag, so prefer the one referenced
+                let images = DockerImage::list(docker, true)
+                    .await?
+                    .into_iter()
+                    .filter(|i| i.id == id)
+                    .collect::<Vec<_>>();
+                let image = images
+                    .iter()
+                    .find(|i| [&self.id, &self.name].contains(&&i.get_full_name()))
+                    .or(images.first())
+                    .cloned()
+                    .with_context(|| format!("no image found with id {id}"))?;
+                EventResource::Image(image)
+            }
+            "volume" => {
+                let volume = DockerVolume::list(docker)
+                    .await
+                    .context("unable to retrieve list of volumes")?
+                    .into_iter()
+                    .find(|v| v.name == self.id)
+                    .with_context(|| format!("no volume found with name {}", self.id))?;
+                EventResource::Volume(volume)
+            }
+            "network" => {
+                let network = DockerNetwork::list(docker)
+                    .await
+                    .context("unable to retrieve list of networks")?
+                    .into_iter()
+                    .find(|n| n.id == self.id)
+                    .with_context(|| format!("no network found with id {}", self.id))?;
+                EventResource::Network(network)
+            }
+            typ => bail!("{typ} events have no page to show"),
+        };
+        Ok(resource)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(typ: &str, attributes: &[(&str, &str)]) -> DockerEvent {
+        DockerEvent {
+            typ: typ.into(),
+            action: "die".into(),
+            id: "0123456789abcdef".into(),
+            name: "web".into(),
+            time: 0,
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    fn filter(typ: Option<&str>, labels: Option<&str>) -> EventFilter {
+        EventFilter {
+            typ: typ.map(String::from),
+            labels: labels.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_from_event_message() {
+        let message: EventMessage = serde_json::from_value(serde_json::json!({
+            "Type": "container",
+            "Action": "die",
+            "Actor": {
+                "ID": "0123456789abcdef0123",
+                "Attributes": {"name": "web", "exitCode": "137"}
+            },
+            "time": 1700000000,
+            "timeNano": 1700000000123456789_i64
+        }))
+        .unwrap();
+
+        let event = DockerEvent::from(message);
+
+        assert_eq!(event.typ, "container");
+        assert_eq!(event.action, "die");
+        assert_eq!(event.id, "0123456789abcdef0123");
+        assert_eq!(event.name, "web");
+        assert_eq!(event.time, 1700000000123456789);
+        assert_eq!(event.attributes.get("exitCode").unwrap(), "137");
+    }
+
+    #[test]
+    fn test_from_event_message_fallbacks() {
+        let message: EventMessage = serde_json::from_value(serde_json::json!({
+            "Type": "image",
+            "Action": "delete",
+            "Actor": {"ID": "sha256:0123456789abcdef0123"},
+            "time": 1700000000
+        }))
+        .unwrap();
+
+        let event = DockerEvent::from(message);
+
+        // Without a name the short ID is shown, and without nanoseconds the
+        // seconds are converted
+        assert_eq!(event.name, "0123456789ab");
+        assert_eq!(event.time, 1700000000 * 1_000_000_000);
+        assert!(event.attributes.is_empty());
+
+        let event = DockerEvent::from(EventMessage::default());
+        assert_eq!(event.typ, "");
+        assert_eq!(event.id, "");
+        assert_eq!(event.time, 0);
+    }
+
+    #[test]
+    fn test_detail() {
+        let e = event(
+            "container",
+            &[("exitCode", "137"), ("signal", "9"), ("image", "nginx")],
+        );
+        assert_eq!(e.detail(), "exitCode=137, signal=9");
+
+        let e = event("network", &[("container", "0123456789abcdef0123")]);
+        assert_eq!(e.detail(), "container=0123456789ab");
+
+        let e = event("image", &[("name", "nginx")]);
+        assert_eq!(e.detail(), "");
+    }
+
+    #[test]
+    fn test_matches_type() {
+        let e = event("container", &[]);
+        assert!(e.matches(&EventFilter::default()));
+        assert!(e.matches(&filter(Some("container"), None)));
+        assert!(!e.matches(&filter(Some("image"), None)));
+    }
+
+    #[test]
+    fn test_matches_labels() {
+        let e = event(
+            "container",
+            &[("com.example.app", "web"), ("tier", "front")],
+        );
+        assert!(e.matches(&filter(None, Some("com.example.app"))));
+        assert!(e.matches(&filter(None, Some("com.example.app=web, tier=front"))));
+        assert!(!e.matches(&filter(None, Some("com.example.app=db"))));
+        assert!(!e.matches(&filter(None, Some("missing"))));
+        assert!(e.matches(&filter(Some("container"), Some("tier=front"))));
+        assert!(!e.matches(&filter(Some("image"), Some("tier=front"))));
+    }
+
+    #[test]
+    fn test_matches_invalid_labels() {
+        let e = event("container", &[("tier", "front")]);
+        assert!(!e.matches(&filter(None, Some("=front"))));
+    }
+}
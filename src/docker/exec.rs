@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use bollard::container::LogOutput;
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecOptions, StartExecResults};
+use color_eyre::eyre::{Context, Result, bail};
+use futures::StreamExt;
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    container::DockerContainer,
+    terminal::{TerminalSession, TtyResize},
+    util::split_command,
+};
+use crate::events::{Key, Message, Transition};
+
+/// An interactive process running in a TTY inside a container, created through
+/// the same daemon connection as the rest of the application
+#[derive(Debug, Clone)]
+pub struct DockerExec {
+    pub id: String,
+    docker: bollard::Docker,
+}
+
+impl DockerExec {
+    /// Creates (but doesn't start) an exec instance running the given command in
+    /// the container; the command is split into arguments as a shell would
+    pub async fn create(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+        cmd: &str,
+    ) -> Result<Self> {
+        let cmd = split_command(cmd)?;
+        if cmd.is_empty() {
+            bail!("no command provided to exec");
+        }
+
+        let opts = CreateExecOptions {
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            tty: Some(true),
+            cmd: Some(cmd),
+            ..Default::default()
+        };
+
+        let exec = docker
+            .create_exec(&container.id, opts)
+            .await
+            .context("unable to create exec")?;
+
+        Ok(Self {
+            id: exec.id,
+            docker: docker.clone(),
+        })
+    }
+
+    /// Runs a command in the container without a TTY and waits for it to exit,
+    /// returning what it wrote to stdout.  Fails with what it wrote to stderr
+    /// where it exits unsuccessfully.
+    pub async fn output(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+        cmd: &[&str],
+    ) -> Result<Vec<u8>> {
+        let opts = CreateExecOptions {
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            cmd: Some(cmd.iter().map(|c| c.to_string()).collect()),
+            ..Default::default()
+        };
+        let exec = docker
+            .create_exec(&container.id, opts)
+            .await
+            .context("unable to create exec")?;
+
+        let mut output = match docker
+            .start_exec(&exec.id, None)
+            .await
+            .context("unable to start exec")?
+        {
+            StartExecResults::Attached { output, .. } => output,
+            StartExecResults::Detached => bail!("exec unexpectedly started detached"),
+        };
+
+        let (mut stdout, mut stderr) = (vec![], vec![]);
+        while let Some(chunk) = output.next().await {
+            match chunk.context("unable to read exec output")? {
+                LogOutput::StdOut { message } => stdout.extend_from_slice(&message),
+                LogOutput::StdErr { message } => stderr.extend_from_slice(&message),
+                _ => {}
+            }
+        }
+
+        let exit_code = docker
+            .inspect_exec(&exec.id)
+            .await
+            .context("unable to inspect exec")?
+            .exit_code;
+        match exit_code {
+            Some(0) => Ok(stdout),
+            _ if !stderr.is_empty() => bail!("{}", String::from_utf8_lossy(&stderr).trim()),
+            Some(code) => bail!("`{}` exited with status {code}", cmd.join(" ")),
+            None => bail!("`{}` did not exit", cmd.join(" ")),
+        }
+    }
+
+    /// Resizes the exec's TTY
+    pub async fn resize(&self, width: u16, height: u16) -> Result<()> {
+        self.docker
+            .resize_exec(&self.id, ResizeExecOptions { height, width })
+            .await
+            .context("unable to resize exec")?;
+        Ok(())
+    }
+
+    /// Gets the exit code of the exec's process, if it has exited
+    pub async fn exit_code(&self) -> Result<Option<i64>> {
+        let inspect = self
+            .docker
+            .inspect_exec(&self.id)
+            .await
+            .context("unable to inspect exec")?;
+        Ok(inspect.exit_code)
+    }
+
+    /// Starts the exec, returning a session through which its TTY can be drawn
+    /// and interacted with inside the TUI
+    pub async fn start(&self, tx: Sender<Message<Key, Transition>>) -> Result<TerminalSession> {
+        let opts = StartExecOptions {
+            detach: false,
+            tty: true,
+            ..Default::default()
+        };
+
+        let (output, input) = match self
+            .docker
+            .start_exec(&self.id, Some(opts))
+            .await
+            .context("unable to start exec")?
+        {
+            StartExecResults::Attached { output, input } => (output, input),
+            StartExecResults::Detached => bail!("exec unexpectedly started detached"),
+        };
+
+        let exec = self.clone();
+        let resize: TtyResize = Box::new(move |cols, rows| {
+            let exec = exec.clone();
+            Box::pin(async move { exec.resize(cols, rows).await })
+        });
+
+        Ok(TerminalSession::new(output, input, resize, tx))
+    }
+}
+
+/// An exec session which is still running, along with the command it was started with
+#[derive(Debug)]
+pub struct ExecSession {
+    pub exec: DockerExec,
+    pub command: String,
+    pub terminal: TerminalSession,
+}
+
+/// Exec sessions keyed by container ID, so that a session can be left and
+/// resumed later without killing the process
+pub type ExecSessions = Arc<Mutex<HashMap<String, Arc<ExecSession>>>>;
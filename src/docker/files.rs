@@ -0,0 +1,462 @@
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use bollard::container::PathStatResponse;
+use bollard::errors::Error::DockerResponseServerError;
+use bollard::query_parameters::{
+    ContainerArchiveInfoOptionsBuilder, DownloadFromContainerOptionsBuilder,
+    UploadToContainerOptionsBuilder,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{Context, ContextCompat, Report, Result, bail};
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use tokio_util::io::{ReaderStream, StreamReader, SyncIoBridge};
+
+use super::{container::DockerContainer, exec::DockerExec};
+
+/// Maximum number of bytes of a file read when previewing it
+pub const PREVIEW_LIMIT: u64 = 64 * 1024;
+
+/// Number of files in a directory which are inspected at once when listing it
+const STAT_CONCURRENCY: usize = 16;
+
+/// Size of the buffer between the archive being built and uploaded
+const UPLOAD_BUFFER: usize = 64 * 1024;
+
+// File type bits of the modes reported by the daemon, which are Go's
+// `os.FileMode` rather than unix modes
+const MODE_DIR: u32 = 1 << 31;
+const MODE_SYMLINK: u32 = 1 << 27;
+const MODE_TYPE: u32 = MODE_DIR | MODE_SYMLINK | 1 << 26 | 1 << 25 | 1 << 24 | 1 << 21 | 1 << 19;
+
+type ArchiveStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+type ArchiveReader = SyncIoBridge<StreamReader<ArchiveStream, Bytes>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileKind {
+    Directory,
+    File,
+    Symlink(String),
+    Other,
+}
+
+/// A file within a container's filesystem, as reported by the daemon's
+/// archive API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerFile {
+    pub name: String,
+    pub path: String,
+    pub kind: FileKind,
+    pub size: u64,
+    pub mode: String,
+    pub modified: String,
+}
+
+/// The start of a text file within a container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePreview {
+    pub content: String,
+    /// Whether the file was longer than `PREVIEW_LIMIT`
+    pub truncated: bool,
+}
+
+impl ContainerFile {
+    fn from_stat(stat: PathStatResponse, path: String) -> Self {
+        let mode = stat.file_mode;
+        let (kind, type_char) = match mode & MODE_TYPE {
+            MODE_DIR => (FileKind::Directory, 'd'),
+            MODE_SYMLINK => (FileKind::Symlink(stat.link_target), 'l'),
+            0 => (FileKind::File, '-'),
+            _ => (FileKind::Other, '?'),
+        };
+        let permissions: String = (0..9)
+            .map(|bit| {
+                if mode & (0o400 >> bit) == 0 {
+                    '-'
+                } else {
+                    ['r', 'w', 'x'][bit % 3]
+                }
+            })
+            .collect();
+
+        let modified = stat
+            .modification_time
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+            .map(|d| {
+                d.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        Self {
+            name: path.rsplit('/').next().unwrap_or_default().to_string(),
+            path,
+            kind,
+            size: u64::try_from(stat.size).unwrap_or_default(),
+            mode: format!("{type_char}{permissions}"),
+            modified,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.kind == FileKind::Directory
+    }
+
+    /// Lists the contents of a directory in the container, following the
+    /// directory if it is a symlink.
+    ///
+    /// The daemon's archives are always recursive, so the names are listed with
+    /// `ls` in the container, which must be running, and each is then inspected
+    /// through the daemon.
+    pub async fn list(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+        dir: &str,
+    ) -> Result<Vec<Self>> {
+        let dir = dir.trim_end_matches('/').to_string();
+        // The trailing slash causes symlinks to directories to be followed
+        let listed_dir = format!("{dir}/");
+
+        let dir_stat = stat(docker, container, &listed_dir)
+            .await?
+            .with_context(|| format!("{dir} not found"))?;
+        if dir_stat.file_mode & MODE_DIR == 0 {
+            bail!("{dir} is not a directory");
+        }
+
+        let names = DockerExec::output(docker, container, &["ls", "-A1", "--", &listed_dir])
+            .await
+            .with_context(|| format!("unable to list {dir}"))?;
+        let paths = listed_paths(&dir, &names);
+
+        let mut files = stream::iter(paths)
+            .map(|path| async move {
+                let file = stat(docker, container, &path).await?;
+                Ok::<_, Report>(file.map(|f| Self::from_stat(f, path)))
+            })
+            .buffered(STAT_CONCURRENCY)
+            // Files removed since being listed are skipped
+            .try_filter_map(|file| async move { Ok(file) })
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        files.sort_by(|a, b| b.is_dir().cmp(&a.is_dir()).then(a.name.cmp(&b.name)));
+        Ok(files)
+    }
+
+    /// Reads the start of the file at `path`, failing where it doesn't appear to be text
+    pub async fn preview(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+        path: &str,
+    ) -> Result<FilePreview> {
+        let name = path.to_string();
+        read_archive(docker, container, path, move |mut archive| {
+            let mut entry = archive
+                .entries()?
+                .next()
+                .with_context(|| format!("{name} not found"))??;
+            if entry.header().entry_type() != tar::EntryType::Regular {
+                bail!("{name} is not a regular file");
+            }
+
+            let size = entry.header().size()?;
+            let mut content = vec![];
+            entry
+                .by_ref()
+                .take(PREVIEW_LIMIT)
+                .read_to_end(&mut content)?;
+            if content.contains(&0) {
+                bail!("{name} appears to be a binary file");
+            }
+
+            Ok(FilePreview {
+                content: String::from_utf8_lossy(&content).to_string(),
+                truncated: size > PREVIEW_LIMIT,
+            })
+        })
+        .await
+    }
+
+    /// Copies the file or directory at `path` into the local directory `dest`,
+    /// which is created if it doesn't exist
+    pub async fn download(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+        path: &str,
+        dest: &Path,
+    ) -> Result<()> {
+        let dest = dest.to_path_buf();
+        read_archive(docker, container, path, move |mut archive| {
+            archive
+                .unpack(&dest)
+                .with_context(|| format!("unable to write to {}", dest.display()))
+        })
+        .await
+    }
+
+    /// Copies a local file or directory into the directory `dir` within the
+    /// container, streaming the archive as it is built
+    pub async fn upload(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+        source: &Path,
+        dir: &str,
+    ) -> Result<()> {
+        let (writer, reader) = tokio::io::duplex(UPLOAD_BUFFER);
+        // The tar crate only supports synchronous writers
+        let writer = SyncIoBridge::new(writer);
+
+        let source = source.to_path_buf();
+        let build = tokio::task::spawn_blocking(move || -> Result<()> {
+            let name = source
+                .file_name()
+                .with_context(|| format!("invalid path {}", source.display()))?;
+
+            let mut builder = tar::Builder::new(writer);
+            if source.is_dir() {
+                builder.append_dir_all(name, &source)?;
+            } else {
+                builder.append_path_with_name(&source, name)?;
+            }
+            builder
+                .into_inner()
+                .with_context(|| format!("unable to read {}", source.display()))?;
+            Ok(())
+        });
+
+        let opts = UploadToContainerOptionsBuilder::default().path(dir).build();
+        let upload = docker
+            .upload_to_container(
+                &container.id,
+                Some(opts),
+                bollard::body_try_stream(ReaderStream::new(reader)),
+            )
+            .await
+            .with_context(|| format!("unable to upload to {dir}"));
+
+        match (build.await?, upload) {
+            // The archive stops being read when the upload fails, so building
+            // it fails too
+            (Err(e), Err(upload)) if is_broken_pipe(&e) => Err(upload),
+            (Err(e), _) | (Ok(()), Err(e)) => Err(e),
+            (Ok(()), Ok(())) => Ok(()),
+        }
+    }
+}
+
+/// Streams the tar archive of a path in the container to `f`, which is run on
+/// a blocking thread as the tar crate only supports synchronous readers
+async fn read_archive<T: Send + 'static>(
+    docker: &bollard::Docker,
+    container: &DockerContainer,
+    path: &str,
+    f: impl FnOnce(tar::Archive<ArchiveReader>) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let opts = DownloadFromContainerOptionsBuilder::default()
+        .path(path)
+        .build();
+    let stream = docker
+        .download_from_container(&container.id, Some(opts))
+        .map_err(std::io::Error::other);
+    let stream: ArchiveStream = Box::pin(stream);
+    let reader = SyncIoBridge::new(StreamReader::new(stream));
+
+    tokio::task::spawn_blocking(move || f(tar::Archive::new(reader)))
+        .await?
+        .with_context(|| format!("unable to read {path} from container"))
+}
+
+/// Inspects a path in the container without reading it, returning `None`
+/// where it doesn't exist
+async fn stat(
+    docker: &bollard::Docker,
+    container: &DockerContainer,
+    path: &str,
+) -> Result<Option<PathStatResponse>> {
+    let opts = ContainerArchiveInfoOptionsBuilder::default()
+        .path(path)
+        .build();
+    match docker
+        .get_container_archive_info(&container.id, Some(opts))
+        .await
+    {
+        Ok(stat) => Ok(Some(stat)),
+        Err(DockerResponseServerError {
+            status_code: 404, ..
+        }) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("unable to inspect {path}")),
+    }
+}
+
+/// The paths of the files in `dir` from the output of `ls -A1`, which lists
+/// one name per line
+fn listed_paths(dir: &str, output: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .filter(|n| !n.is_empty())
+        .map(|n| join(dir, n))
+        .collect()
+}
+
+fn is_broken_pipe(e: &Report) -> bool {
+    e.chain()
+        .filter_map(|e| e.downcast_ref::<std::io::Error>())
+        .any(|e| e.kind() == ErrorKind::BrokenPipe)
+}
+
+/// Joins a name onto an absolute directory path within a container
+pub fn join(dir: &str, name: &str) -> String {
+    format!("{}/{name}", dir.trim_end_matches('/'))
+}
+
+/// The parent of an absolute path within a container
+pub fn parent(path: &str) -> String {
+    match path.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) | None => "/".into(),
+        Some((parent, _)) => parent.into(),
+    }
+}
+
+/// Resolves the target of a symlink at `path` to an absolute path
+pub fn resolve_link(path: &str, target: &str) -> String {
+    let joined = if target.starts_with('/') {
+        target.to_string()
+    } else {
+        join(&parent(path), target)
+    };
+
+    let mut components: Vec<&str> = vec![];
+    for component in joined.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            c => components.push(c),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
+
+/// Validates that a local file or directory exists
+pub fn validate_local_path(path: &str) -> Result<()> {
+    if path.trim().is_empty() {
+        bail!("a path is required");
+    }
+    let expanded = expand_local_path(path.trim());
+    if !expanded.exists() {
+        bail!("{} does not exist", expanded.display());
+    }
+    Ok(())
+}
+
+/// Expands a leading `~` in a local path to the user's home directory
+pub fn expand_local_path(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs_next::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_stat(file_mode: u32, link_target: &str) -> PathStatResponse {
+        PathStatResponse {
+            name: String::new(),
+            size: 42,
+            file_mode,
+            modification_time: None,
+            link_target: link_target.into(),
+        }
+    }
+
+    #[test]
+    fn test_listed_paths() {
+        assert_eq!(
+            listed_paths("/etc", b"hosts\n.profile\nwith space\n\n"),
+            vec!["/etc/hosts", "/etc/.profile", "/etc/with space"]
+        );
+        assert_eq!(listed_paths("", b"bin\n"), vec!["/bin"]);
+        assert!(listed_paths("/empty", b"").is_empty());
+    }
+
+    #[test]
+    fn test_from_stat() {
+        let file = ContainerFile::from_stat(path_stat(0o644, ""), "/etc/hosts".into());
+        assert_eq!(file.name, "hosts");
+        assert_eq!(file.kind, FileKind::File);
+        assert_eq!(file.mode, "-rw-r--r--");
+        assert_eq!(file.size, 42);
+        assert_eq!(file.modified, "");
+
+        let dir = ContainerFile::from_stat(path_stat(MODE_DIR | 0o755, ""), "/usr".into());
+        assert!(dir.is_dir());
+        assert_eq!(dir.mode, "drwxr-xr-x");
+
+        let link =
+            ContainerFile::from_stat(path_stat(MODE_SYMLINK | 0o777, "/usr/bin"), "/bin".into());
+        assert_eq!(link.kind, FileKind::Symlink("/usr/bin".into()));
+        assert_eq!(link.mode, "lrwxrwxrwx");
+
+        // Eg a named pipe
+        let other = ContainerFile::from_stat(path_stat(1 << 25 | 0o600, ""), "/fifo".into());
+        assert_eq!(other.kind, FileKind::Other);
+        assert_eq!(other.mode, "?rw-------");
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(join("/", "etc"), "/etc");
+        assert_eq!(join("/etc/", "hosts"), "/etc/hosts");
+    }
+
+    #[test]
+    fn test_parent() {
+        assert_eq!(parent("/etc/hosts"), "/etc");
+        assert_eq!(parent("/etc/"), "/");
+        assert_eq!(parent("/"), "/");
+    }
+
+    #[test]
+    fn test_resolve_link() {
+        assert_eq!(resolve_link("/bin", "usr/bin"), "/usr/bin");
+        assert_eq!(
+            resolve_link("/usr/lib/a.so", "../../lib/./a.so.1"),
+            "/lib/a.so.1"
+        );
+        assert_eq!(resolve_link("/a/b", "/c/../d"), "/d");
+        assert_eq!(resolve_link("/a", "../../.."), "/");
+    }
+
+    #[test]
+    fn test_validate_local_path() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(validate_local_path(dir.path().to_str().unwrap()).is_ok());
+        assert!(validate_local_path(dir.path().join("missing").to_str().unwrap()).is_err());
+        assert!(validate_local_path("  ").is_err());
+    }
+
+    #[test]
+    fn test_expand_local_path() {
+        let home = dirs_next::home_dir().unwrap();
+        assert_eq!(expand_local_path("~"), home);
+        assert_eq!(expand_local_path("~/a/b"), home.join("a/b"));
+        assert_eq!(expand_local_path("~user/a"), PathBuf::from("~user/a"));
+        assert_eq!(expand_local_path("/tmp"), PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_is_broken_pipe() {
+        let e = Report::new(std::io::Error::from(ErrorKind::BrokenPipe)).wrap_err("upload");
+        assert!(is_broken_pipe(&e));
+        assert!(!is_broken_pipe(&Report::msg("upload")));
+    }
+}
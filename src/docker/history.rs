@@ -0,0 +1,145 @@
+use bollard::secret::HistoryResponseItem;
+use color_eyre::eyre::{Context, Result};
+
+use super::{image::DockerImage, util::short_id};
+
+/// Id the daemon reports for layers which were built elsewhere, eg those of a
+/// pulled base image
+const MISSING_ID: &str = "<missing>";
+
+/// A single step in an image's history, corresponding to a Dockerfile instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageLayer {
+    pub id: String,
+    /// Unix timestamp the layer was created at
+    pub created: i64,
+    pub created_by: String,
+    pub size: i64,
+    pub tags: Vec<String>,
+    pub comment: String,
+}
+
+impl ImageLayer {
+    fn from(item: HistoryResponseItem) -> Self {
+        Self {
+            // Layers built elsewhere are reported as `<missing>`, which is
+            // short enough to be kept as is
+            id: short_id(&item.id),
+            created: item.created,
+            created_by: item.created_by,
+            size: item.size,
+            tags: item.tags,
+            comment: item.comment,
+        }
+    }
+
+    /// Lists the image's layers, newest first as with `docker history`
+    pub async fn list(docker: &bollard::Docker, image: &DockerImage) -> Result<Vec<Self>> {
+        let history = docker
+            .image_history(&image.id)
+            .await
+            .with_context(|| format!("unable to retrieve history of {}", image.get_full_name()))?;
+        Ok(history.into_iter().map(Self::from).collect())
+    }
+
+    pub fn is_missing(&self) -> bool {
+        self.id == MISSING_ID
+    }
+
+    /// The instruction which created the layer, with the shell wrapping added
+    /// by the legacy builder removed, eg `RUN apt-get update` rather than
+    /// `/bin/sh -c apt-get update`
+    pub fn instruction(&self) -> String {
+        let created_by = self
+            .created_by
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(nop) = created_by.strip_prefix("/bin/sh -c #(nop) ") {
+            nop.trim().to_string()
+        } else if let Some(run) = created_by.strip_prefix("/bin/sh -c ") {
+            format!("RUN {}", run.trim())
+        } else {
+            created_by
+        }
+    }
+}
+
+/// The total size of each layer and all those beneath it, given layers in
+/// newest first order
+pub fn cumulative_sizes(layers: &[ImageLayer]) -> Vec<i64> {
+    let mut total = 0;
+    let mut sizes = layers
+        .iter()
+        .rev()
+        .map(|layer| {
+            total += layer.size.max(0);
+            total
+        })
+        .collect::<Vec<_>>();
+    sizes.reverse();
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(id: &str, created_by: &str, size: i64) -> ImageLayer {
+        ImageLayer::from(HistoryResponseItem {
+            id: id.into(),
+            created: 0,
+            created_by: created_by.into(),
+            tags: vec![],
+            size,
+            comment: String::new(),
+        })
+    }
+
+    #[test]
+    fn test_cumulative_sizes() {
+        let layers = [
+            layer("sha256:c", "", 30),
+            layer(MISSING_ID, "", 0),
+            layer(MISSING_ID, "", 20),
+            layer(MISSING_ID, "", 10),
+        ];
+        assert_eq!(cumulative_sizes(&layers), vec![60, 30, 30, 10]);
+        assert!(cumulative_sizes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_cumulative_sizes_ignores_unknown_sizes() {
+        let layers = [layer(MISSING_ID, "", 5), layer(MISSING_ID, "", -1)];
+        assert_eq!(cumulative_sizes(&layers), vec![5, 0]);
+    }
+
+    #[test]
+    fn test_layer_ids() {
+        let built = layer(
+            "sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            "",
+            0,
+        );
+        assert_eq!(built.id, "0123456789ab");
+        assert!(!built.is_missing());
+        assert!(layer(MISSING_ID, "", 0).is_missing());
+    }
+
+    #[test]
+    fn test_instruction() {
+        let instruction = |created_by| layer(MISSING_ID, created_by, 0).instruction();
+        assert_eq!(
+            instruction("/bin/sh -c #(nop)  CMD [\"bash\"]"),
+            "CMD [\"bash\"]"
+        );
+        assert_eq!(
+            instruction("/bin/sh -c apt-get update \t&& apt-get install -y curl"),
+            "RUN apt-get update && apt-get install -y curl"
+        );
+        assert_eq!(
+            instruction("RUN /bin/sh -c make # buildkit"),
+            "RUN /bin/sh -c make # buildkit"
+        );
+    }
+}
@@ -1,17 +1,28 @@
-use bollard::query_parameters::{ListImagesOptionsBuilder, RemoveImageOptionsBuilder};
+use bollard::query_parameters::{
+    ImportImageOptionsBuilder, ListImagesOptionsBuilder, RemoveImageOptionsBuilder,
+    TagImageOptionsBuilder,
+};
 use byte_unit::{Byte, UnitType};
 use chrono::Local;
 use chrono::prelude::DateTime;
-use color_eyre::eyre::{Context, Result};
+use color_eyre::eyre::{Context, ContextCompat, Result, bail};
+use futures::TryStreamExt;
 use itertools::Itertools;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, UNIX_EPOCH};
 
-use bollard::secret::ImageSummary;
+use bollard::secret::{ImageInspect, ImageSummary};
 
 use crate::docker::traits::DescribeSection;
 
-use super::traits::Describe;
+use super::{
+    container::{env_section, healthcheck_section},
+    traits::Describe,
+    transfer::{Transfer, read_from_file, write_to_file},
+    util::format_bytes,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct DockerImage {
@@ -39,12 +50,14 @@ impl DockerImage {
 
         if !bollard_image.repo_tags.is_empty() {
             for repo_tag in bollard_image.repo_tags {
-                let split_tag = repo_tag.split(':').collect::<Vec<&str>>();
+                // Split on the last colon, as the registry may include a port,
+                // eg `localhost:5000/app:latest`
+                let (name, tag) = repo_tag.rsplit_once(':').unwrap_or((&repo_tag, "<none>"));
 
                 response.push(Self {
                     id: bollard_image.id.clone(),
-                    name: split_tag[0].to_string(),
-                    tag: split_tag[1].to_string(),
+                    name: name.to_string(),
+                    tag: tag.to_string(),
                     created: datetime.to_string(),
                     size: format!("{b:.2}"),
                     tags: tags.clone(),
@@ -87,6 +100,96 @@ impl DockerImage {
         Ok(images)
     }
 
+    /// Retrieves an image by a reference, eg `repository:tag`
+    pub async fn get(docker: &bollard::Docker, reference: &str) -> Result<Self> {
+        let filters = HashMap::from([("reference", vec![reference])]);
+        let opts = ListImagesOptionsBuilder::default()
+            .filters(&filters)
+            .build();
+        let images = docker
+            .list_images(Some(opts))
+            .await
+            .context("unable to retrieve image")?
+            .into_iter()
+            .flat_map(DockerImage::from)
+            .collect_vec();
+
+        // An image has an entry per tag, so prefer the one matching the reference
+        images
+            .iter()
+            .find(|i| i.get_full_name() == reference)
+            .or(images.first())
+            .cloned()
+            .with_context(|| format!("no image found with reference {reference}"))
+    }
+
+    /// Saves the image as a tarball to a local file, in the same format as `docker save`
+    pub async fn save(
+        &self,
+        docker: &bollard::Docker,
+        path: &Path,
+        transfer: &Transfer,
+    ) -> Result<()> {
+        // An image without a tag can only be referred to by its ID
+        let reference = if self.name == "<none>" || self.tag == "<none>" {
+            self.id.clone()
+        } else {
+            self.get_full_name()
+        };
+        write_to_file(docker.export_image(&reference), path, transfer)
+            .await
+            .context("unable to save image")
+    }
+
+    /// Loads the images in a tarball created by `docker save`, returning the
+    /// names of the images loaded
+    pub async fn load(
+        docker: &bollard::Docker,
+        path: &Path,
+        transfer: Arc<Transfer>,
+    ) -> Result<Vec<String>> {
+        let stream = read_from_file(path, transfer).await?;
+        let opts = ImportImageOptionsBuilder::default().quiet(false).build();
+        let mut output = std::pin::pin!(docker.import_image_stream(opts, stream, None));
+
+        let mut loaded = vec![];
+        while let Some(info) = output.try_next().await.context("unable to load image")? {
+            if let Some(detail) = info.error_detail {
+                bail!(
+                    "unable to load image: {}",
+                    detail.message.unwrap_or_default()
+                );
+            }
+            let line = info.stream.unwrap_or_default();
+            if let Some(name) = line
+                .trim()
+                .strip_prefix("Loaded image: ")
+                .or_else(|| line.trim().strip_prefix("Loaded image ID: "))
+            {
+                loaded.push(name.to_string());
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Adds a new repository and tag to the image, returning the new reference
+    pub async fn tag(
+        &self,
+        docker: &bollard::Docker,
+        repository: &str,
+        tag: &str,
+    ) -> Result<String> {
+        let opts = TagImageOptionsBuilder::default()
+            .repo(repository)
+            .tag(tag)
+            .build();
+        docker
+            .tag_image(&self.id, Some(opts))
+            .await
+            .with_context(|| format!("unable to tag {}", self.get_full_name()))?;
+        Ok(format!("{repository}:{tag}"))
+    }
+
     pub async fn delete(&self, docker: &bollard::Docker, force: bool) -> Result<()> {
         let opts = RemoveImageOptionsBuilder::default().force(force).build();
         docker
@@ -95,6 +198,13 @@ impl DockerImage {
         Ok(())
     }
 
+    async fn inspect_response(&self, docker: &bollard::Docker) -> Result<ImageInspect> {
+        docker
+            .inspect_image(&self.id)
+            .await
+            .context("unable to inspect image")
+    }
+
     pub fn get_full_name(&self) -> String {
         let image = format!("{}:{}", self.name, self.tag);
 
@@ -106,6 +216,7 @@ impl DockerImage {
     }
 }
 
+#[async_trait::async_trait]
 impl Describe for DockerImage {
     fn get_id(&self) -> String {
         self.id.clone()
@@ -113,16 +224,65 @@ impl Describe for DockerImage {
     fn get_name(&self) -> String {
         format!("image: {}", self.name)
     }
-    fn describe(&self) -> Result<Vec<DescribeSection>> {
+    async fn describe(&self, docker: &bollard::Docker) -> Result<Vec<DescribeSection>> {
+        let inspect = self.inspect_response(docker).await?;
+
         let mut summary = DescribeSection::new("Summary");
         summary
-            .item("ID", &self.id)
+            .item_opt("ID", inspect.id)
             .item("Name", &self.name)
             .item("Tag", &self.tag)
-            .item("Created", &self.created)
-            .item("Size", &self.size)
-            .item("Tags", self.tags.join(", "))
-            .item("Digests", self.digests.join(", "));
-        Ok(vec![summary])
+            .item_opt("Created", inspect.created)
+            .item_opt("Size", inspect.size.and_then(format_bytes))
+            .item_opt("Architecture", inspect.architecture)
+            .item_opt("Variant", inspect.variant)
+            .item_opt("OS", inspect.os)
+            .item_opt("Author", inspect.author.filter(|a| !a.is_empty()))
+            .item_opt("Comment", inspect.comment.filter(|c| !c.is_empty()))
+            .item_opt("Tags", inspect.repo_tags.map(|t| t.join(", ")))
+            .item_opt("Digests", inspect.repo_digests.map(|d| d.join(", ")));
+        let mut result = vec![summary];
+
+        if let Some(config) = inspect.config {
+            let mut config_section = DescribeSection::new("Config");
+            config_section
+                .item_opt("User", config.user.filter(|u| !u.is_empty()))
+                .item_opt("Working Dir", config.working_dir)
+                .item_opt("Entrypoint", config.entrypoint.map(|e| e.join(" ")))
+                .item_opt("Command", config.cmd.map(|c| c.join(" ")))
+                .item_opt("Stop Signal", config.stop_signal)
+                .item_opt("Exposed Ports", config.exposed_ports.map(|p| p.join(", ")))
+                .item_opt("Volumes", config.volumes.map(|v| v.join(", ")));
+            if let Some(healthcheck) = &config.healthcheck {
+                config_section.section(healthcheck_section(healthcheck));
+            }
+            result.push(config_section);
+
+            let env = env_section(config.env.unwrap_or_default());
+            if !env.is_empty() {
+                result.push(env);
+            }
+
+            let labels = DescribeSection::from_map("Labels", &config.labels.unwrap_or_default());
+            if !labels.is_empty() {
+                result.push(labels);
+            }
+        }
+
+        if let Some(root_fs) = inspect.root_fs {
+            let mut root_fs_section = DescribeSection::new("Root FS");
+            root_fs_section.item("Type", root_fs.typ);
+            for (idx, layer) in root_fs.layers.unwrap_or_default().iter().enumerate() {
+                root_fs_section.item(format!("Layer {}", idx + 1), layer);
+            }
+            result.push(root_fs_section);
+        }
+
+        Ok(result)
+    }
+
+    async fn inspect(&self, docker: &bollard::Docker) -> Result<serde_json::Value> {
+        let inspect = self.inspect_response(docker).await?;
+        Ok(serde_json::to_value(inspect)?)
     }
 }
@@ -1,7 +1,25 @@
+pub mod build;
+pub mod changes;
+pub mod commit;
 pub mod container;
+pub mod create;
+pub mod credentials;
+pub mod events;
+pub mod exec;
+pub mod files;
+pub mod history;
 pub mod image;
 pub mod logs;
 pub mod network;
+pub mod prune;
+pub mod registry;
+pub mod run;
+pub mod stats;
+pub mod system;
+pub mod terminal;
 pub mod traits;
+pub mod transfer;
+pub mod usage;
 pub mod util;
 pub mod volume;
+pub mod volume_browser;
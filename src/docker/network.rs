@@ -1,10 +1,77 @@
-use bollard::query_parameters::ListNetworksOptionsBuilder;
-use bollard::secret::Network;
-use color_eyre::eyre::Result;
+use bollard::query_parameters::{InspectNetworkOptions, ListNetworksOptionsBuilder};
+use std::net::IpAddr;
+
+use bollard::secret::{
+    EndpointIpamConfig, EndpointResource, EndpointSettings, Network, NetworkConnectRequest,
+    NetworkDisconnectRequest, NetworkInspect,
+};
+use color_eyre::eyre::{Context, ContextCompat, Result, bail};
 
 use crate::docker::traits::DescribeSection;
 
-use super::traits::Describe;
+use super::{create::NetworkCreateConfig, traits::Describe};
+
+/// A container attached to a network, along with its addresses on the network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkEndpoint {
+    pub container_id: String,
+    pub name: String,
+    pub ipv4_address: String,
+    pub ipv6_address: String,
+    pub mac_address: String,
+}
+
+impl NetworkEndpoint {
+    fn from(container_id: String, endpoint: EndpointResource) -> Self {
+        Self {
+            name: endpoint.name.unwrap_or(container_id.clone()),
+            container_id,
+            ipv4_address: endpoint.ipv4_address.unwrap_or_default(),
+            ipv6_address: endpoint.ipv6_address.unwrap_or_default(),
+            mac_address: endpoint.mac_address.unwrap_or_default(),
+        }
+    }
+}
+
+/// Configuration used to connect a container to a network, mirroring the
+/// flags of `docker network connect`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkConnectConfig {
+    pub container_id: String,
+    pub alias: Option<String>,
+    /// A static IPv4 or IPv6 address for the container on the network
+    pub ip: Option<String>,
+}
+
+impl NetworkConnectConfig {
+    /// Builds the request for bollard's connect_network, validating any user
+    /// provided values along the way
+    pub fn to_request(&self) -> Result<NetworkConnectRequest> {
+        let ipam_config = match &self.ip {
+            Some(ip) => match ip.parse::<IpAddr>() {
+                Ok(IpAddr::V4(_)) => Some(EndpointIpamConfig {
+                    ipv4_address: Some(ip.clone()),
+                    ..Default::default()
+                }),
+                Ok(IpAddr::V6(_)) => Some(EndpointIpamConfig {
+                    ipv6_address: Some(ip.clone()),
+                    ..Default::default()
+                }),
+                Err(_) => bail!("invalid IP address \"{ip}\""),
+            },
+            None => None,
+        };
+
+        Ok(NetworkConnectRequest {
+            container: self.container_id.clone(),
+            endpoint_config: Some(EndpointSettings {
+                ipam_config,
+                aliases: self.alias.clone().map(|a| vec![a]),
+                ..Default::default()
+            }),
+        })
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct DockerNetwork {
@@ -40,12 +107,74 @@ impl DockerNetwork {
         Ok(network)
     }
 
+    async fn inspect_response(&self, docker: &bollard::Docker) -> Result<NetworkInspect> {
+        docker
+            .inspect_network(&self.id, None::<InspectNetworkOptions>)
+            .await
+            .context("unable to inspect network")
+    }
+
+    /// Creates a new network from the given config, returning the new network
+    pub async fn create(docker: &bollard::Docker, config: &NetworkCreateConfig) -> Result<Self> {
+        let response = docker
+            .create_network(config.to_request()?)
+            .await
+            .context("unable to create network")?;
+
+        Self::list(docker)
+            .await?
+            .into_iter()
+            .find(|n| n.id == response.id)
+            .with_context(|| format!("no network found with id {}", response.id))
+    }
+
+    /// Lists the containers attached to the network, sorted by name
+    pub async fn endpoints(&self, docker: &bollard::Docker) -> Result<Vec<NetworkEndpoint>> {
+        let inspect = self.inspect_response(docker).await?;
+        let mut endpoints = inspect
+            .containers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, endpoint)| NetworkEndpoint::from(id, endpoint))
+            .collect::<Vec<_>>();
+        endpoints.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(endpoints)
+    }
+
+    pub async fn connect(
+        &self,
+        docker: &bollard::Docker,
+        config: &NetworkConnectConfig,
+    ) -> Result<()> {
+        docker
+            .connect_network(&self.id, config.to_request()?)
+            .await
+            .context("unable to connect container to network")
+    }
+
+    pub async fn disconnect(
+        &self,
+        docker: &bollard::Docker,
+        container_id: &str,
+        force: bool,
+    ) -> Result<()> {
+        let request = NetworkDisconnectRequest {
+            container: container_id.to_string(),
+            force: Some(force),
+        };
+        docker
+            .disconnect_network(&self.id, request)
+            .await
+            .context("unable to disconnect container from network")
+    }
+
     pub async fn delete(&self, docker: &bollard::Docker) -> Result<()> {
         docker.remove_network(&self.get_name()).await?;
         Ok(())
     }
 }
 
+#[async_trait::async_trait]
 impl Describe for DockerNetwork {
     fn get_id(&self) -> String {
         self.get_name()
@@ -54,16 +183,163 @@ impl Describe for DockerNetwork {
         self.name.clone()
     }
 
-    fn describe(&self) -> Result<Vec<DescribeSection>> {
+    async fn describe(&self, docker: &bollard::Docker) -> Result<Vec<DescribeSection>> {
+        let inspect = self.inspect_response(docker).await?;
+
         let mut summary = DescribeSection::new("Summary");
         summary
-            .item("ID", &self.id)
-            .item("Name", &self.name)
-            .item("Driver", &self.driver)
-            .item("Created At", &self.created_at)
-            .item("Scope", &self.scope)
-            .item_opt("Internal", self.internal)
-            .item_opt("Attachable", self.attachable);
-        Ok(vec![summary])
+            .item_opt("ID", inspect.id)
+            .item_opt("Name", inspect.name)
+            .item_opt("Driver", inspect.driver)
+            .item_opt("Created At", inspect.created)
+            .item_opt("Scope", inspect.scope)
+            .item_opt("IPv4", inspect.enable_ipv4)
+            .item_opt("IPv6", inspect.enable_ipv6)
+            .item_opt("Internal", inspect.internal)
+            .item_opt("Attachable", inspect.attachable)
+            .item_opt("Ingress", inspect.ingress);
+        let mut result = vec![summary];
+
+        if let Some(ipam) = inspect.ipam {
+            let mut ipam_section = DescribeSection::new("IPAM");
+            ipam_section.item_opt("Driver", ipam.driver);
+            for (idx, config) in ipam.config.unwrap_or_default().into_iter().enumerate() {
+                let mut config_section = DescribeSection::new(format!("Config {}", idx + 1));
+                config_section
+                    .item_opt("Subnet", config.subnet)
+                    .item_opt("IP Range", config.ip_range)
+                    .item_opt("Gateway", config.gateway);
+                ipam_section.section(config_section);
+            }
+            let options = DescribeSection::from_map("Options", &ipam.options.unwrap_or_default());
+            if !options.is_empty() {
+                ipam_section.section(options);
+            }
+            result.push(ipam_section);
+        }
+
+        let mut containers: Vec<_> = inspect.containers.unwrap_or_default().into_iter().collect();
+        if !containers.is_empty() {
+            containers.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let mut containers_section = DescribeSection::new("Containers");
+            for (id, endpoint) in containers {
+                let mut container_section =
+                    DescribeSection::new(endpoint.name.clone().unwrap_or(id.clone()));
+                container_section
+                    .item("Container ID", id)
+                    .item_opt("Endpoint ID", endpoint.endpoint_id)
+                    .item_opt("MAC Address", endpoint.mac_address)
+                    .item_opt("IPv4 Address", endpoint.ipv4_address)
+                    .item_opt("IPv6 Address", endpoint.ipv6_address);
+                containers_section.section(container_section);
+            }
+            result.push(containers_section);
+        }
+
+        let options = DescribeSection::from_map("Options", &inspect.options.unwrap_or_default());
+        if !options.is_empty() {
+            result.push(options);
+        }
+        let labels = DescribeSection::from_map("Labels", &inspect.labels.unwrap_or_default());
+        if !labels.is_empty() {
+            result.push(labels);
+        }
+
+        Ok(result)
+    }
+
+    async fn inspect(&self, docker: &bollard::Docker) -> Result<serde_json::Value> {
+        let inspect = self.inspect_response(docker).await?;
+        Ok(serde_json::to_value(inspect)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_from() {
+        let endpoint = NetworkEndpoint::from(
+            "abc".into(),
+            EndpointResource {
+                name: Some("web".into()),
+                ipv4_address: Some("172.18.0.2/16".into()),
+                ipv6_address: Some("fd00::2/64".into()),
+                mac_address: Some("02:42:ac:12:00:02".into()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            endpoint,
+            NetworkEndpoint {
+                container_id: "abc".into(),
+                name: "web".into(),
+                ipv4_address: "172.18.0.2/16".into(),
+                ipv6_address: "fd00::2/64".into(),
+                mac_address: "02:42:ac:12:00:02".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_endpoint_from_without_details() {
+        let endpoint = NetworkEndpoint::from("abc".into(), EndpointResource::default());
+        assert_eq!(endpoint.name, "abc");
+        assert_eq!(endpoint.ipv4_address, "");
+        assert_eq!(endpoint.ipv6_address, "");
+    }
+
+    #[test]
+    fn test_connect_request() {
+        let config = NetworkConnectConfig {
+            container_id: "abc".into(),
+            alias: Some("db".into()),
+            ip: Some("10.0.0.5".into()),
+        };
+        let request = config.to_request().unwrap();
+        assert_eq!(request.container, "abc");
+        let endpoint = request.endpoint_config.unwrap();
+        assert_eq!(endpoint.aliases, Some(vec!["db".to_string()]));
+        let ipam = endpoint.ipam_config.unwrap();
+        assert_eq!(ipam.ipv4_address.as_deref(), Some("10.0.0.5"));
+        assert_eq!(ipam.ipv6_address, None);
+    }
+
+    #[test]
+    fn test_connect_request_ipv6() {
+        let config = NetworkConnectConfig {
+            container_id: "abc".into(),
+            ip: Some("fd00::5".into()),
+            ..Default::default()
+        };
+        let ipam = config
+            .to_request()
+            .unwrap()
+            .endpoint_config
+            .unwrap()
+            .ipam_config
+            .unwrap();
+        assert_eq!(ipam.ipv4_address, None);
+        assert_eq!(ipam.ipv6_address.as_deref(), Some("fd00::5"));
+    }
+
+    #[test]
+    fn test_connect_request_without_options() {
+        let request = NetworkConnectConfig {
+            container_id: "abc".into(),
+            ..Default::default()
+        }
+        .to_request()
+        .unwrap();
+        let endpoint = request.endpoint_config.unwrap();
+        assert_eq!(endpoint.aliases, None);
+        assert_eq!(endpoint.ipam_config, None);
+
+        let invalid = NetworkConnectConfig {
+            ip: Some("10.0.0".into()),
+            ..Default::default()
+        };
+        assert!(invalid.to_request().is_err());
     }
 }
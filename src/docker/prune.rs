@@ -0,0 +1,538 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bollard::query_parameters::{
+    DataUsageOptionsBuilder, ListNetworksOptionsBuilder, PruneBuildOptionsBuilder,
+    PruneContainersOptionsBuilder, PruneImagesOptionsBuilder, PruneNetworksOptionsBuilder,
+    PruneVolumesOptionsBuilder,
+};
+use bollard::secret::{
+    BuildCache, ContainerSummary, ContainerSummaryStateEnum, ImageSummary, Network, Volume,
+};
+use chrono::DateTime;
+use color_eyre::eyre::{Context, Result, bail};
+
+use super::{
+    container::DockerContainer,
+    system::parse_items,
+    util::{format_bytes, matches_labels, parse_labels, short_id},
+};
+
+/// Networks created by the daemon, which are never pruned
+const PREDEFINED_NETWORKS: [&str; 3] = ["bridge", "host", "none"];
+
+/// Label the daemon gives to anonymous volumes; only these are pruned unless
+/// named volumes are included
+const ANONYMOUS_VOLUME_LABEL: &str = "com.docker.volume.anonymous";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneCategory {
+    Containers,
+    Images,
+    Volumes,
+    Networks,
+    BuildCache,
+}
+
+impl Display for PruneCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Containers => "containers",
+            Self::Images => "images",
+            Self::Volumes => "volumes",
+            Self::Networks => "networks",
+            Self::BuildCache => "build cache records",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What to prune, mirroring the flags of the various `docker * prune` commands.
+///
+/// Labels are provided as a comma separated list in the form `KEY` or
+/// `KEY=value`, all of which must match.  `until` is either a duration such as
+/// `24h` or `1h30m`, or an RFC 3339 timestamp.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneConfig {
+    /// Stopped containers
+    pub containers: bool,
+    /// Dangling images not used by any container
+    pub images: bool,
+    /// Include tagged images not used by any container
+    pub all_images: bool,
+    /// Anonymous volumes not used by any container
+    pub volumes: bool,
+    /// Include named volumes not used by any container
+    pub all_volumes: bool,
+    /// Custom networks not used by any container
+    pub networks: bool,
+    /// Build cache not in use by a build
+    pub build_cache: bool,
+    pub labels: Option<String>,
+    pub until: Option<String>,
+}
+
+impl PruneConfig {
+    fn label_filters(&self) -> Result<Vec<(String, Option<String>)>> {
+        self.labels
+            .as_deref()
+            .map(parse_labels)
+            .unwrap_or(Ok(vec![]))
+    }
+
+    /// The unix timestamp before which resources must have been created to be
+    /// pruned
+    fn until_timestamp(&self) -> Result<Option<i64>> {
+        self.until.as_deref().map(parse_until).transpose()
+    }
+
+    /// Filters for the prune requests; the same cutoff is used by the preview
+    /// so that the two match
+    fn filters(&self, category: PruneCategory) -> Result<HashMap<String, Vec<String>>> {
+        let mut filters = HashMap::new();
+        let labels = self.label_filters()?;
+        if !labels.is_empty() {
+            filters.insert(
+                "label".into(),
+                labels
+                    .iter()
+                    .map(|(key, value)| match value {
+                        Some(value) => format!("{key}={value}"),
+                        None => key.clone(),
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(until) = self.until_timestamp()? {
+            // Build cache pruning only accepts a duration
+            let until = if category == PruneCategory::BuildCache {
+                format!("{}s", (now() - until).max(0))
+            } else {
+                until.to_string()
+            };
+            filters.insert("until".into(), vec![until]);
+        }
+        match category {
+            PruneCategory::Images => {
+                filters.insert("dangling".into(), vec![(!self.all_images).to_string()]);
+            }
+            PruneCategory::Volumes if self.all_volumes => {
+                filters.insert("all".into(), vec!["true".into()]);
+            }
+            _ => {}
+        }
+        Ok(filters)
+    }
+
+    /// Volumes can't be filtered by age, and build cache records have no
+    /// labels, so these are skipped when the corresponding filter is set
+    fn includes(&self, category: PruneCategory) -> bool {
+        match category {
+            PruneCategory::Containers => self.containers,
+            PruneCategory::Images => self.images,
+            PruneCategory::Volumes => self.volumes && self.until.is_none(),
+            PruneCategory::Networks => self.networks,
+            PruneCategory::BuildCache => self.build_cache && self.labels.is_none(),
+        }
+    }
+
+    /// Categories which were selected but are skipped due to the filters
+    pub fn skipped(&self) -> Vec<PruneCategory> {
+        [
+            (PruneCategory::Volumes, self.volumes),
+            (PruneCategory::BuildCache, self.build_cache),
+        ]
+        .into_iter()
+        .filter(|(category, selected)| *selected && !self.includes(*category))
+        .map(|(category, _)| category)
+        .collect()
+    }
+}
+
+/// A resource which will be removed by a prune
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneCandidate {
+    pub category: PruneCategory,
+    pub id: String,
+    pub name: String,
+    /// The space expected to be reclaimed; images only count the layers which
+    /// aren't shared with other images
+    pub size: Option<i64>,
+}
+
+/// Lists exactly which resources a prune with the config would remove.
+///
+/// Containers are pruned first, so images, volumes and networks which are only
+/// used by containers being pruned are treated as unused, as `docker system
+/// prune` does.
+pub async fn preview(
+    docker: &bollard::Docker,
+    config: &PruneConfig,
+) -> Result<Vec<PruneCandidate>> {
+    let labels = config.label_filters()?;
+    let until = config.until_timestamp()?;
+    let is_old = |created: Option<i64>| match (until, created) {
+        (Some(until), Some(created)) => created < until,
+        (Some(_), None) => false,
+        (None, _) => true,
+    };
+
+    let opts = DataUsageOptionsBuilder::default().verbose(true).build();
+    let df = docker
+        .df(Some(opts))
+        .await
+        .context("unable to retrieve disk usage")?;
+
+    let containers: Vec<ContainerSummary> =
+        parse_items(df.containers_disk_usage.and_then(|u| u.items))?;
+    let mut candidates = vec![];
+    let mut remaining = vec![];
+    for c in containers {
+        let stopped = matches!(
+            c.state,
+            Some(
+                ContainerSummaryStateEnum::CREATED
+                    | ContainerSummaryStateEnum::EXITED
+                    | ContainerSummaryStateEnum::DEAD
+            )
+        );
+        if config.includes(PruneCategory::Containers)
+            && stopped
+            && matches_labels(c.labels.as_ref(), &labels)
+            && is_old(c.created)
+        {
+            candidates.push(PruneCandidate {
+                category: PruneCategory::Containers,
+                id: c.id.clone().unwrap_or_default(),
+                name: DockerContainer::from(c.clone()).names,
+                size: c.size_rw,
+            });
+        } else {
+            remaining.push(DockerContainer::from(c));
+        }
+    }
+
+    let used_images: HashSet<&str> = remaining.iter().map(|c| c.image_id.as_str()).collect();
+    let used_volumes: HashSet<&str> = remaining
+        .iter()
+        .flat_map(|c| c.volumes.iter().map(String::as_str))
+        .collect();
+    let used_networks: HashSet<&str> = remaining
+        .iter()
+        .flat_map(|c| c.networks.iter().map(String::as_str))
+        .collect();
+
+    if config.includes(PruneCategory::Images) {
+        let images: Vec<ImageSummary> = parse_items(df.images_disk_usage.and_then(|u| u.items))?;
+        candidates.extend(
+            images
+                .into_iter()
+                .filter(|i| !used_images.contains(i.id.as_str()))
+                .filter(|i| config.all_images || is_dangling(i))
+                .filter(|i| matches_labels(Some(&i.labels), &labels) && is_old(Some(i.created)))
+                .map(|i| PruneCandidate {
+                    category: PruneCategory::Images,
+                    name: i
+                        .repo_tags
+                        .first()
+                        .filter(|_| !is_dangling(&i))
+                        .cloned()
+                        .unwrap_or(short_id(&i.id)),
+                    id: i.id,
+                    size: Some(i.size - i.shared_size.max(0)),
+                }),
+        );
+    }
+
+    if config.includes(PruneCategory::Volumes) {
+        let volumes: Vec<Volume> = parse_items(df.volumes_disk_usage.and_then(|u| u.items))?;
+        candidates.extend(
+            volumes
+                .into_iter()
+                .filter(|v| !used_volumes.contains(v.name.as_str()))
+                .filter(|v| config.all_volumes || v.labels.contains_key(ANONYMOUS_VOLUME_LABEL))
+                .filter(|v| matches_labels(Some(&v.labels), &labels))
+                .map(|v| PruneCandidate {
+                    category: PruneCategory::Volumes,
+                    id: v.name.clone(),
+                    size: v.usage_data.map(|u| u.size).filter(|s| *s >= 0),
+                    name: v.name,
+                }),
+        );
+    }
+
+    if config.includes(PruneCategory::Networks) {
+        let opts = ListNetworksOptionsBuilder::default().build();
+        let networks: Vec<Network> = docker
+            .list_networks(Some(opts))
+            .await
+            .context("unable to retrieve list of networks")?;
+        candidates.extend(
+            networks
+                .into_iter()
+                .filter(|n| !PREDEFINED_NETWORKS.contains(&n.name.as_deref().unwrap_or_default()))
+                .filter(|n| n.scope.as_deref() != Some("swarm"))
+                .filter(|n| !used_networks.contains(n.id.as_deref().unwrap_or_default()))
+                .filter(|n| {
+                    matches_labels(n.labels.as_ref(), &labels)
+                        && is_old(n.created.as_deref().and_then(parse_timestamp))
+                })
+                .map(|n| PruneCandidate {
+                    category: PruneCategory::Networks,
+                    id: n.id.unwrap_or_default(),
+                    name: n.name.unwrap_or_default(),
+                    size: None,
+                }),
+        );
+    }
+
+    if config.includes(PruneCategory::BuildCache) {
+        let records: Vec<BuildCache> =
+            parse_items(df.build_cache_disk_usage.and_then(|u| u.items))?;
+        candidates.extend(
+            records
+                .into_iter()
+                .filter(|r| !r.in_use.unwrap_or_default())
+                .filter(|r| {
+                    let last_used = r.last_used_at.as_ref().or(r.created_at.as_ref());
+                    is_old(last_used.and_then(|t| parse_timestamp(t)))
+                })
+                .map(|r| PruneCandidate {
+                    category: PruneCategory::BuildCache,
+                    name: r
+                        .description
+                        .clone()
+                        .filter(|d| !d.is_empty())
+                        .unwrap_or(short_id(r.id.as_deref().unwrap_or_default())),
+                    id: r.id.unwrap_or_default(),
+                    size: r.size,
+                }),
+        );
+    }
+
+    Ok(candidates)
+}
+
+/// The total space expected to be reclaimed by removing the candidates
+pub fn estimated_size(candidates: &[PruneCandidate]) -> i64 {
+    candidates.iter().filter_map(|c| c.size).sum()
+}
+
+/// The outcome of a prune, as reported by the daemon
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub removed: Vec<(PruneCategory, usize)>,
+    pub reclaimed: i64,
+}
+
+impl Display for PruneReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let removed = self
+            .removed
+            .iter()
+            .map(|(category, count)| format!("{count} {category}"))
+            .collect::<Vec<_>>();
+        let removed = if removed.is_empty() {
+            "nothing".into()
+        } else {
+            removed.join(", ")
+        };
+        write!(
+            f,
+            "Removed {removed}, reclaiming {}",
+            format_bytes(self.reclaimed).unwrap_or_default()
+        )
+    }
+}
+
+/// Prunes the selected categories, containers first so that anything only
+/// they used is also pruned
+pub async fn prune(docker: &bollard::Docker, config: &PruneConfig) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    let mut record = |category, removed: usize, reclaimed: Option<i64>| {
+        report.removed.push((category, removed));
+        report.reclaimed += reclaimed.unwrap_or_default();
+    };
+
+    if config.includes(PruneCategory::Containers) {
+        let filters = config.filters(PruneCategory::Containers)?;
+        let opts = PruneContainersOptionsBuilder::default()
+            .filters(&filters)
+            .build();
+        let res = docker
+            .prune_containers(Some(opts))
+            .await
+            .context("unable to prune containers")?;
+        let removed = res.containers_deleted.unwrap_or_default().len();
+        record(PruneCategory::Containers, removed, res.space_reclaimed);
+    }
+
+    if config.includes(PruneCategory::Images) {
+        let filters = config.filters(PruneCategory::Images)?;
+        let opts = PruneImagesOptionsBuilder::default()
+            .filters(&filters)
+            .build();
+        let res = docker
+            .prune_images(Some(opts))
+            .await
+            .context("unable to prune images")?;
+        // Untagging an image is reported alongside deleting it
+        let removed = res
+            .images_deleted
+            .unwrap_or_default()
+            .iter()
+            .filter(|i| i.deleted.is_some())
+            .count();
+        record(PruneCategory::Images, removed, res.space_reclaimed);
+    }
+
+    if config.includes(PruneCategory::Volumes) {
+        let filters = config.filters(PruneCategory::Volumes)?;
+        let opts = PruneVolumesOptionsBuilder::default()
+            .filters(&filters)
+            .build();
+        let res = docker
+            .prune_volumes(Some(opts))
+            .await
+            .context("unable to prune volumes")?;
+        let removed = res.volumes_deleted.unwrap_or_default().len();
+        record(PruneCategory::Volumes, removed, res.space_reclaimed);
+    }
+
+    if config.includes(PruneCategory::Networks) {
+        let filters = config.filters(PruneCategory::Networks)?;
+        let opts = PruneNetworksOptionsBuilder::default()
+            .filters(&filters)
+            .build();
+        let res = docker
+            .prune_networks(Some(opts))
+            .await
+            .context("unable to prune networks")?;
+        let removed = res.networks_deleted.unwrap_or_default().len();
+        record(PruneCategory::Networks, removed, None);
+    }
+
+    if config.includes(PruneCategory::BuildCache) {
+        let filters = config.filters(PruneCategory::BuildCache)?;
+        let opts = PruneBuildOptionsBuilder::default()
+            .all(true)
+            .filters(&filters)
+            .build();
+        let res = docker
+            .prune_build(Some(opts))
+            .await
+            .context("unable to prune build cache")?;
+        let removed = res.caches_deleted.unwrap_or_default().len();
+        record(PruneCategory::BuildCache, removed, res.space_reclaimed);
+    }
+
+    Ok(report)
+}
+
+/// Validates an optional duration, eg `24h`, or RFC 3339 timestamp
+pub fn validate_until(until: &str) -> Result<()> {
+    if until.trim().is_empty() {
+        return Ok(());
+    }
+    parse_until(until.trim()).map(|_| ())
+}
+
+fn parse_until(until: &str) -> Result<i64> {
+    if let Some(timestamp) = parse_timestamp(until) {
+        return Ok(timestamp);
+    }
+
+    let mut seconds = 0;
+    let mut digits = String::new();
+    for c in until.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 60 * 60,
+            'm' => 60,
+            's' => 1,
+            _ => bail!("expected a duration, eg 24h, or a timestamp, got \"{until}\""),
+        };
+        let Some(value) = digits
+            .parse::<i64>()
+            .ok()
+            .and_then(|v| v.checked_mul(unit))
+            .and_then(|v| v.checked_add(seconds))
+        else {
+            bail!("expected a duration, eg 24h, or a timestamp, got \"{until}\"");
+        };
+        seconds = value;
+        digits.clear();
+    }
+    if !digits.is_empty() || seconds == 0 {
+        bail!("expected a duration, eg 24h, or a timestamp, got \"{until}\"");
+    }
+    Ok(now() - seconds)
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.timestamp())
+}
+
+fn is_dangling(image: &ImageSummary) -> bool {
+    image
+        .repo_tags
+        .iter()
+        .all(|t| t.is_empty() || t == "<none>:<none>")
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ago(until: &str, seconds: i64) {
+        let expected = now() - seconds;
+        let actual = parse_until(until).unwrap();
+        assert!(
+            (expected - actual).abs() <= 1,
+            "{until} parsed as {actual}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn test_parse_until_duration() {
+        assert_ago("24h", 24 * 60 * 60);
+        assert_ago("90m", 90 * 60);
+        assert_ago("30s", 30);
+        assert_ago("1h30m15s", 60 * 60 + 30 * 60 + 15);
+    }
+
+    #[test]
+    fn test_parse_until_timestamp() {
+        assert_eq!(parse_until("2024-01-31T12:00:00Z").unwrap(), 1706702400);
+        assert_eq!(
+            parse_until("2024-01-31T12:00:00+01:00").unwrap(),
+            1706698800
+        );
+    }
+
+    #[test]
+    fn test_parse_until_invalid() {
+        assert!(parse_until("").is_err());
+        assert!(parse_until("24").is_err());
+        assert!(parse_until("h").is_err());
+        assert!(parse_until("0h").is_err());
+        assert!(parse_until("24d").is_err());
+        assert!(parse_until("1.5h").is_err());
+        assert!(parse_until("2024-01-31").is_err());
+        assert!(parse_until("9999999999999999999h").is_err());
+        assert!(parse_until("999999999999999999h").is_err());
+    }
+}
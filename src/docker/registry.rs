@@ -0,0 +1,279 @@
+use bollard::auth::DockerCredentials;
+use bollard::query_parameters::{CreateImageOptionsBuilder, PushImageOptionsBuilder};
+use bollard::secret::{CreateImageInfo, PushImageInfo};
+use color_eyre::eyre::{Context, Result, bail};
+use futures::{Stream, StreamExt};
+
+use super::commit::DEFAULT_TAG;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerPhase {
+    Waiting,
+    Downloading,
+    /// Downloaded and waiting to be extracted
+    Downloaded,
+    Extracting,
+    Complete,
+}
+
+impl LayerPhase {
+    fn from_status(status: &str) -> Self {
+        match status {
+            "Downloading" => Self::Downloading,
+            "Verifying Checksum" | "Download complete" => Self::Downloaded,
+            "Extracting" => Self::Extracting,
+            "Pull complete" | "Already exists" => Self::Complete,
+            _ => Self::Waiting,
+        }
+    }
+}
+
+impl std::fmt::Display for LayerPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Waiting => "Waiting",
+            Self::Downloading => "Downloading",
+            Self::Downloaded => "Downloaded",
+            Self::Extracting => "Extracting",
+            Self::Complete => "Complete",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerProgress {
+    pub id: String,
+    pub phase: LayerPhase,
+    /// Bytes downloaded or extracted so far in the current phase
+    pub current: Option<i64>,
+    pub total: Option<i64>,
+}
+
+impl LayerProgress {
+    /// The fraction of the current phase which has completed, where known
+    pub fn ratio(&self) -> Option<f64> {
+        match (self.phase, self.current, self.total) {
+            (LayerPhase::Complete | LayerPhase::Downloaded, _, _) => Some(1.0),
+            (_, Some(current), Some(total)) if total > 0 => {
+                Some((current as f64 / total as f64).clamp(0.0, 1.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The progress of an image pull, built up from the daemon's progress messages
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PullProgress {
+    /// Layers in the order the daemon first reported them
+    pub layers: Vec<LayerProgress>,
+    /// Messages which aren't about a specific layer, eg the digest pulled
+    pub messages: Vec<String>,
+}
+
+impl PullProgress {
+    /// Applies a single progress message from the daemon, failing if the
+    /// message reports an error
+    pub fn update(&mut self, info: CreateImageInfo) -> Result<()> {
+        if let Some(detail) = info.error_detail {
+            bail!("{}", detail.message.unwrap_or_default());
+        }
+        let status = info.status.unwrap_or_default();
+
+        // The first message reports the tag being pulled in the id field, eg
+        // `{"status": "Pulling from library/nginx", "id": "latest"}`
+        match info.id {
+            Some(id) if !status.starts_with("Pulling from") => {
+                let (current, total) = info
+                    .progress_detail
+                    .map(|d| (d.current, d.total))
+                    .unwrap_or_default();
+                let layer = LayerProgress {
+                    id: id.clone(),
+                    phase: LayerPhase::from_status(&status),
+                    current,
+                    total,
+                };
+
+                match self.layers.iter_mut().find(|l| l.id == id) {
+                    Some(existing) => *existing = layer,
+                    None => self.layers.push(layer),
+                }
+            }
+            _ if !status.is_empty() => self.messages.push(status),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// The progress of an image push, built up from the daemon's progress messages.
+/// Unlike a pull, push messages aren't attributed to a layer, so only the
+/// number of layers completed and the most recent upload are tracked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushProgress {
+    pub layers: usize,
+    pub complete: usize,
+    /// Bytes uploaded and the size of the layer most recently reported
+    pub current: Option<(i64, i64)>,
+    /// Messages which aren't about a layer, eg the digest pushed
+    pub messages: Vec<String>,
+}
+
+impl PushProgress {
+    /// Applies a single progress message from the daemon, failing if the
+    /// message reports an error
+    pub fn update(&mut self, info: PushImageInfo) -> Result<()> {
+        if let Some(detail) = info.error_detail {
+            bail!("{}", detail.message.unwrap_or_default());
+        }
+        let status = info.status.unwrap_or_default();
+
+        match status.as_str() {
+            // Every layer is reported as preparing before any are pushed
+            "Preparing" => self.layers += 1,
+            "Waiting" | "" => {}
+            "Pushing" => {
+                self.current = info
+                    .progress_detail
+                    .and_then(|d| d.current.zip(d.total))
+                    .filter(|(_, total)| *total > 0)
+            }
+            "Pushed" | "Layer already exists" => self.complete += 1,
+            s if s.starts_with("Mounted from") => self.complete += 1,
+            _ => self.messages.push(status),
+        }
+        Ok(())
+    }
+
+    /// The fraction of the layer most recently reported which has been uploaded
+    pub fn ratio(&self) -> Option<f64> {
+        self.current
+            .map(|(current, total)| (current as f64 / total as f64).clamp(0.0, 1.0))
+    }
+}
+
+/// Splits an image reference into the image and tag to pull, defaulting the
+/// tag to `latest`.  References pinned to a digest have no tag.
+pub fn split_reference(reference: &str) -> (String, Option<String>) {
+    if reference.contains('@') {
+        return (reference.to_string(), None);
+    }
+    // A colon before the last slash is a registry port rather than a tag
+    let name_start = reference.rfind('/').map(|i| i + 1).unwrap_or_default();
+    match reference[name_start..].rsplit_once(':') {
+        Some((name, tag)) => (
+            format!("{}{name}", &reference[..name_start]),
+            Some(tag.to_string()),
+        ),
+        None => (reference.to_string(), Some(DEFAULT_TAG.to_string())),
+    }
+}
+
+/// Validates an image reference to be pulled or pushed, eg `nginx:1.27`
+pub fn validate_reference(reference: &str) -> Result<()> {
+    if reference.trim().is_empty() {
+        bail!("an image is required");
+    }
+    if reference.trim().contains(char::is_whitespace) {
+        bail!("image references may not contain whitespace");
+    }
+    Ok(())
+}
+
+/// Pulls an image, returning a stream of the daemon's progress messages
+pub fn pull(
+    docker: &bollard::Docker,
+    reference: &str,
+) -> impl Stream<Item = Result<CreateImageInfo>> + use<> {
+    let (image, tag) = split_reference(reference);
+    let mut builder = CreateImageOptionsBuilder::default().from_image(&image);
+    if let Some(tag) = &tag {
+        builder = builder.tag(tag);
+    }
+
+    docker
+        .create_image(Some(builder.build()), None, None)
+        .map(move |info| info.with_context(|| format!("unable to pull {image}")))
+}
+
+/// Pushes an image to its registry, returning a stream of the daemon's
+/// progress messages
+pub fn push(
+    docker: &bollard::Docker,
+    reference: &str,
+    credentials: Option<DockerCredentials>,
+) -> impl Stream<Item = Result<PushImageInfo>> + use<> {
+    let (image, tag) = split_reference(reference);
+    let mut builder = PushImageOptionsBuilder::default();
+    if let Some(tag) = &tag {
+        builder = builder.tag(tag);
+    }
+
+    docker
+        .push_image(&image, Some(builder.build()), credentials)
+        .map(move |info| info.with_context(|| format!("unable to push {image}")))
+}
+
+/// Whether an error from the registry means credentials are missing or were
+/// rejected, in which case the user can be asked to log in
+pub fn is_auth_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "unauthorized",
+        "authentication required",
+        "denied",
+        "no basic auth",
+    ]
+    .iter()
+    .any(|e| error.contains(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reference() {
+        assert_eq!(
+            split_reference("nginx"),
+            ("nginx".to_string(), Some("latest".to_string()))
+        );
+        assert_eq!(
+            split_reference("nginx:1.27"),
+            ("nginx".to_string(), Some("1.27".to_string()))
+        );
+        assert_eq!(
+            split_reference("ghcr.io/org/app:v2"),
+            ("ghcr.io/org/app".to_string(), Some("v2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_reference_registry_port() {
+        assert_eq!(
+            split_reference("localhost:5000/app"),
+            ("localhost:5000/app".to_string(), Some("latest".to_string()))
+        );
+        assert_eq!(
+            split_reference("localhost:5000/app:1.0"),
+            ("localhost:5000/app".to_string(), Some("1.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_reference_digest() {
+        let reference = "nginx@sha256:0123456789abcdef";
+        assert_eq!(split_reference(reference), (reference.to_string(), None));
+    }
+
+    #[test]
+    fn test_validate_reference() {
+        assert!(validate_reference("nginx:1.27").is_ok());
+        assert!(validate_reference(" localhost:5000/app ").is_ok());
+        assert!(validate_reference("").is_err());
+        assert!(validate_reference("   ").is_err());
+        assert!(validate_reference("nginx 1.27").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use std::{collections::HashMap, env, net::IpAddr};
+
+use bollard::secret::{
+    ContainerCreateBody, HostConfig, PortBinding, RestartPolicy, RestartPolicyNameEnum,
+};
+use color_eyre::eyre::{Result, bail};
+
+use super::util::{parse_key_value, split_command};
+
+pub const RESTART_POLICIES: [&str; 4] = ["no", "always", "unless-stopped", "on-failure"];
+
+/// The network mode used when the user doesn't pick a network; the daemon will
+/// attach the container to the default bridge network
+pub const DEFAULT_NETWORK: &str = "default";
+
+/// Configuration used to create a new container from an image, mirroring the
+/// most commonly used flags of `docker run`.
+///
+/// The command is split into arguments as a shell would, eg `sh -c "echo hi"`.
+/// Env vars, ports and volumes are provided as comma separated lists in the
+/// same format as the docker cli, eg `8080:80,127.0.0.1:5432:5432/tcp`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerRunConfig {
+    pub image: String,
+    pub name: Option<String>,
+    pub command: Option<String>,
+    pub env: Option<String>,
+    pub ports: Option<String>,
+    pub volumes: Option<String>,
+    pub network: Option<String>,
+    pub restart_policy: Option<String>,
+    pub auto_remove: bool,
+}
+
+impl ContainerRunConfig {
+    /// Builds the body for bollard's create_container, validating any user
+    /// provided lists along the way
+    pub fn to_create_body(&self) -> Result<ContainerCreateBody> {
+        let cmd = match &self.command {
+            Some(command) => Some(split_command(command)?),
+            None => None,
+        };
+
+        let env = match &self.env {
+            Some(env) => Some(parse_env(env)?),
+            None => None,
+        };
+
+        let (exposed_ports, port_bindings) = match &self.ports {
+            Some(ports) => {
+                let ports = parse_ports(ports)?;
+                let exposed_ports = ports.keys().cloned().collect();
+                (Some(exposed_ports), Some(ports))
+            }
+            None => (None, None),
+        };
+
+        let binds = match &self.volumes {
+            Some(volumes) => Some(parse_volumes(volumes)?),
+            None => None,
+        };
+
+        let network_mode = self
+            .network
+            .clone()
+            .filter(|n| n.as_str() != DEFAULT_NETWORK);
+
+        let restart_policy = match &self.restart_policy {
+            Some(policy) => Some(RestartPolicy {
+                name: Some(parse_restart_policy(policy)?),
+                ..Default::default()
+            }),
+            None => None,
+        };
+
+        Ok(ContainerCreateBody {
+            image: Some(self.image.clone()),
+            cmd,
+            env,
+            exposed_ports,
+            host_config: Some(HostConfig {
+                binds,
+                network_mode,
+                port_bindings,
+                restart_policy,
+                auto_remove: Some(self.auto_remove),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+/// Validates a command, which may quote or escape arguments containing spaces
+pub fn validate_command(command: &str) -> Result<()> {
+    split_command(command).map(|_| ())
+}
+
+/// Validates a comma separated list of env vars in the form `KEY=value` or `KEY`
+pub fn validate_env(env: &str) -> Result<()> {
+    parse_env(env).map(|_| ())
+}
+
+/// Validates a comma separated list of port mappings in the form
+/// `[ip:][host_port:]container_port[/protocol]`, where IPv6 addresses are
+/// bracketed, eg `[::1]:8080:80`
+pub fn validate_ports(ports: &str) -> Result<()> {
+    parse_ports(ports).map(|_| ())
+}
+
+/// Validates a comma separated list of volume mounts in the form
+/// `source:target[:options]`
+pub fn validate_volumes(volumes: &str) -> Result<()> {
+    parse_volumes(volumes).map(|_| ())
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Splits a comma separated list of env vars, where a comma following a
+/// `KEY=value` only starts a new entry when it is followed by another `KEY=`,
+/// so that values such as `NO_PROXY=localhost,127.0.0.1` can contain commas
+fn split_env(env: &str) -> Vec<&str> {
+    let starts_with_key = |part: &str| {
+        part.trim_start().split_once('=').is_some_and(|(key, _)| {
+            key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    };
+
+    let mut entries: Vec<(usize, usize)> = vec![];
+    let mut start = 0;
+    for part in env.split(',') {
+        let end = start + part.len();
+        match entries.last_mut() {
+            Some(last)
+                if env[last.0..last.1].contains('=')
+                    && !part.trim().is_empty()
+                    && !starts_with_key(part) =>
+            {
+                last.1 = end
+            }
+            _ => entries.push((start, end)),
+        }
+        start = end + 1;
+    }
+
+    entries
+        .into_iter()
+        .map(|(start, end)| env[start..end].trim())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Parses env vars in the same way as `docker run -e`; a bare `KEY` takes its
+/// value from ducker's own environment, and is otherwise passed on as is
+fn parse_env(env: &str) -> Result<Vec<String>> {
+    split_env(env)
+        .into_iter()
+        .map(|v| {
+            let (key, value) = parse_key_value(v, true)?;
+            Ok(match value.or_else(|| env::var(&key).ok()) {
+                Some(value) => format!("{key}={value}"),
+                None => key,
+            })
+        })
+        .collect()
+}
+
+fn parse_ports(ports: &str) -> Result<HashMap<String, Option<Vec<PortBinding>>>> {
+    let mut port_map: HashMap<String, Option<Vec<PortBinding>>> = HashMap::new();
+
+    for mapping in split_list(ports) {
+        let (mapping, protocol) = match mapping.split_once('/') {
+            Some((m, p @ ("tcp" | "udp" | "sctp"))) => (m, p),
+            Some((_, p)) => bail!("unknown protocol \"{p}\""),
+            None => (mapping, "tcp"),
+        };
+
+        // IPv6 addresses contain colons, so must be bracketed, eg `[::1]:8080:80`
+        let (bracketed_ip, ports) = match mapping.strip_prefix('[') {
+            Some(rest) => match rest.split_once("]:") {
+                Some((ip, ports)) => (Some(ip), ports),
+                None => bail!("expected [ip]:host_port:container_port, got \"{mapping}\""),
+            },
+            None => (None, mapping),
+        };
+
+        let parts: Vec<&str> = ports.split(':').collect();
+        let (host_ip, host_port, container_port) = match (bracketed_ip, parts.as_slice()) {
+            (None, [container]) => (None, None, *container),
+            (None, [host, container]) => (None, Some(*host), *container),
+            (None, [ip, host, container]) => (Some(*ip), Some(*host), *container),
+            (Some(ip), [host, container]) => (Some(ip), Some(*host), *container),
+            _ => bail!("expected [ip:][host_port:]container_port, got \"{mapping}\""),
+        };
+
+        if let Some(ip) = host_ip
+            && ip.parse::<IpAddr>().is_err()
+        {
+            bail!("invalid IP address \"{ip}\"");
+        }
+
+        // An empty host port, eg `127.0.0.1::80`, binds a random port
+        let host_port = host_port.filter(|p| !p.is_empty());
+        for port in [Some(container_port), host_port].into_iter().flatten() {
+            if port.parse::<u16>().is_err() {
+                bail!("invalid port \"{port}\"");
+            }
+        }
+
+        let binding = PortBinding {
+            host_ip: host_ip.map(String::from),
+            host_port: host_port.map(String::from),
+        };
+
+        port_map
+            .entry(format!("{container_port}/{protocol}"))
+            .or_insert_with(|| Some(vec![]))
+            .get_or_insert_with(Vec::new)
+            .push(binding);
+    }
+
+    Ok(port_map)
+}
+
+fn parse_volumes(volumes: &str) -> Result<Vec<String>> {
+    split_list(volumes)
+        .map(|v| match v.split(':').collect::<Vec<&str>>().as_slice() {
+            [source, target] | [source, target, _] if !source.is_empty() && !target.is_empty() => {
+                Ok(v.to_string())
+            }
+            _ => bail!("expected source:target[:options], got \"{v}\""),
+        })
+        .collect()
+}
+
+fn parse_restart_policy(policy: &str) -> Result<RestartPolicyNameEnum> {
+    let policy = match policy {
+        "no" => RestartPolicyNameEnum::NO,
+        "always" => RestartPolicyNameEnum::ALWAYS,
+        "unless-stopped" => RestartPolicyNameEnum::UNLESS_STOPPED,
+        "on-failure" => RestartPolicyNameEnum::ON_FAILURE,
+        _ => bail!("unknown restart policy \"{policy}\""),
+    };
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(ip: Option<&str>, port: Option<&str>) -> PortBinding {
+        PortBinding {
+            host_ip: ip.map(String::from),
+            host_port: port.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_parse_env() {
+        assert_eq!(
+            parse_env("A=1, B=, C=x=y").unwrap(),
+            vec!["A=1", "B=", "C=x=y"]
+        );
+        assert!(parse_env("=1").is_err());
+    }
+
+    #[test]
+    fn test_parse_env_commas_in_value() {
+        assert_eq!(
+            parse_env("NO_PROXY=localhost,127.0.0.1, A=a,b,c=d ,B=2,").unwrap(),
+            vec!["NO_PROXY=localhost,127.0.0.1", "A=a,b", "c=d", "B=2"]
+        );
+        assert_eq!(
+            parse_env("DUCKER_TEST_UNSET_VAR, A=a,b").unwrap(),
+            vec!["DUCKER_TEST_UNSET_VAR", "A=a,b"]
+        );
+    }
+
+    #[test]
+    fn test_parse_env_bare_key() {
+        let path = env::var("PATH").unwrap();
+        assert_eq!(parse_env("PATH").unwrap(), vec![format!("PATH={path}")]);
+        assert_eq!(
+            parse_env("DUCKER_TEST_UNSET_VAR").unwrap(),
+            vec!["DUCKER_TEST_UNSET_VAR"]
+        );
+    }
+
+    #[test]
+    fn test_parse_ports() {
+        let ports = parse_ports("80, 8080:80, 127.0.0.1:5432:5432/tcp, 53:53/udp").unwrap();
+        assert_eq!(
+            ports["80/tcp"],
+            Some(vec![binding(None, None), binding(None, Some("8080"))])
+        );
+        assert_eq!(
+            ports["5432/tcp"],
+            Some(vec![binding(Some("127.0.0.1"), Some("5432"))])
+        );
+        assert_eq!(ports["53/udp"], Some(vec![binding(None, Some("53"))]));
+    }
+
+    #[test]
+    fn test_parse_ports_ipv6() {
+        let ports = parse_ports("[::1]:8080:80, [fe80::1]::443").unwrap();
+        assert_eq!(
+            ports["80/tcp"],
+            Some(vec![binding(Some("::1"), Some("8080"))])
+        );
+        assert_eq!(ports["443/tcp"], Some(vec![binding(Some("fe80::1"), None)]));
+    }
+
+    #[test]
+    fn test_parse_ports_invalid() {
+        for ports in [
+            "http",
+            "80/icmp",
+            "70000",
+            "1:2:3:4",
+            "::1:8080:80",
+            "[::1]8080:80",
+            "[::1]:80",
+            "localhost:8080:80",
+        ] {
+            assert!(parse_ports(ports).is_err(), "{ports} should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_parse_volumes() {
+        assert_eq!(
+            parse_volumes("data:/data, /host:/container:ro").unwrap(),
+            vec!["data:/data", "/host:/container:ro"]
+        );
+        for volumes in ["data", ":/data", "data:", "a:b:c:d"] {
+            assert!(
+                parse_volumes(volumes).is_err(),
+                "{volumes} should be invalid"
+            );
+        }
+    }
+}
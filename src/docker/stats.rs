@@ -0,0 +1,349 @@
+use bollard::query_parameters::StatsOptionsBuilder;
+use bollard::secret::{ContainerCpuStats, ContainerStatsResponse};
+use color_eyre::eyre::{Context, ContextCompat, Result};
+use futures::{Stream, StreamExt, future::join_all};
+
+use super::container::DockerContainer;
+
+/// A single sample of resource usage for a container, computed from the raw
+/// stats response returned by the docker daemon.
+///
+/// Network and block I/O values are cumulative byte counts since the container
+/// started; rates can be derived by comparing consecutive samples.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DockerStats {
+    pub cpu_percent: f64,
+    pub memory_usage: u64,
+    pub memory_limit: u64,
+    pub memory_percent: f64,
+    pub net_rx: u64,
+    pub net_tx: u64,
+    pub block_read: u64,
+    pub block_write: u64,
+}
+
+impl DockerStats {
+    /// Builds a DockerStats struct from a bollard::...::ContainerStatsResponse instance.
+    ///
+    /// CPU and memory values are calculated in the same way as the docker cli.
+    pub fn from(s: ContainerStatsResponse) -> Self {
+        let cpu_percent = match (&s.cpu_stats, &s.precpu_stats) {
+            (Some(cpu), Some(precpu)) => cpu_percent(cpu, precpu),
+            _ => 0.0,
+        };
+
+        let (memory_usage, memory_limit) = match &s.memory_stats {
+            Some(m) => {
+                let usage = m.usage.unwrap_or_default();
+                // cgroups v1 reports total_inactive_file, v2 reports inactive_file;
+                // both count as cache, which the docker cli excludes from usage
+                let cache = m
+                    .stats
+                    .as_ref()
+                    .and_then(|s| s.get("total_inactive_file").or(s.get("inactive_file")))
+                    .copied()
+                    .unwrap_or_default();
+                (usage.saturating_sub(cache), m.limit.unwrap_or_default())
+            }
+            None => (0, 0),
+        };
+
+        let memory_percent = if memory_limit > 0 {
+            memory_usage as f64 / memory_limit as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        let (net_rx, net_tx) =
+            s.networks
+                .unwrap_or_default()
+                .values()
+                .fold((0, 0), |(rx, tx), n| {
+                    (
+                        rx + n.rx_bytes.unwrap_or_default(),
+                        tx + n.tx_bytes.unwrap_or_default(),
+                    )
+                });
+
+        let (block_read, block_write) = s
+            .blkio_stats
+            .and_then(|b| b.io_service_bytes_recursive)
+            .unwrap_or_default()
+            .into_iter()
+            .fold((0, 0), |(read, write), entry| {
+                let value = entry.value.unwrap_or_default();
+                match entry.op.unwrap_or_default().to_lowercase().as_str() {
+                    "read" => (read + value, write),
+                    "write" => (read, write + value),
+                    _ => (read, write),
+                }
+            });
+
+        Self {
+            cpu_percent,
+            memory_usage,
+            memory_limit,
+            memory_percent,
+            net_rx,
+            net_tx,
+            block_read,
+            block_write,
+        }
+    }
+
+    /// Streams stats samples for the given container; the docker daemon emits
+    /// roughly one sample per second until the stream is dropped.
+    pub fn get_stats_stream(
+        docker: &bollard::Docker,
+        container: &DockerContainer,
+    ) -> impl Stream<Item = Result<Self>> + 'static {
+        let opts = StatsOptionsBuilder::default().stream(true).build();
+        let stats_stream = docker.stats(&container.id, Some(opts)).map(|res| {
+            res.map(Self::from)
+                .context("unable to retrieve container stats")
+        });
+
+        Box::pin(stats_stream)
+    }
+
+    /// Retrieves a single stats sample for the given container.
+    ///
+    /// The daemon waits to collect a second sample before responding so that CPU
+    /// usage can be calculated; expect this to take around a second.
+    pub async fn get(docker: &bollard::Docker, container: &DockerContainer) -> Result<Self> {
+        let opts = StatsOptionsBuilder::default().stream(false).build();
+        let stats = docker
+            .stats(&container.id, Some(opts))
+            .next()
+            .await
+            .context("no stats returned for container")?
+            .context("unable to retrieve container stats")?;
+        Ok(Self::from(stats))
+    }
+}
+
+/// Pairs a container with a stats sample, used to present resource usage across
+/// all running containers
+#[derive(Debug, Clone, PartialEq)]
+pub struct DockerContainerStats {
+    pub container: DockerContainer,
+    pub stats: DockerStats,
+}
+
+impl DockerContainerStats {
+    /// Retrieves a stats sample for every running container on the docker daemon.
+    /// Requests are made concurrently; containers which stop before their stats
+    /// can be retrieved are omitted.
+    pub async fn list(docker: &bollard::Docker) -> Result<Vec<Self>> {
+        let containers = DockerContainer::list(docker)
+            .await?
+            .into_iter()
+            .filter(|c| c.running);
+
+        let handlers = containers.map(|container| async move {
+            DockerStats::get(docker, &container)
+                .await
+                .ok()
+                .map(|stats| Self { container, stats })
+        });
+
+        Ok(join_all(handlers).await.into_iter().flatten().collect())
+    }
+}
+
+/// Calculates CPU usage as a percentage of a single core, so a container
+/// saturating two cores reports 200%
+fn cpu_percent(cpu: &ContainerCpuStats, precpu: &ContainerCpuStats) -> f64 {
+    let total = |c: &ContainerCpuStats| {
+        c.cpu_usage
+            .as_ref()
+            .and_then(|u| u.total_usage)
+            .unwrap_or_default()
+    };
+
+    // The first sample of a stream has no previous sample to compare against,
+    // and comparing against zero would give the average since the host booted
+    if precpu.system_cpu_usage.unwrap_or_default() == 0 {
+        return 0.0;
+    }
+
+    let cpu_delta = total(cpu).saturating_sub(total(precpu)) as f64;
+    let system_delta = cpu
+        .system_cpu_usage
+        .unwrap_or_default()
+        .saturating_sub(precpu.system_cpu_usage.unwrap_or_default()) as f64;
+
+    let online_cpus = match cpu.online_cpus {
+        Some(n) if n > 0 => n as f64,
+        _ => cpu
+            .cpu_usage
+            .as_ref()
+            .and_then(|u| u.percpu_usage.as_ref())
+            .map(|p| p.len())
+            .unwrap_or(1) as f64,
+    };
+
+    if system_delta > 0.0 && cpu_delta > 0.0 {
+        cpu_delta / system_delta * online_cpus * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bollard::secret::{
+        ContainerBlkioStatEntry, ContainerBlkioStats, ContainerCpuUsage, ContainerMemoryStats,
+        ContainerNetworkStats,
+    };
+
+    use super::*;
+
+    fn cpu_stats(total_usage: u64, system_cpu_usage: Option<u64>) -> ContainerCpuStats {
+        ContainerCpuStats {
+            cpu_usage: Some(ContainerCpuUsage {
+                total_usage: Some(total_usage),
+                ..Default::default()
+            }),
+            system_cpu_usage,
+            online_cpus: Some(4),
+            ..Default::default()
+        }
+    }
+
+    fn memory_stats(usage: u64, stats: &[(&str, u64)]) -> ContainerMemoryStats {
+        ContainerMemoryStats {
+            usage: Some(usage),
+            limit: Some(1000),
+            stats: Some(
+                stats
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), *v))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn blkio_entry(op: &str, value: u64) -> ContainerBlkioStatEntry {
+        ContainerBlkioStatEntry {
+            op: Some(op.into()),
+            value: Some(value),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cpu_percent() {
+        let stats = DockerStats::from(ContainerStatsResponse {
+            cpu_stats: Some(cpu_stats(300, Some(2000))),
+            precpu_stats: Some(cpu_stats(100, Some(1000))),
+            ..Default::default()
+        });
+        // 200 of 1000 across 4 cpus
+        assert_eq!(stats.cpu_percent, 80.0);
+    }
+
+    #[test]
+    fn test_cpu_percent_first_sample() {
+        let stats = DockerStats::from(ContainerStatsResponse {
+            cpu_stats: Some(cpu_stats(300, Some(2000))),
+            precpu_stats: Some(ContainerCpuStats::default()),
+            ..Default::default()
+        });
+        assert_eq!(stats.cpu_percent, 0.0);
+
+        let stats = DockerStats::from(ContainerStatsResponse {
+            cpu_stats: Some(cpu_stats(300, Some(2000))),
+            ..Default::default()
+        });
+        assert_eq!(stats.cpu_percent, 0.0);
+    }
+
+    #[test]
+    fn test_cpu_percent_counts_percpu_usage() {
+        let mut cpu = cpu_stats(300, Some(2000));
+        cpu.online_cpus = None;
+        cpu.cpu_usage.as_mut().unwrap().percpu_usage = Some(vec![150, 150]);
+        let stats = DockerStats::from(ContainerStatsResponse {
+            cpu_stats: Some(cpu),
+            precpu_stats: Some(cpu_stats(100, Some(1000))),
+            ..Default::default()
+        });
+        assert_eq!(stats.cpu_percent, 40.0);
+    }
+
+    #[test]
+    fn test_memory_cgroup_v1() {
+        let stats = DockerStats::from(ContainerStatsResponse {
+            memory_stats: Some(memory_stats(
+                500,
+                &[("total_inactive_file", 100), ("inactive_file", 50)],
+            )),
+            ..Default::default()
+        });
+        assert_eq!(stats.memory_usage, 400);
+        assert_eq!(stats.memory_limit, 1000);
+        assert_eq!(stats.memory_percent, 40.0);
+    }
+
+    #[test]
+    fn test_memory_cgroup_v2() {
+        let stats = DockerStats::from(ContainerStatsResponse {
+            memory_stats: Some(memory_stats(500, &[("inactive_file", 250)])),
+            ..Default::default()
+        });
+        assert_eq!(stats.memory_usage, 250);
+        assert_eq!(stats.memory_percent, 25.0);
+    }
+
+    #[test]
+    fn test_memory_without_limit() {
+        let stats = DockerStats::from(ContainerStatsResponse {
+            memory_stats: Some(ContainerMemoryStats {
+                usage: Some(500),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert_eq!(stats.memory_usage, 500);
+        assert_eq!(stats.memory_percent, 0.0);
+    }
+
+    #[test]
+    fn test_io_totals() {
+        let network = |rx, tx| ContainerNetworkStats {
+            rx_bytes: Some(rx),
+            tx_bytes: Some(tx),
+            ..Default::default()
+        };
+        let stats = DockerStats::from(ContainerStatsResponse {
+            networks: Some(HashMap::from([
+                ("eth0".to_string(), network(100, 10)),
+                ("eth1".to_string(), network(200, 20)),
+            ])),
+            blkio_stats: Some(ContainerBlkioStats {
+                io_service_bytes_recursive: Some(vec![
+                    blkio_entry("Read", 1000),
+                    blkio_entry("Write", 300),
+                    blkio_entry("read", 24),
+                    blkio_entry("Total", 1324),
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert_eq!((stats.net_rx, stats.net_tx), (300, 30));
+        assert_eq!((stats.block_read, stats.block_write), (1024, 300));
+    }
+
+    #[test]
+    fn test_empty_response() {
+        assert_eq!(
+            DockerStats::from(ContainerStatsResponse::default()),
+            DockerStats::default()
+        );
+    }
+}
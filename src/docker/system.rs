@@ -0,0 +1,393 @@
+use std::fmt::Display;
+
+use bollard::query_parameters::DataUsageOptionsBuilder;
+use bollard::secret::{BuildCache, ContainerSummary, ImageSummary, Volume};
+use color_eyre::eyre::{Context, Result};
+use serde::de::DeserializeOwned;
+
+use super::{
+    container::DockerContainer,
+    image::DockerImage,
+    util::{format_bytes, short_id},
+    volume::DockerVolume,
+};
+
+/// The kinds of object the daemon reports disk usage for, in the order shown
+/// by `docker system df`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageCategory {
+    Images,
+    Containers,
+    Volumes,
+    BuildCache,
+}
+
+impl Display for UsageCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Images => "Images",
+            Self::Containers => "Containers",
+            Self::Volumes => "Local Volumes",
+            Self::BuildCache => "Build Cache",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The resource an item of disk usage belongs to, used to show the resource on
+/// its own page
+#[derive(Debug, Clone, PartialEq)]
+pub enum UsageResource {
+    Image(DockerImage),
+    Container(DockerContainer),
+    Volume(DockerVolume),
+}
+
+/// A single image, container, volume or build cache record
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageItem {
+    pub name: String,
+    pub size: i64,
+    /// Whether the item is in use, and so can't be reclaimed by pruning
+    pub active: bool,
+    pub detail: String,
+    /// Build cache records have no page of their own, so have no resource
+    pub resource: Option<UsageResource>,
+}
+
+/// The disk usage of one category, with its items ordered largest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryUsage {
+    pub category: UsageCategory,
+    pub total_count: i64,
+    pub active_count: i64,
+    pub size: i64,
+    pub reclaimable: i64,
+    pub items: Vec<UsageItem>,
+}
+
+impl CategoryUsage {
+    /// The proportion of the category's size which could be reclaimed
+    pub fn reclaimable_percent(&self) -> f64 {
+        if self.size <= 0 {
+            return 0.0;
+        }
+        self.reclaimable as f64 / self.size as f64 * 100.0
+    }
+}
+
+/// Disk space used by the daemon, as reported by `docker system df -v`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskUsage {
+    pub categories: Vec<CategoryUsage>,
+}
+
+impl DiskUsage {
+    pub async fn get(docker: &bollard::Docker) -> Result<Self> {
+        let opts = DataUsageOptionsBuilder::default().verbose(true).build();
+        let df = docker
+            .df(Some(opts))
+            .await
+            .context("unable to retrieve disk usage")?;
+
+        let mut categories = vec![];
+        if let Some(images) = df.images_disk_usage {
+            categories.push(category(
+                UsageCategory::Images,
+                (images.total_count, images.active_count),
+                (images.total_size, images.reclaimable),
+                parse_items(images.items)?
+                    .into_iter()
+                    .map(image_item)
+                    .collect(),
+            ));
+        }
+        if let Some(containers) = df.containers_disk_usage {
+            categories.push(category(
+                UsageCategory::Containers,
+                (containers.total_count, containers.active_count),
+                (containers.total_size, containers.reclaimable),
+                parse_items(containers.items)?
+                    .into_iter()
+                    .map(container_item)
+                    .collect(),
+            ));
+        }
+        if let Some(volumes) = df.volumes_disk_usage {
+            categories.push(category(
+                UsageCategory::Volumes,
+                (volumes.total_count, volumes.active_count),
+                (volumes.total_size, volumes.reclaimable),
+                parse_items(volumes.items)?
+                    .into_iter()
+                    .map(volume_item)
+                    .collect(),
+            ));
+        }
+        if let Some(build_cache) = df.build_cache_disk_usage {
+            categories.push(category(
+                UsageCategory::BuildCache,
+                (build_cache.total_count, build_cache.active_count),
+                (build_cache.total_size, build_cache.reclaimable),
+                parse_items(build_cache.items)?
+                    .into_iter()
+                    .map(build_cache_item)
+                    .collect(),
+            ));
+        }
+
+        Ok(Self { categories })
+    }
+
+    /// The total size of all categories
+    pub fn size(&self) -> i64 {
+        self.categories.iter().map(|c| c.size).sum()
+    }
+
+    /// The total space which could be reclaimed across all categories
+    pub fn reclaimable(&self) -> i64 {
+        self.categories.iter().map(|c| c.reclaimable).sum()
+    }
+}
+
+fn category(
+    category: UsageCategory,
+    (total_count, active_count): (Option<i64>, Option<i64>),
+    (size, reclaimable): (Option<i64>, Option<i64>),
+    mut items: Vec<UsageItem>,
+) -> CategoryUsage {
+    items.sort_by(|a, b| b.size.cmp(&a.size).then(a.name.cmp(&b.name)));
+    CategoryUsage {
+        category,
+        total_count: total_count.unwrap_or_default(),
+        active_count: active_count.unwrap_or_default(),
+        size: size.unwrap_or_default(),
+        reclaimable: reclaimable.unwrap_or_default(),
+        items,
+    }
+}
+
+/// The daemon returns the items of each category as untyped JSON, so these are
+/// parsed into the matching model
+pub fn parse_items<T: DeserializeOwned>(items: Option<Vec<serde_json::Value>>) -> Result<Vec<T>> {
+    items
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| serde_json::from_value(item).context("unable to parse disk usage"))
+        .collect()
+}
+
+fn image_item(image: ImageSummary) -> UsageItem {
+    let name = match image.repo_tags.first() {
+        Some(tag) => tag.clone(),
+        None => short_id(&image.id),
+    };
+    let detail = format!(
+        "{} containers, {} shared",
+        image.containers.max(0),
+        format_bytes(image.shared_size).unwrap_or("-".into())
+    );
+    UsageItem {
+        name,
+        size: image.size,
+        active: image.containers > 0,
+        detail,
+        resource: DockerImage::from(image)
+            .into_iter()
+            .next()
+            .map(UsageResource::Image),
+    }
+}
+
+fn container_item(container: ContainerSummary) -> UsageItem {
+    let size = container.size_rw.unwrap_or_default();
+    let container = DockerContainer::from(container);
+    UsageItem {
+        name: container.names.clone(),
+        size,
+        active: container.running || container.paused,
+        detail: format!("{} ({})", container.image, container.status),
+        resource: Some(UsageResource::Container(container)),
+    }
+}
+
+fn volume_item(volume: Volume) -> UsageItem {
+    let (size, ref_count) = match &volume.usage_data {
+        Some(usage) => (usage.size, usage.ref_count),
+        None => (-1, -1),
+    };
+    UsageItem {
+        name: volume.name.clone(),
+        size,
+        active: ref_count > 0,
+        detail: format!("{} containers", ref_count.max(0)),
+        resource: Some(UsageResource::Volume(DockerVolume::from(volume))),
+    }
+}
+
+fn build_cache_item(record: BuildCache) -> UsageItem {
+    let mut detail = record.typ.map(|t| t.to_string()).unwrap_or_default();
+    if record.shared.unwrap_or_default() {
+        detail.push_str(", shared");
+    }
+    UsageItem {
+        name: record
+            .description
+            .filter(|d| !d.is_empty())
+            .unwrap_or(short_id(&record.id.unwrap_or_default())),
+        size: record.size.unwrap_or_default(),
+        active: record.in_use.unwrap_or_default(),
+        detail,
+        resource: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bollard::secret::{BuildCacheTypeEnum, VolumeUsageData};
+    use serde_json::json;
+
+    use super::*;
+
+    fn item(name: &str, size: i64) -> UsageItem {
+        UsageItem {
+            name: name.into(),
+            size,
+            active: false,
+            detail: String::new(),
+            resource: None,
+        }
+    }
+
+    fn usage(size: i64, reclaimable: i64) -> CategoryUsage {
+        category(
+            UsageCategory::Images,
+            (Some(2), Some(1)),
+            (Some(size), Some(reclaimable)),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_reclaimable_percent() {
+        assert_eq!(usage(200, 50).reclaimable_percent(), 25.0);
+        assert_eq!(usage(200, 200).reclaimable_percent(), 100.0);
+        assert_eq!(usage(0, 0).reclaimable_percent(), 0.0);
+        assert_eq!(usage(-1, 10).reclaimable_percent(), 0.0);
+    }
+
+    #[test]
+    fn test_totals() {
+        let usage = DiskUsage {
+            categories: vec![usage(200, 50), usage(100, 0)],
+        };
+        assert_eq!(usage.size(), 300);
+        assert_eq!(usage.reclaimable(), 50);
+    }
+
+    #[test]
+    fn test_category_sorts_items() {
+        let usage = category(
+            UsageCategory::Volumes,
+            (None, None),
+            (None, None),
+            vec![item("b", 1), item("c", 10), item("a", 1)],
+        );
+        let names: Vec<_> = usage.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "b"]);
+        assert_eq!(usage.total_count, 0);
+        assert_eq!(usage.size, 0);
+    }
+
+    #[test]
+    fn test_parse_items() {
+        let items = Some(vec![json!({
+            "Name": "data",
+            "Driver": "local",
+            "Mountpoint": "/var/lib/docker/volumes/data/_data",
+            "Labels": null,
+            "Scope": "local",
+            "Options": null,
+            "UsageData": {"Size": 2048, "RefCount": 1}
+        })]);
+        let volumes = parse_items::<Volume>(items).unwrap();
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].name, "data");
+        assert_eq!(volumes[0].usage_data.as_ref().unwrap().size, 2048);
+
+        assert!(parse_items::<Volume>(None).unwrap().is_empty());
+        assert!(parse_items::<Volume>(Some(vec![json!({"Name": 1})])).is_err());
+    }
+
+    #[test]
+    fn test_image_item() {
+        let image = |repo_tags: Vec<String>, containers| ImageSummary {
+            id: "sha256:0123456789abcdef".into(),
+            repo_tags,
+            size: 1024,
+            shared_size: -1,
+            containers,
+            ..Default::default()
+        };
+
+        let tagged = image_item(image(vec!["alpine:3".into()], 2));
+        assert_eq!(tagged.name, "alpine:3");
+        assert!(tagged.active);
+        assert_eq!(tagged.detail, "2 containers, - shared");
+        assert!(matches!(tagged.resource, Some(UsageResource::Image(_))));
+
+        let untagged = image_item(image(vec![], -1));
+        assert_eq!(untagged.name, "0123456789ab");
+        assert!(!untagged.active);
+        assert_eq!(untagged.detail, "0 containers, - shared");
+    }
+
+    #[test]
+    fn test_volume_item() {
+        let volume = |usage_data| Volume {
+            name: "data".into(),
+            usage_data,
+            ..Default::default()
+        };
+
+        let used = volume_item(volume(Some(VolumeUsageData {
+            size: 2048,
+            ref_count: 1,
+        })));
+        assert_eq!(used.size, 2048);
+        assert!(used.active);
+        assert_eq!(used.detail, "1 containers");
+
+        // Usage data is only reported for local volumes
+        let unknown = volume_item(volume(None));
+        assert_eq!(unknown.size, -1);
+        assert!(!unknown.active);
+        assert_eq!(unknown.detail, "0 containers");
+    }
+
+    #[test]
+    fn test_build_cache_item() {
+        let record = build_cache_item(BuildCache {
+            id: Some("sha256:fedcba9876543210".into()),
+            typ: Some(BuildCacheTypeEnum::REGULAR),
+            description: Some("mount / from exec /bin/sh -c make".into()),
+            shared: Some(true),
+            in_use: Some(true),
+            size: Some(10),
+            ..Default::default()
+        });
+        assert_eq!(record.name, "mount / from exec /bin/sh -c make");
+        assert_eq!(record.detail, "regular, shared");
+        assert!(record.active);
+        assert!(record.resource.is_none());
+
+        let record = build_cache_item(BuildCache {
+            id: Some("sha256:fedcba9876543210".into()),
+            description: Some(String::new()),
+            ..Default::default()
+        });
+        assert_eq!(record.name, "fedcba987654");
+        assert_eq!(record.detail, "");
+        assert_eq!(record.size, 0);
+    }
+}
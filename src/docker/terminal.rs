@@ -0,0 +1,378 @@
+use std::fmt::Debug;
+use std::pin::Pin;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use bollard::container::LogOutput;
+use color_eyre::eyre::{Report, Result};
+use futures::{Stream, StreamExt, future::BoxFuture};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
+
+use crate::events::{Key, Message, Transition};
+
+const DEFAULT_ROWS: u16 = 24;
+const DEFAULT_COLS: u16 = 80;
+
+pub type TtyOutput = Pin<Box<dyn Stream<Item = Result<LogOutput, bollard::errors::Error>> + Send>>;
+pub type TtyInput = Pin<Box<dyn AsyncWrite + Send>>;
+
+/// Resizes the remote TTY to the given number of columns and rows
+pub type TtyResize = Box<dyn Fn(u16, u16) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// A process attached to a TTY on the docker daemon, with its output parsed into
+/// a grid of cells which can be drawn inside the TUI.
+///
+/// Output is read and input written on a background task; a tick is sent each
+/// time new output arrives so that the screen is redrawn. The session keeps
+/// running when nothing is drawing it, so it can be left and later resumed.
+pub struct TerminalSession {
+    parser: Arc<Mutex<vt100::Parser>>,
+    input_tx: mpsc::UnboundedSender<Vec<u8>>,
+    size_tx: watch::Sender<(u16, u16)>,
+    /// The line being typed where the process has no TTY to echo and edit
+    /// input itself; `None` when keys are sent straight to a TTY
+    line: Option<Mutex<String>>,
+    finished: Arc<AtomicBool>,
+    /// Why the connection to the process was lost, where it didn't simply exit
+    error: Arc<Mutex<Option<Report>>>,
+    handle: JoinHandle<()>,
+}
+
+impl Debug for TerminalSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalSession")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl TerminalSession {
+    /// Creates a session for a process attached to a TTY, which echoes input
+    /// and is resized along with the screen
+    pub fn new(
+        output: TtyOutput,
+        input: TtyInput,
+        resize: TtyResize,
+        tx: mpsc::Sender<Message<Key, Transition>>,
+    ) -> Self {
+        Self::spawn(output, input, Some(resize), false, tx)
+    }
+
+    /// Creates a session for a process without a TTY.  Input is echoed locally
+    /// and sent a line at a time, as a terminal's line discipline would do.
+    pub fn without_tty(
+        output: TtyOutput,
+        input: TtyInput,
+        tx: mpsc::Sender<Message<Key, Transition>>,
+    ) -> Self {
+        Self::spawn(output, input, None, true, tx)
+    }
+
+    fn spawn(
+        mut output: TtyOutput,
+        mut input: TtyInput,
+        resize: Option<TtyResize>,
+        line_buffered: bool,
+        tx: mpsc::Sender<Message<Key, Transition>>,
+    ) -> Self {
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(
+            DEFAULT_ROWS,
+            DEFAULT_COLS,
+            0,
+        )));
+        // Input is unbounded so that keys typed faster than they can be written,
+        // eg when pasting, are queued rather than dropped
+        let (input_tx, mut input_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (size_tx, mut size_rx) = watch::channel((DEFAULT_COLS, DEFAULT_ROWS));
+        let finished = Arc::new(AtomicBool::new(false));
+        let error = Arc::new(Mutex::new(None));
+
+        let task_parser = parser.clone();
+        let task_finished = finished.clone();
+        let task_error = error.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    chunk = output.next() => match chunk {
+                        Some(Ok(chunk)) => {
+                            task_parser.lock().unwrap().process(chunk.as_ref());
+                            let _ = tx.send(Message::Tick).await;
+                        }
+                        Some(Err(err)) => {
+                            *task_error.lock().unwrap() =
+                                Some(Report::new(err).wrap_err("lost connection to the process"));
+                            break;
+                        }
+                        None => break,
+                    },
+                    Some(bytes) = input_rx.recv() => {
+                        let written = match input.write_all(&bytes).await {
+                            Ok(()) => input.flush().await,
+                            Err(err) => Err(err),
+                        };
+                        if let Err(err) = written {
+                            *task_error.lock().unwrap() =
+                                Some(Report::new(err).wrap_err("could not send input to the process"));
+                            break;
+                        }
+                    }
+                    Ok(()) = size_rx.changed() => {
+                        let (cols, rows) = *size_rx.borrow_and_update();
+                        // The process may have already exited, so failures are ignored
+                        if let Some(resize) = &resize {
+                            let _ = resize(cols, rows).await;
+                        }
+                    }
+                }
+            }
+            task_finished.store(true, Ordering::SeqCst);
+            let _ = tx.send(Message::Tick).await;
+        });
+
+        Self {
+            parser,
+            input_tx,
+            size_tx,
+            line: line_buffered.then(Mutex::default),
+            finished,
+            error,
+            handle,
+        }
+    }
+
+    /// Whether the process has exited or the connection to it has been lost
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Returns the error which ended the session, if it ended with one
+    pub fn take_error(&self) -> Option<Report> {
+        self.error.lock().unwrap().take()
+    }
+
+    /// Sends the bytes a terminal would send for the given key to the process
+    pub fn send_key(&self, key: Key) {
+        match &self.line {
+            Some(line) => self.edit_line(&mut line.lock().unwrap(), key),
+            None => self.send_bytes(key.to_terminal_bytes()),
+        }
+    }
+
+    fn send_bytes(&self, bytes: Vec<u8>) {
+        if !bytes.is_empty() {
+            let _ = self.input_tx.send(bytes);
+        }
+    }
+
+    /// Edits the line being typed, echoing it to the screen, and sends it with a
+    /// line feed on Enter.  Control keys are sent straight away so that the
+    /// daemon still sees the detach keys.
+    fn edit_line(&self, line: &mut String, key: Key) {
+        let echo = match key {
+            Key::Enter => {
+                let mut bytes = std::mem::take(line).into_bytes();
+                bytes.push(b'\n');
+                self.send_bytes(bytes);
+                "\r\n".to_string()
+            }
+            Key::Backspace if line.pop().is_some() => "\x08 \x08".to_string(),
+            Key::Char(c) => {
+                line.push(c);
+                c.to_string()
+            }
+            Key::Tab => {
+                line.push('\t');
+                "\t".to_string()
+            }
+            Key::Ctrl(_) => {
+                self.send_bytes(key.to_terminal_bytes());
+                return;
+            }
+            _ => return,
+        };
+        self.parser.lock().unwrap().process(echo.as_bytes());
+    }
+
+    /// Resizes the local screen and the remote TTY, if the size has changed
+    pub fn resize(&self, cols: u16, rows: u16) {
+        {
+            let mut parser = self.parser.lock().unwrap();
+            if parser.screen().size() == (rows, cols) {
+                return;
+            }
+            parser.screen_mut().set_size(rows, cols);
+        }
+        let _ = self.size_tx.send((cols, rows));
+    }
+
+    /// Runs the given function against the current state of the screen, eg to draw it
+    pub fn with_screen<T>(&self, f: impl FnOnce(&vt100::Screen) -> T) -> T {
+        f(self.parser.lock().unwrap().screen())
+    }
+
+    /// Stops reading from and writing to the process
+    pub fn close(&self) {
+        self.handle.abort();
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use bytes::Bytes;
+    use futures::stream;
+    use tokio::io::{AsyncReadExt, DuplexStream, duplex};
+
+    use super::*;
+
+    fn output(chunks: Vec<Result<&'static str, bollard::errors::Error>>) -> TtyOutput {
+        Box::pin(stream::iter(chunks.into_iter().map(|c| {
+            c.map(|message| LogOutput::Console {
+                message: Bytes::from_static(message.as_bytes()),
+            })
+        })))
+    }
+
+    fn input() -> (TtyInput, DuplexStream) {
+        let (input, remote) = duplex(1024);
+        (Box::pin(input), remote)
+    }
+
+    async fn wait_until_finished(
+        session: &TerminalSession,
+        rx: &mut mpsc::Receiver<Message<Key, Transition>>,
+    ) {
+        while !session.is_finished() {
+            rx.recv().await.unwrap();
+        }
+    }
+
+    fn contents(session: &TerminalSession) -> String {
+        session.with_screen(|screen| screen.contents())
+    }
+
+    #[tokio::test]
+    async fn test_output_is_drawn_to_screen() {
+        let (tx, mut rx) = mpsc::channel(32);
+        let (input, _remote) = input();
+        let session = TerminalSession::without_tty(
+            output(vec![Ok("hello\r\n"), Ok("\x1b[1mworld")]),
+            input,
+            tx,
+        );
+
+        wait_until_finished(&session, &mut rx).await;
+        assert_eq!(contents(&session), "hello\nworld");
+        assert!(session.take_error().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_is_kept() {
+        let (tx, mut rx) = mpsc::channel(32);
+        let (input, _remote) = input();
+        let err = bollard::errors::Error::IOError {
+            err: std::io::Error::other("connection reset"),
+        };
+        let session = TerminalSession::without_tty(output(vec![Ok("a"), Err(err)]), input, tx);
+
+        wait_until_finished(&session, &mut rx).await;
+        let err = session.take_error().unwrap();
+        assert!(format!("{err:#}").contains("connection reset"));
+        assert!(session.take_error().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tty_keys_are_sent_as_typed() {
+        let (tx, _rx) = mpsc::channel(32);
+        let (input, mut remote) = input();
+        let resize: TtyResize = Box::new(|_, _| Box::pin(async { Ok(()) }));
+        let session = TerminalSession::new(Box::pin(stream::pending()), input, resize, tx);
+
+        for key in [Key::Char('l'), Key::Char('s'), Key::Enter, Key::Ctrl('c')] {
+            session.send_key(key);
+        }
+
+        let mut buf = [0; 4];
+        remote.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ls\r\x03");
+        // Nothing is echoed locally, as that is the TTY's job
+        assert_eq!(contents(&session), "");
+    }
+
+    #[tokio::test]
+    async fn test_input_without_tty_is_line_buffered() {
+        let (tx, _rx) = mpsc::channel(32);
+        let (input, mut remote) = input();
+        let session = TerminalSession::without_tty(Box::pin(stream::pending()), input, tx);
+
+        for key in [
+            Key::Char('l'),
+            Key::Char('x'),
+            Key::Backspace,
+            Key::Char('s'),
+            Key::Enter,
+        ] {
+            session.send_key(key);
+        }
+
+        let mut buf = [0; 3];
+        remote.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ls\n");
+        assert_eq!(contents(&session), "ls");
+    }
+
+    #[tokio::test]
+    async fn test_control_keys_without_tty_are_sent_straight_away() {
+        let (tx, _rx) = mpsc::channel(32);
+        let (input, mut remote) = input();
+        let session = TerminalSession::without_tty(Box::pin(stream::pending()), input, tx);
+
+        // The daemon must see the detach keys even though the line isn't finished
+        for key in [Key::Char('a'), Key::Ctrl('p'), Key::Ctrl('q')] {
+            session.send_key(key);
+        }
+
+        let mut buf = [0; 2];
+        remote.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"\x10\x11");
+        assert_eq!(contents(&session), "a");
+    }
+
+    #[tokio::test]
+    async fn test_resize() {
+        let (tx, _rx) = mpsc::channel(32);
+        let (input, _remote) = input();
+        let resizes = Arc::new(AtomicU32::new(0));
+        let (size_tx, mut size_rx) = mpsc::unbounded_channel();
+        let counter = resizes.clone();
+        let resize: TtyResize = Box::new(move |cols, rows| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let _ = size_tx.send((cols, rows));
+            Box::pin(async { Ok(()) })
+        });
+        let session = TerminalSession::new(Box::pin(stream::pending()), input, resize, tx);
+
+        session.resize(100, 30);
+        assert_eq!(session.with_screen(|screen| screen.size()), (30, 100));
+        assert_eq!(size_rx.recv().await, Some((100, 30)));
+
+        // An unchanged size isn't sent on to the daemon again
+        session.resize(100, 30);
+        session.resize(120, 40);
+        assert_eq!(size_rx.recv().await, Some((120, 40)));
+        assert_eq!(resizes.load(Ordering::SeqCst), 2);
+    }
+}
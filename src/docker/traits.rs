@@ -1,5 +1,7 @@
 use std::fmt;
 
+use bollard::Docker;
+
 use color_eyre::eyre::Result;
 use dyn_clone::DynClone;
 use uuid::Uuid;
@@ -8,13 +10,17 @@ use uuid::Uuid;
 /// struct in a human readable format.
 /// Provides a generic minimal description interface over a selection of
 /// docker resources
+#[async_trait::async_trait]
 pub trait Describe: fmt::Debug + Send + Sync + DynClone {
     /// Get the ID of the resource being described
     fn get_id(&self) -> String;
     /// Get a human readable name of the resource being described
     fn get_name(&self) -> String;
-    /// Get a human readable description of the resource being described
-    fn describe(&self) -> Result<Vec<DescribeSection>>;
+    /// Get a human readable description of the resource being described, using
+    /// the full details reported by the relevant inspect endpoint
+    async fn describe(&self, docker: &Docker) -> Result<Vec<DescribeSection>>;
+    /// Get the complete document returned by the relevant inspect endpoint
+    async fn inspect(&self, docker: &Docker) -> Result<serde_json::Value>;
 }
 
 dyn_clone::clone_trait_object!(Describe);
@@ -24,6 +30,7 @@ pub struct DescribeSection {
     pub(crate) id: Uuid,
     pub(crate) name: String,
     pub(crate) items: Vec<DescribeItem>,
+    pub(crate) sections: Vec<DescribeSection>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -49,7 +56,27 @@ impl DescribeSection {
             id: Uuid::new_v4(),
             name: name.to_string(),
             items: vec![],
+            sections: vec![],
+        }
+    }
+
+    /// Builds a section with an item for each entry of the map, sorted by key
+    pub fn from_map<'a, N: ToString>(
+        name: N,
+        map: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Self {
+        let mut section = Self::new(name);
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort();
+        for (key, value) in entries {
+            section.item(key, value);
         }
+        section
+    }
+
+    /// Whether the section has neither items nor nested sections
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.sections.is_empty()
     }
 
     pub(crate) fn item<N: ToString, V: ToString>(&mut self, name: N, value: V) -> &mut Self {
@@ -68,4 +95,10 @@ impl DescribeSection {
         ));
         self
     }
+
+    /// Nests a section within this one
+    pub(crate) fn section(&mut self, section: DescribeSection) -> &mut Self {
+        self.sections.push(section);
+        self
+    }
 }
@@ -0,0 +1,311 @@
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use color_eyre::eyre::{Context, Result, bail};
+use futures::{Stream, StreamExt, TryStreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::Sender;
+use tokio_util::io::ReaderStream;
+
+use super::files::expand_local_path;
+use crate::events::{Key, Message, Transition};
+
+/// How long a successful transfer remains listed once it has finished
+const FINISHED_DISPLAY_TIME: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferState {
+    Running,
+    Done(String),
+    Failed(String),
+}
+
+/// A long running copy of data between the local machine and the docker
+/// daemon, eg exporting a container or loading an image
+#[derive(Debug)]
+pub struct Transfer {
+    pub description: String,
+    bytes: AtomicU64,
+    state: Mutex<TransferState>,
+    finished: Mutex<Option<Instant>>,
+}
+
+impl Transfer {
+    fn new(description: String) -> Self {
+        Self {
+            description,
+            bytes: AtomicU64::new(0),
+            state: Mutex::new(TransferState::Running),
+            finished: Mutex::new(None),
+        }
+    }
+
+    pub fn add_bytes(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// The number of bytes transferred so far
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn state(&self) -> TransferState {
+        self.state.lock().unwrap().clone()
+    }
+
+    fn finish(&self, state: TransferState) {
+        *self.state.lock().unwrap() = state;
+        *self.finished.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn is_expired(&self) -> bool {
+        matches!(*self.finished.lock().unwrap(), Some(t) if t.elapsed() > FINISHED_DISPLAY_TIME)
+    }
+}
+
+/// Transfers which are running or have recently finished.  These are shared
+/// between pages, so a transfer continues when the page which started it is
+/// left.
+#[derive(Debug, Clone, Default)]
+pub struct Transfers(Arc<Mutex<Vec<Arc<Transfer>>>>);
+
+impl Transfers {
+    /// Runs a transfer in the background; the future is given the transfer to
+    /// record its progress against, and returns a message describing the result
+    pub fn spawn<F, Fut>(&self, tx: Sender<Message<Key, Transition>>, description: String, f: F)
+    where
+        F: FnOnce(Arc<Transfer>) -> Fut,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        let transfer = Arc::new(Transfer::new(description));
+        self.0.lock().unwrap().push(transfer.clone());
+
+        let job = f(transfer.clone());
+        tokio::spawn(async move {
+            let state = match job.await {
+                Ok(message) => TransferState::Done(message),
+                Err(e) => TransferState::Failed(format!("{e:#}")),
+            };
+            transfer.finish(state);
+            let _ = tx.send(Message::Tick).await;
+        });
+    }
+
+    /// The transfers which should be displayed, dropping any successful ones
+    /// which finished a while ago
+    pub fn visible(&self) -> Vec<Arc<Transfer>> {
+        let mut transfers = self.0.lock().unwrap();
+        transfers.retain(|t| !(t.is_expired() && matches!(t.state(), TransferState::Done(_))));
+        transfers.clone()
+    }
+
+    /// Removes and returns any failed transfers, so that their errors can be
+    /// reported
+    pub fn take_failed(&self) -> Vec<Arc<Transfer>> {
+        let mut transfers = self.0.lock().unwrap();
+        let (failed, rest) = transfers
+            .drain(..)
+            .partition(|t| matches!(t.state(), TransferState::Failed(_)));
+        *transfers = rest;
+        failed
+    }
+}
+
+/// Writes a stream of bytes from the daemon to a new local file, recording
+/// progress against the transfer.  The file is removed if the stream fails.
+pub async fn write_to_file<S>(stream: S, path: &Path, transfer: &Transfer) -> Result<()>
+where
+    S: Stream<Item = Result<Bytes, bollard::errors::Error>>,
+{
+    // Refuse to replace an existing file, which may have been created since
+    // the path was validated
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await
+        .with_context(|| format!("unable to create {}", path.display()))?;
+
+    let result: Result<()> = async {
+        let mut stream = std::pin::pin!(stream);
+        while let Some(chunk) = stream.try_next().await? {
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("unable to write {}", path.display()))?;
+            transfer.add_bytes(chunk.len());
+        }
+        file.flush()
+            .await
+            .with_context(|| format!("unable to write {}", path.display()))
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+    result
+}
+
+/// Streams a local file to be sent to the daemon, recording progress against
+/// the transfer as it is read
+pub async fn read_from_file(
+    path: &Path,
+    transfer: Arc<Transfer>,
+) -> Result<impl Stream<Item = std::io::Result<Bytes>> + Send + 'static> {
+    if !path.is_file() {
+        bail!("{} is not a file", path.display());
+    }
+    let file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("unable to open {}", path.display()))?;
+
+    Ok(ReaderStream::new(file).inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            transfer.add_bytes(chunk.len());
+        }
+    }))
+}
+
+/// Validates a local path which a transfer can write a new file to
+pub fn validate_output_path(path: &str) -> Result<()> {
+    if path.trim().is_empty() {
+        bail!("a path is required");
+    }
+    let path = expand_local_path(path.trim());
+    if path.is_dir() {
+        bail!("{} is a directory", path.display());
+    }
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            bail!("{} does not exist", parent.display())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre::eyre;
+    use futures::stream;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_take_failed() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let transfers = Transfers::default();
+        transfers.spawn(tx.clone(), "ok".into(), |_| async { Ok("done".into()) });
+        transfers.spawn(tx, "bad".into(), |_| async { Err(eyre!("disk full")) });
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+
+        let failed = transfers.take_failed();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].description, "bad");
+        assert_eq!(failed[0].state(), TransferState::Failed("disk full".into()));
+        assert!(transfers.take_failed().is_empty());
+
+        let visible = transfers.visible();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].state(), TransferState::Done("done".into()));
+    }
+
+    #[test]
+    fn test_visible_drops_expired_transfers() {
+        let transfers = Transfers::default();
+        let expired = Instant::now() - FINISHED_DISPLAY_TIME * 2;
+        for state in [
+            TransferState::Running,
+            TransferState::Done("done".into()),
+            TransferState::Failed("failed".into()),
+        ] {
+            let transfer = Transfer::new(format!("{state:?}"));
+            transfer.finish(state);
+            *transfer.finished.lock().unwrap() = Some(expired);
+            transfers.0.lock().unwrap().push(Arc::new(transfer));
+        }
+
+        // Failures remain until they have been reported
+        let states: Vec<_> = transfers.visible().iter().map(|t| t.state()).collect();
+        assert_eq!(
+            states,
+            vec![
+                TransferState::Running,
+                TransferState::Failed("failed".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_output_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing.tar");
+        std::fs::write(&existing, "").unwrap();
+        let path = |p: &Path| p.to_str().unwrap().to_string();
+
+        assert!(validate_output_path(&path(&dir.path().join("new.tar"))).is_ok());
+        assert!(validate_output_path("relative.tar").is_ok());
+        assert!(validate_output_path(" ").is_err());
+        assert!(validate_output_path(&path(dir.path())).is_err());
+        assert!(validate_output_path(&path(&existing)).is_err());
+        assert!(validate_output_path(&path(&dir.path().join("missing/new.tar"))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tar");
+        let transfer = Transfer::new("export".into());
+        let chunks = stream::iter([Ok(Bytes::from("abc")), Ok(Bytes::from("de"))]);
+
+        write_to_file(chunks, &path, &transfer).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcde");
+        assert_eq!(transfer.bytes(), 5);
+
+        // Existing files are never replaced
+        let chunks = stream::iter([Ok(Bytes::from("new"))]);
+        assert!(write_to_file(chunks, &path, &transfer).await.is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcde");
+    }
+
+    #[tokio::test]
+    async fn test_write_to_file_removes_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tar");
+        let transfer = Transfer::new("export".into());
+        let err = bollard::errors::Error::IOError {
+            err: std::io::Error::other("connection reset"),
+        };
+        let chunks = stream::iter([Ok(Bytes::from("abc")), Err(err)]);
+
+        assert!(write_to_file(chunks, &path, &transfer).await.is_err());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_read_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("in.tar");
+        std::fs::write(&path, "abcde").unwrap();
+        let transfer = Arc::new(Transfer::new("load".into()));
+
+        let chunks: Vec<Bytes> = read_from_file(&path, transfer.clone())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), b"abcde");
+        assert_eq!(transfer.bytes(), 5);
+
+        assert!(read_from_file(dir.path(), transfer).await.is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::{Context, Result};
+
+use super::{
+    container::DockerContainer, image::DockerImage, network::DockerNetwork, volume::DockerVolume,
+};
+
+/// How long the listing of containers is reused before the daemon is asked again
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The containers on the daemon, used to find which containers reference each
+/// image, volume and network.  A single listing of the containers includes
+/// their image, mounts and networks, so this avoids inspecting each resource.
+///
+/// Pages refresh their resources on every tick, so the listing is cached and
+/// only fetched again once it is `REFRESH_INTERVAL` old.
+#[derive(Debug, Clone, Default)]
+pub struct ContainerUsage {
+    containers: Vec<DockerContainer>,
+    fetched: Option<Instant>,
+}
+
+impl ContainerUsage {
+    /// Lists the containers again where the cached listing is out of date
+    pub async fn refresh(&mut self, docker: &bollard::Docker) -> Result<()> {
+        if !self.is_stale() {
+            return Ok(());
+        }
+        self.containers = DockerContainer::list(docker)
+            .await
+            .context("unable to retrieve container usage")?;
+        self.fetched = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Forces the containers to be listed on the next refresh, eg when a page is
+    /// opened and the cached listing may be from some time ago
+    pub fn invalidate(&mut self) {
+        self.fetched = None;
+    }
+
+    fn is_stale(&self) -> bool {
+        self.fetched
+            .is_none_or(|fetched| fetched.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// Containers created from the image
+    pub fn image(&self, image: &DockerImage) -> Vec<&DockerContainer> {
+        self.containers
+            .iter()
+            .filter(|c| c.image_id == image.id)
+            .collect()
+    }
+
+    /// Containers with the volume mounted
+    pub fn volume(&self, volume: &DockerVolume) -> Vec<&DockerContainer> {
+        self.containers
+            .iter()
+            .filter(|c| c.volumes.contains(&volume.name))
+            .collect()
+    }
+
+    /// Containers connected to the network
+    pub fn network(&self, network: &DockerNetwork) -> Vec<&DockerContainer> {
+        self.containers
+            .iter()
+            .filter(|c| c.networks.contains(&network.id))
+            .collect()
+    }
+}
+
+/// Summarises the containers using a resource for display in a table
+pub fn format_used_by(containers: &[&DockerContainer]) -> String {
+    containers
+        .iter()
+        .map(|c| c.names.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Options for picking one of the containers using a resource, labelled with
+/// each container's name and status
+pub fn used_by_options(containers: &[&DockerContainer]) -> Vec<(String, DockerContainer)> {
+    containers
+        .iter()
+        .map(|c| (format!("{} ({})", c.names, c.status), (*c).clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bollard::secret::{
+        ContainerSummary, ContainerSummaryNetworkSettings, EndpointSettings, MountPoint,
+        MountPointTypeEnum, Network, Volume,
+    };
+
+    use super::*;
+
+    fn container(
+        name: &str,
+        image_id: &str,
+        volumes: &[&str],
+        networks: &[&str],
+    ) -> DockerContainer {
+        let mounts = volumes
+            .iter()
+            .map(|v| MountPoint {
+                typ: Some(MountPointTypeEnum::VOLUME),
+                name: Some(v.to_string()),
+                ..Default::default()
+            })
+            // Bind mounts have no name, so are never matched to a volume
+            .chain([MountPoint {
+                typ: Some(MountPointTypeEnum::BIND),
+                source: Some("/src".into()),
+                ..Default::default()
+            }])
+            .collect();
+        let networks = networks
+            .iter()
+            .map(|id| {
+                let endpoint = EndpointSettings {
+                    network_id: Some(id.to_string()),
+                    ..Default::default()
+                };
+                (format!("{id}-name"), endpoint)
+            })
+            .collect::<HashMap<_, _>>();
+
+        DockerContainer::from(ContainerSummary {
+            id: Some(name.into()),
+            names: Some(vec![format!("/{name}")]),
+            image_id: Some(image_id.into()),
+            status: Some("Up 2 hours".into()),
+            mounts: Some(mounts),
+            network_settings: Some(ContainerSummaryNetworkSettings {
+                networks: Some(networks),
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn usage() -> ContainerUsage {
+        ContainerUsage {
+            containers: vec![
+                container("web", "sha256:a", &["data"], &["net1", "net2"]),
+                container("db", "sha256:b", &["data", "db"], &["net2"]),
+            ],
+            fetched: Some(Instant::now()),
+        }
+    }
+
+    fn names(containers: Vec<&DockerContainer>) -> Vec<&str> {
+        containers.iter().map(|c| c.names.as_str()).collect()
+    }
+
+    #[test]
+    fn test_image() {
+        let image = |id: &str| {
+            DockerImage::from(bollard::secret::ImageSummary {
+                id: id.into(),
+                repo_tags: vec!["image:latest".into()],
+                ..Default::default()
+            })
+            .remove(0)
+        };
+        assert_eq!(names(usage().image(&image("sha256:a"))), vec!["web"]);
+        assert!(usage().image(&image("sha256:c")).is_empty());
+    }
+
+    #[test]
+    fn test_volume() {
+        let volume = |name: &str| {
+            DockerVolume::from(Volume {
+                name: name.into(),
+                ..Default::default()
+            })
+        };
+        assert_eq!(names(usage().volume(&volume("data"))), vec!["web", "db"]);
+        assert_eq!(names(usage().volume(&volume("db"))), vec!["db"]);
+        assert!(usage().volume(&volume("/src")).is_empty());
+    }
+
+    #[test]
+    fn test_network() {
+        let network = |id: &str| {
+            DockerNetwork::from(Network {
+                id: Some(id.into()),
+                ..Default::default()
+            })
+        };
+        assert_eq!(names(usage().network(&network("net1"))), vec!["web"]);
+        assert_eq!(names(usage().network(&network("net2"))), vec!["web", "db"]);
+        // Containers are matched by network ID rather than name
+        assert!(usage().network(&network("net1-name")).is_empty());
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let mut usage = usage();
+        assert!(!usage.is_stale());
+        usage.fetched = Some(Instant::now() - REFRESH_INTERVAL);
+        assert!(usage.is_stale());
+        usage.fetched = Some(Instant::now());
+        usage.invalidate();
+        assert!(usage.is_stale());
+        assert!(ContainerUsage::default().is_stale());
+    }
+
+    #[test]
+    fn test_used_by() {
+        let usage = usage();
+        let containers: Vec<_> = usage.containers.iter().collect();
+        assert_eq!(format_used_by(&containers), "web, db");
+        assert!(format_used_by(&[]).is_empty());
+
+        let options = used_by_options(&containers);
+        assert_eq!(options[0].0, "web (Up 2 hours)");
+        assert_eq!(options[1].1, usage.containers[1]);
+    }
+}
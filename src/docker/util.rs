@@ -1,6 +1,12 @@
 use bollard::{API_DEFAULT_VERSION, Docker};
-use color_eyre::eyre::{Context, Result};
-use std::{env, path::Path};
+use byte_unit::{Byte, UnitType};
+use color_eyre::eyre::{Context, Result, bail};
+use std::{
+    collections::HashMap,
+    env,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use super::container::DockerContainer;
 
@@ -117,6 +123,157 @@ pub async fn new_local_docker_connection(
         .await
 }
 
+/// Formats a size in bytes in the most appropriate binary unit, eg `12.34 MiB`;
+/// negative sizes are used by the daemon where the size is unknown
+pub fn format_bytes(size: impl TryInto<u64>) -> Option<String> {
+    let size = size.try_into().ok()?;
+    let byte = Byte::from_u64(size).get_appropriate_unit(UnitType::Binary);
+    Some(format!("{byte:.2}"))
+}
+
+/// Splits a command into arguments as a shell would, so that arguments can be
+/// quoted or escaped, eg `sh -c "echo hello"`
+pub fn split_command(command: &str) -> Result<Vec<String>> {
+    match shell_words::split(command) {
+        Ok(args) => Ok(args),
+        Err(_) => bail!("missing closing quote in \"{command}\""),
+    }
+}
+
+/// Shortens an image or build cache ID to the 12 characters shown by the docker CLI
+pub fn short_id(id: &str) -> String {
+    id.trim_start_matches("sha256:").chars().take(12).collect()
+}
+
+/// Formats how long ago a unix timestamp was, eg `3 weeks ago`
+pub fn format_age(timestamp: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let seconds = (now - timestamp).max(0);
+
+    let (amount, unit) = match seconds {
+        s if s < 60 => (s, "second"),
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 60 * 60 * 24 => (s / (60 * 60), "hour"),
+        s if s < 60 * 60 * 24 * 7 => (s / (60 * 60 * 24), "day"),
+        s if s < 60 * 60 * 24 * 30 => (s / (60 * 60 * 24 * 7), "week"),
+        s if s < 60 * 60 * 24 * 365 => (s / (60 * 60 * 24 * 30), "month"),
+        s => (s / (60 * 60 * 24 * 365), "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural} ago")
+}
+
+/// Validates a comma separated list of labels in the form `KEY` or `KEY=value`
+pub fn validate_labels(labels: &str) -> Result<()> {
+    parse_labels(labels).map(|_| ())
+}
+
+/// Parses a comma separated list of labels in the form `KEY` or `KEY=value`
+pub fn parse_labels(labels: &str) -> Result<Vec<(String, Option<String>)>> {
+    parse_key_values(labels, true)
+}
+
+/// Parses a comma separated list in the form `KEY=value`, eg options or build
+/// args; where `allow_bare` is set, a `KEY` without any value is also accepted
+pub fn parse_key_values(list: &str, allow_bare: bool) -> Result<Vec<(String, Option<String>)>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| parse_key_value(v, allow_bare))
+        .collect()
+}
+
+/// Parses a single `KEY=value`, or a bare `KEY` where `allow_bare` is set
+pub fn parse_key_value(v: &str, allow_bare: bool) -> Result<(String, Option<String>)> {
+    match v.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), Some(value.to_string()))),
+        None if allow_bare => Ok((v.to_string(), None)),
+        _ if allow_bare => bail!("expected KEY or KEY=value, got \"{v}\""),
+        _ => bail!("expected KEY=value, got \"{v}\""),
+    }
+}
+
+/// Parses a comma separated list in the form `KEY=value` into a map, where
+/// any repeated key takes its last value
+pub fn parse_key_value_map(list: &str) -> Result<HashMap<String, String>> {
+    Ok(parse_key_values(list, false)?
+        .into_iter()
+        .map(|(key, value)| (key, value.unwrap_or_default()))
+        .collect())
+}
+
+/// Whether a resource has all of the labels, matching values where given
+pub fn matches_labels(
+    resource: Option<&HashMap<String, String>>,
+    labels: &[(String, Option<String>)],
+) -> bool {
+    labels.iter().all(
+        |(key, value)| match (resource.and_then(|r| r.get(key)), value) {
+            (Some(actual), Some(value)) => actual == value,
+            (Some(_), None) => true,
+            (None, _) => false,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command(r#"sh -c "echo 'hello world'" it\'s"#).unwrap(),
+            vec!["sh", "-c", "echo 'hello world'", "it's"]
+        );
+        assert_eq!(split_command("  /bin/bash  ").unwrap(), vec!["/bin/bash"]);
+        assert!(split_command("").unwrap().is_empty());
+        assert!(split_command("sh -c \"echo").is_err());
+    }
+
+    #[test]
+    fn test_parse_key_values() {
+        assert_eq!(
+            parse_key_values(" a=1, b=, c=x=y ,", false).unwrap(),
+            vec![
+                ("a".to_string(), Some("1".to_string())),
+                ("b".to_string(), Some(String::new())),
+                ("c".to_string(), Some("x=y".to_string())),
+            ]
+        );
+        assert!(parse_key_values("", false).unwrap().is_empty());
+        assert!(parse_key_values("a", false).is_err());
+        assert!(parse_key_values("=1", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_key_values_bare() {
+        assert_eq!(
+            parse_key_values("a,b=2", true).unwrap(),
+            vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some("2".to_string())),
+            ]
+        );
+        assert!(parse_key_values("=1", true).is_err());
+    }
+
+    #[test]
+    fn test_parse_key_value_map() {
+        assert_eq!(
+            parse_key_value_map("a=1,b=2,a=3").unwrap(),
+            HashMap::from([
+                ("a".to_string(), "3".to_string()),
+                ("b".to_string(), "2".to_string()),
+            ])
+        );
+        assert!(parse_key_value_map("a,b=2").is_err());
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
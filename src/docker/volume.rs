@@ -1,12 +1,25 @@
-use bollard::query_parameters::{ListVolumesOptionsBuilder, RemoveVolumeOptionsBuilder};
+use bollard::query_parameters::{
+    DownloadFromContainerOptionsBuilder, ListVolumesOptionsBuilder, RemoveVolumeOptionsBuilder,
+    UploadToContainerOptionsBuilder,
+};
 use bollard::secret::{Volume, VolumeScopeEnum};
 use byte_unit::{Byte, UnitType};
-use color_eyre::eyre::{Result, bail};
+use color_eyre::eyre::{Context, Result, bail};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 use crate::docker::traits::DescribeSection;
 
-use super::traits::Describe;
+use super::{
+    create::VolumeCreateConfig,
+    exec::DockerExec,
+    traits::Describe,
+    transfer::{Transfer, read_from_file, write_to_file},
+    volume_browser::{MOUNT_PATH, create_helper, discard_helper},
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct DockerVolume {
@@ -74,13 +87,98 @@ impl DockerVolume {
         Ok(docker_volumes)
     }
 
+    async fn inspect_response(&self, docker: &bollard::Docker) -> Result<Volume> {
+        docker
+            .inspect_volume(&self.name)
+            .await
+            .context("unable to inspect volume")
+    }
+
+    /// Creates a new volume from the given config, returning the new volume
+    pub async fn create(docker: &bollard::Docker, config: &VolumeCreateConfig) -> Result<Self> {
+        let volume = docker
+            .create_volume(config.to_request()?)
+            .await
+            .context("unable to create volume")?;
+        Ok(Self::from(volume))
+    }
+
     pub async fn delete(&self, docker: &bollard::Docker, force: bool) -> Result<()> {
         let opts = RemoveVolumeOptionsBuilder::default().force(force).build();
         docker.remove_volume(&self.get_name(), Some(opts)).await?;
         Ok(())
     }
+
+    /// Whether the volume contains no files
+    pub async fn is_empty(&self, docker: &bollard::Docker) -> Result<bool> {
+        let helper = create_helper(docker, self, true).await?;
+        let names = DockerExec::output(docker, &helper, &["ls", "-A", MOUNT_PATH]).await;
+        discard_helper(docker, &helper).await;
+        let names = names.with_context(|| format!("unable to list volume {}", self.name))?;
+        Ok(names.iter().all(u8::is_ascii_whitespace))
+    }
+
+    /// Writes a tarball of the volume's contents to a new local file through a
+    /// helper container, returning the SHA-256 checksum of the tarball.
+    ///
+    /// Paths in the tarball are relative to the root of the volume, as with
+    /// `tar -C /data -cf - .`, so it can be restored into any volume.
+    pub async fn backup(
+        &self,
+        docker: &bollard::Docker,
+        path: &Path,
+        transfer: &Transfer,
+    ) -> Result<String> {
+        let helper = create_helper(docker, self, true).await?;
+
+        let mut hasher = Sha256::new();
+        // The trailing `/.` causes the daemon to archive the directory's
+        // contents rather than the directory itself
+        let opts = DownloadFromContainerOptionsBuilder::default()
+            .path(&format!("{MOUNT_PATH}/."))
+            .build();
+        let stream = docker
+            .download_from_container(&helper.id, Some(opts))
+            .inspect_ok(|chunk| hasher.update(chunk));
+        let result = write_to_file(stream, path, transfer).await;
+
+        discard_helper(docker, &helper).await;
+        result.with_context(|| format!("unable to back up volume {}", self.name))?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Extracts a local tarball into the volume through a helper container,
+    /// returning the SHA-256 checksum of the tarball.  Files in the tarball
+    /// replace those of the same name in the volume; other files are left as
+    /// they are.
+    pub async fn restore(
+        &self,
+        docker: &bollard::Docker,
+        path: &Path,
+        transfer: Arc<Transfer>,
+    ) -> Result<String> {
+        let hasher = Arc::new(Mutex::new(Sha256::new()));
+        let stream_hasher = hasher.clone();
+        let stream = read_from_file(path, transfer)
+            .await?
+            .inspect_ok(move |chunk| stream_hasher.lock().unwrap().update(chunk));
+
+        let helper = create_helper(docker, self, false).await?;
+        let opts = UploadToContainerOptionsBuilder::default()
+            .path(MOUNT_PATH)
+            .build();
+        let result = docker
+            .upload_to_container(&helper.id, Some(opts), bollard::body_try_stream(stream))
+            .await;
+
+        discard_helper(docker, &helper).await;
+        result.with_context(|| format!("unable to restore volume {}", self.name))?;
+        let checksum = hasher.lock().unwrap().clone().finalize();
+        Ok(format!("{checksum:x}"))
+    }
 }
 
+#[async_trait::async_trait]
 impl Describe for DockerVolume {
     fn get_id(&self) -> String {
         self.get_name()
@@ -89,33 +187,47 @@ impl Describe for DockerVolume {
         self.name.clone()
     }
 
-    fn describe(&self) -> Result<Vec<DescribeSection>> {
+    async fn describe(&self, docker: &bollard::Docker) -> Result<Vec<DescribeSection>> {
+        let inspect = self.inspect_response(docker).await?;
+
         let mut summary = DescribeSection::new("Summary");
         summary
-            .item("Name", &self.name)
-            .item("Driver", &self.driver)
-            .item("Mountpoint", &self.mountpoint)
-            .item_opt("Created At", self.created_at.as_ref())
-            .item_opt("Scope", self.scope)
+            .item("Name", &inspect.name)
+            .item("Driver", &inspect.driver)
+            .item("Mountpoint", &inspect.mountpoint)
+            .item_opt("Created At", inspect.created_at.as_ref())
+            .item_opt("Scope", inspect.scope);
+        let mut result = vec![summary];
+
+        // Usage is only reported by the daemon when listing volumes, so the
+        // values from the list are used
+        let mut usage_section = DescribeSection::new("Usage");
+        usage_section
             .item_opt("Reference Count", self.ref_count)
             .item_opt("Size", self.size.as_ref());
-        let mut result = vec![summary];
+        result.push(usage_section);
 
-        if !self.labels.is_empty() {
-            let mut label_section = DescribeSection::new("Labels");
-            for (key, value) in &self.labels {
-                label_section.item(key, value);
-            }
+        let label_section = DescribeSection::from_map("Labels", &inspect.labels);
+        if !label_section.is_empty() {
             result.push(label_section);
         }
-        if !self.options.is_empty() {
-            let mut options_section = DescribeSection::new("Options");
-            for (key, value) in &self.options {
-                options_section.item(key, value);
-            }
+        let options_section = DescribeSection::from_map("Options", &inspect.options);
+        if !options_section.is_empty() {
             result.push(options_section);
         }
+        if let Some(status) = inspect.status.filter(|s| !s.is_empty()) {
+            let mut status_section = DescribeSection::new("Status");
+            for (idx, value) in status.iter().enumerate() {
+                status_section.item(idx + 1, value);
+            }
+            result.push(status_section);
+        }
 
         Ok(result)
     }
+
+    async fn inspect(&self, docker: &bollard::Docker) -> Result<serde_json::Value> {
+        let inspect = self.inspect_response(docker).await?;
+        Ok(serde_json::to_value(inspect)?)
+    }
 }
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use bollard::errors::Error::DockerResponseServerError;
+use bollard::query_parameters::{
+    CreateContainerOptionsBuilder, ListContainersOptionsBuilder, RemoveContainerOptionsBuilder,
+};
+use bollard::secret::{ContainerCreateBody, HostConfig};
+use color_eyre::eyre::{Context, Result};
+use futures::TryStreamExt;
+
+use super::{container::DockerContainer, registry, volume::DockerVolume};
+
+/// Image of the helper containers, which provides the `ls` used to list
+/// directories
+pub const HELPER_IMAGE: &str = "busybox:latest";
+
+/// Command run by the helper containers, which only need to stay running so
+/// that commands can be exec'd in them
+const HELPER_COMMAND: [&str; 3] = ["tail", "-f", "/dev/null"];
+
+/// Path at which the volume is mounted within the helper container
+pub const MOUNT_PATH: &str = "/volume";
+
+/// Label identifying the helper containers created by ducker
+const HELPER_LABEL: &str = "ducker.volume-browser";
+
+/// Label recording the session which created a helper container, in the form
+/// `host:pid`, so that only the helpers of sessions which have ended are
+/// removed as stale
+const OWNER_LABEL: &str = "ducker.volume-browser.owner";
+
+/// Starts a helper container with the volume mounted, through which the
+/// volume's files can be listed, read and written with the same APIs used for
+/// any other container.  This works wherever the daemon is, unlike reading the
+/// volume's mountpoint.
+///
+/// The container has no network and idles until it is removed; should ducker
+/// exit without removing it, it is removed by `remove_stale_helpers`.
+pub async fn create_helper(
+    docker: &bollard::Docker,
+    volume: &DockerVolume,
+    read_only: bool,
+) -> Result<DockerContainer> {
+    if docker.inspect_image(HELPER_IMAGE).await.is_err() {
+        registry::pull(docker, HELPER_IMAGE)
+            .try_collect::<Vec<_>>()
+            .await?;
+    }
+
+    let body = ContainerCreateBody {
+        image: Some(HELPER_IMAGE.into()),
+        cmd: Some(HELPER_COMMAND.map(String::from).to_vec()),
+        labels: Some(HashMap::from([
+            (HELPER_LABEL.into(), volume.name.clone()),
+            (OWNER_LABEL.into(), owner()),
+        ])),
+        host_config: Some(HostConfig {
+            binds: Some(vec![format!(
+                "{}:{MOUNT_PATH}:{}",
+                volume.name,
+                if read_only { "ro" } else { "rw" }
+            )]),
+            network_mode: Some("none".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let opts = CreateContainerOptionsBuilder::default().build();
+
+    let response = docker
+        .create_container(Some(opts), body)
+        .await
+        .with_context(|| format!("unable to create helper container for {}", volume.name))?;
+
+    let helper = DockerContainer::get(docker, &response.id).await?;
+    if let Err(e) = helper.start(docker).await {
+        discard_helper(docker, &helper).await;
+        return Err(e.wrap_err(format!(
+            "unable to start helper container for {}",
+            volume.name
+        )));
+    }
+    Ok(helper)
+}
+
+/// Removes a helper container created by `create_helper`
+pub async fn remove_helper(docker: &bollard::Docker, helper: &DockerContainer) -> Result<()> {
+    let opts = RemoveContainerOptionsBuilder::default()
+        .force(true)
+        .v(false)
+        .build();
+    match docker.remove_container(&helper.id, Some(opts)).await {
+        // The helper may already have been removed, eg by another session
+        // removing stale helpers
+        Ok(())
+        | Err(DockerResponseServerError {
+            status_code: 404, ..
+        }) => Ok(()),
+        Err(e) => Err(e).context("unable to remove helper container"),
+    }
+}
+
+/// Removes a helper container once the operation using it has finished,
+/// logging rather than returning any failure so that it doesn't hide the
+/// operation's own result; a helper left behind is removed as stale the next
+/// time ducker starts
+pub async fn discard_helper(docker: &bollard::Docker, helper: &DockerContainer) {
+    if let Err(e) = remove_helper(docker, helper).await {
+        tracing::warn!("{e:#}");
+    }
+}
+
+/// Removes any helper containers left behind by a previous session which
+/// ended without closing the volume browser, eg after a crash.  Helpers of
+/// sessions which are still running, or which were started on another
+/// machine and so can't be checked, are left alone.
+pub async fn remove_stale_helpers(docker: &bollard::Docker) -> Result<()> {
+    let filters = HashMap::from([("label", vec![HELPER_LABEL])]);
+    let opts = ListContainersOptionsBuilder::default()
+        .all(true)
+        .filters(&filters)
+        .build();
+    let helpers = docker
+        .list_containers(Some(opts))
+        .await
+        .context("unable to retrieve helper containers")?;
+
+    let host = hostname();
+    for helper in helpers {
+        let owner = helper
+            .labels
+            .as_ref()
+            .and_then(|l| l.get(OWNER_LABEL))
+            .map(String::as_str);
+        if is_orphaned(owner, &host) {
+            remove_helper(docker, &DockerContainer::from(helper)).await?;
+        }
+    }
+    Ok(())
+}
+
+fn owner() -> String {
+    format!("{}:{}", hostname(), std::process::id())
+}
+
+/// Whether the session which created a helper has ended; a helper without a
+/// valid owner can't belong to any session
+fn is_orphaned(owner: Option<&str>, host: &str) -> bool {
+    let Some((owner_host, pid)) = owner.and_then(|o| o.rsplit_once(':')) else {
+        return true;
+    };
+    let Ok(pid) = pid.parse::<u32>() else {
+        return true;
+    };
+    owner_host == host && !process_exists(pid)
+}
+
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, which is passed along
+    // with it
+    let result = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if result != 0 {
+        return String::new();
+    }
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).to_string()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn process_exists(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks whether the process exists, without
+    // sending it anything
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    // The process exists but belongs to another user
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a way to check, the session is assumed to still be running, so
+/// its helpers are never removed by mistake
+#[cfg(not(unix))]
+fn process_exists(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_orphaned() {
+        let host = hostname();
+        // The current session is running
+        assert!(!is_orphaned(Some(&owner()), &host));
+        // Sessions on other machines can't be checked
+        assert!(!is_orphaned(Some("elsewhere:1"), &host));
+        assert!(is_orphaned(None, &host));
+        assert!(is_orphaned(Some("invalid"), &host));
+        assert!(is_orphaned(Some(&format!("{host}:x")), &host));
+    }
+}
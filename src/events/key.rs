@@ -1,3 +1,4 @@
+use color_eyre::eyre::{Result, bail};
 use crossterm::event;
 use serde::Deserialize;
 use std::fmt;
@@ -99,6 +100,50 @@ impl Key {
             _ => panic!("unknown function key: F{}", n),
         }
     }
+
+    /// Encodes the key as the byte sequence a terminal would send for it, for
+    /// forwarding input to a process running in a TTY
+    pub fn to_terminal_bytes(&self) -> Vec<u8> {
+        let seq = |s: &str| s.as_bytes().to_vec();
+        match *self {
+            Key::Enter => vec![b'\r'],
+            Key::Tab => vec![b'\t'],
+            Key::Backspace => vec![0x7f],
+            Key::Esc => vec![0x1b],
+            Key::Left => seq("\x1b[D"),
+            Key::Right => seq("\x1b[C"),
+            Key::Up => seq("\x1b[A"),
+            Key::Down => seq("\x1b[B"),
+            Key::Ins => seq("\x1b[2~"),
+            Key::Delete => seq("\x1b[3~"),
+            Key::Home => seq("\x1b[H"),
+            Key::End => seq("\x1b[F"),
+            Key::PageUp => seq("\x1b[5~"),
+            Key::PageDown => seq("\x1b[6~"),
+            Key::F1 => seq("\x1bOP"),
+            Key::F2 => seq("\x1bOQ"),
+            Key::F3 => seq("\x1bOR"),
+            Key::F4 => seq("\x1bOS"),
+            Key::F5 => seq("\x1b[15~"),
+            Key::F6 => seq("\x1b[17~"),
+            Key::F7 => seq("\x1b[18~"),
+            Key::F8 => seq("\x1b[19~"),
+            Key::F9 => seq("\x1b[20~"),
+            Key::F10 => seq("\x1b[21~"),
+            Key::F11 => seq("\x1b[23~"),
+            Key::F12 => seq("\x1b[24~"),
+            Key::Char(c) => c.to_string().into_bytes(),
+            // Control characters are the lower 5 bits of the ascii value, eg ctrl-c is 0x03
+            Key::Ctrl(' ') | Key::Ctrl('@') => vec![0x00],
+            Key::Ctrl(c) if c.is_ascii() => vec![(c.to_ascii_uppercase() as u8) & 0x1f],
+            Key::Alt(c) => {
+                let mut bytes = vec![0x1b];
+                bytes.extend(c.to_string().into_bytes());
+                bytes
+            }
+            _ => vec![],
+        }
+    }
 }
 
 impl fmt::Display for Key {
@@ -214,3 +259,74 @@ impl From<event::KeyEvent> for Key {
         }
     }
 }
+
+/// Parses a comma separated sequence of keys in the format used by docker's
+/// detach keys, eg `ctrl-p,ctrl-q`.  Each key is either a single character or
+/// `ctrl-<char>`.
+pub fn parse_key_sequence(sequence: &str) -> Result<Vec<Key>> {
+    let keys = sequence
+        .split(',')
+        .map(|key| {
+            let key = key.trim();
+            let mut chars = key.chars();
+            match (key.strip_prefix("ctrl-"), chars.next(), chars.next()) {
+                (Some(c), _, _) if c.chars().count() == 1 => {
+                    Ok(Key::Ctrl(c.chars().next().unwrap().to_ascii_lowercase()))
+                }
+                (None, Some(c), None) => Ok(Key::Char(c)),
+                _ => bail!("invalid key \"{key}\"; expected a single character or ctrl-<char>"),
+            }
+        })
+        .collect::<Result<Vec<Key>>>()?;
+
+    if keys.is_empty() {
+        bail!("no keys provided");
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_terminal_bytes() {
+        assert_eq!(Key::Char('a').to_terminal_bytes(), b"a");
+        assert_eq!(Key::Char('é').to_terminal_bytes(), "é".as_bytes());
+        assert_eq!(Key::Enter.to_terminal_bytes(), b"\r");
+        assert_eq!(Key::Backspace.to_terminal_bytes(), [0x7f]);
+        assert_eq!(Key::Up.to_terminal_bytes(), b"\x1b[A");
+        assert_eq!(Key::Delete.to_terminal_bytes(), b"\x1b[3~");
+        assert_eq!(Key::F1.to_terminal_bytes(), b"\x1bOP");
+        assert_eq!(Key::F12.to_terminal_bytes(), b"\x1b[24~");
+        assert_eq!(Key::Alt('x').to_terminal_bytes(), b"\x1bx");
+        assert!(Key::Null.to_terminal_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_to_terminal_bytes_ctrl() {
+        assert_eq!(Key::Ctrl('c').to_terminal_bytes(), [0x03]);
+        assert_eq!(Key::Ctrl('C').to_terminal_bytes(), [0x03]);
+        assert_eq!(Key::Ctrl('p').to_terminal_bytes(), [0x10]);
+        assert_eq!(Key::Ctrl('[').to_terminal_bytes(), [0x1b]);
+        assert_eq!(Key::Ctrl(' ').to_terminal_bytes(), [0x00]);
+        assert!(Key::Ctrl('é').to_terminal_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_parse_key_sequence() {
+        assert_eq!(
+            parse_key_sequence("ctrl-p,ctrl-q").unwrap(),
+            vec![Key::Ctrl('p'), Key::Ctrl('q')]
+        );
+        assert_eq!(
+            parse_key_sequence(" ctrl-P , x").unwrap(),
+            vec![Key::Ctrl('p'), Key::Char('x')]
+        );
+        assert!(parse_key_sequence("").is_err());
+        assert!(parse_key_sequence("ctrl-").is_err());
+        assert!(parse_key_sequence("ctrl-pq").is_err());
+        assert!(parse_key_sequence("ab").is_err());
+        assert!(parse_key_sequence("ctrl-p,").is_err());
+    }
+}
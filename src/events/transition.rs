@@ -11,16 +11,28 @@ use crate::events::{Key, Message};
 #[derive(Debug, Clone, PartialEq)]
 pub enum Transition {
     Quit,
-    ToNewTerminal,
     ToViewMode,
     ToImagePage(AppContext),
+    ToPullPage(AppContext),
+    ToPushPage(AppContext),
+    ToHistoryPage(AppContext),
+    ToBuildPage(AppContext),
     ToContainerPage(AppContext),
     ToLogPage(AppContext),
     ToDescribeContainerPage(AppContext),
     ToAttach(AppContext),
+    ToAttachProcess(AppContext),
+    ToFilesPage(AppContext),
+    ToChangesPage(AppContext),
     ToVolumePage(AppContext),
     ToNetworkPage(AppContext),
+    ToNetworkDetailPage(AppContext),
     ToHelpPage(AppContext),
+    ToStatsPage(AppContext),
+    ToTopPage(AppContext),
+    ToSystemPage(AppContext),
+    ToPrunePage(AppContext),
+    ToEventsPage(AppContext),
 }
 
 pub async fn send_transition(
@@ -6,7 +6,7 @@ use color_eyre::eyre::Context;
 use ducker::{
     config::Config,
     docker::util::new_local_docker_connection,
-    events::{self, EventLoop, Key, Message},
+    events::{EventLoop, Key, Message},
     state, terminal,
     tracing::initialize_logging,
     ui::App,
@@ -95,12 +95,7 @@ async fn main() -> color_eyre::Result<()> {
                 }
             }
             Message::Transition(t) => {
-                if t == events::Transition::ToNewTerminal {
-                    terminal = ratatui::init();
-                    terminal.clear()?;
-                } else {
-                    let _ = &app.transition(t).await;
-                }
+                let _ = &app.transition(t).await;
             }
 
             Message::Tick => {
@@ -113,6 +108,7 @@ async fn main() -> color_eyre::Result<()> {
         }
     }
 
+    app.close().await;
     ratatui::restore();
 
     Ok(())
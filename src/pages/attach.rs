@@ -1,7 +1,5 @@
 use std::sync::{Arc, Mutex};
 
-use crossterm::terminal::disable_raw_mode;
-
 use color_eyre::eyre::{Result, bail};
 use ratatui::{Frame, layout::Rect};
 use ratatui_macros::{horizontal, vertical};
@@ -11,8 +9,11 @@ use crate::components::alert_modal::{AlertModal, ModalState};
 use crate::components::text_input_wrapper::TextInputWrapper;
 use crate::config::Config;
 use crate::context::AppContext;
+use crate::docker::exec::{DockerExec, ExecSession, ExecSessions};
 use crate::docker::traits::Describe;
+use crate::events::key::parse_key_sequence;
 use crate::traits::{Close, ModalComponent};
+use crate::widgets::terminal_pane::TerminalPaneWidget;
 use crate::{
     components::help::{PageHelp, PageHelpBuilder},
     docker::container::DockerContainer,
@@ -34,39 +35,81 @@ enum ModalType {
 #[derive(Debug)]
 pub struct Attach {
     config: Arc<Config>,
+    docker: bollard::Docker,
     container: Option<DockerContainer>,
     next: Option<Transition>,
     tx: Sender<Message<Key, Transition>>,
     page_help: Arc<Mutex<PageHelp>>,
     attach_input: TextInputWrapper,
     alert_modal: AlertModal<ModalType>,
+    sessions: ExecSessions,
+    session: Option<Arc<ExecSession>>,
+    escape_keys: Vec<Key>,
+    /// Number of keys of the escape sequence which have been entered so far
+    escape_progress: usize,
 }
 
 impl Attach {
-    pub fn new(tx: Sender<Message<Key, Transition>>, config: Arc<Config>) -> Self {
-        let page_help = Self::build_page_help(config.clone(), None);
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+        sessions: ExecSessions,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None, false);
+        // The escape keys are validated when the config is loaded
+        let escape_keys = parse_key_sequence(&config.exec_escape_keys).unwrap_or_default();
         Self {
             config,
+            docker,
             container: None,
             next: None,
             tx,
             page_help: Arc::new(Mutex::new(page_help)),
             attach_input: TextInputWrapper::new(PROMPT.into(), None),
             alert_modal: AlertModal::new("Error".into(), ModalType::AlertModal),
+            sessions,
+            session: None,
+            escape_keys,
+            escape_progress: 0,
         }
     }
 
-    pub fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
-        PageHelpBuilder::new(
+    pub fn build_page_help(
+        config: Arc<Config>,
+        name: Option<String>,
+        in_session: bool,
+    ) -> PageHelp {
+        let builder = PageHelpBuilder::new(
             match name {
                 Some(n) => n,
                 None => NAME.into(),
             },
             config.clone(),
-        )
-        .add_input(format!("{ESC_KEY}"), "back".into())
-        .add_input(format!("{ENTER}"), "exec".into())
-        .build()
+        );
+
+        if in_session {
+            builder
+                .add_input(format!("<{}>", config.exec_escape_keys), "leave".into())
+                .build()
+        } else {
+            builder
+                .add_input(format!("{ESC_KEY}"), "back".into())
+                .add_input(format!("{ENTER}"), "exec".into())
+                .build()
+        }
+    }
+
+    fn refresh_page_help(&mut self) {
+        let page_name = self
+            .container
+            .as_ref()
+            .map(|c| format!("{NAME} ({})", c.get_name()));
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            page_name,
+            self.session.is_some(),
+        )));
     }
 
     async fn to_containers(&self) -> Result<()> {
@@ -77,33 +120,97 @@ impl Attach {
         };
 
         self.tx.send(Message::Transition(transition)).await?;
-        self.tx
-            .send(Message::Transition(Transition::ToNewTerminal))
-            .await?;
 
         Ok(())
     }
 
-    async fn attach(&self, exec: &str) -> Result<()> {
-        if self.container.is_none() {
+    async fn start_session(&mut self, command: &str) -> Result<()> {
+        let Some(container) = &self.container else {
             bail!("could not attach to a container when no container is set");
         };
 
-        let container = self.container.clone().unwrap();
+        let exec = DockerExec::create(&self.docker, container, command).await?;
+        let terminal = exec.start(self.tx.clone()).await?;
 
-        disable_raw_mode()?;
-        let res = container.attach(exec).await;
-        self.tx
-            .send(Message::Transition(Transition::ToNewTerminal))
-            .await?;
+        let session = Arc::new(ExecSession {
+            exec,
+            command: command.into(),
+            terminal,
+        });
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(container.id.clone(), session.clone());
+        self.session = Some(session);
+        self.escape_progress = 0;
+        self.refresh_page_help();
 
-        if res.is_err() {
-            bail!("failed to attach to container");
+        Ok(())
+    }
+
+    /// Cleans up a session whose process has exited; if it exited unsuccessfully
+    /// the user is alerted and left on the prompt to try another command
+    async fn end_session(&mut self, session: Arc<ExecSession>) -> Result<()> {
+        if let Some(container) = &self.container {
+            self.sessions.lock().unwrap().remove(&container.id);
+        }
+        self.session = None;
+        self.refresh_page_help();
+
+        if let Some(err) = session.terminal.take_error() {
+            return Err(err.wrap_err(format!("exec of `{}` failed", session.command)));
+        }
+
+        match session.exec.exit_code().await {
+            Ok(Some(0)) | Ok(None) => self.to_containers().await?,
+            Ok(Some(code)) => {
+                let msg = format!("`{}` exited with status {code}", session.command);
+                self.alert_modal.initialise(msg)
+            }
+            Err(_) => {
+                let msg = format!("Error in exec with command\n`{}`", session.command);
+                self.alert_modal.initialise(msg)
+            }
+        }
+        Ok(())
+    }
+
+    /// Forwards a key to the session, unless it completes the escape sequence in
+    /// which case the session is left running in the background
+    async fn update_session(&mut self, session: Arc<ExecSession>, key: Key) -> Result<()> {
+        let (progress, forward) = advance_escape(&self.escape_keys, self.escape_progress, key);
+        for k in forward {
+            session.terminal.send_key(k);
+        }
+
+        if !self.escape_keys.is_empty() && progress == self.escape_keys.len() {
+            self.escape_progress = 0;
+            self.session = None;
+            self.to_containers().await?;
+        } else {
+            self.escape_progress = progress;
         }
         Ok(())
     }
 }
 
+/// Advances through the escape sequence with the given key, returning how many
+/// of its keys have now been entered along with the keys which turned out not
+/// to be part of it, and so belong to the session
+fn advance_escape(escape_keys: &[Key], progress: usize, key: Key) -> (usize, Vec<Key>) {
+    if escape_keys.get(progress) == Some(&key) {
+        return (progress + 1, vec![]);
+    }
+
+    let mut forward = escape_keys[..progress].to_vec();
+    if escape_keys.first() == Some(&key) {
+        (1, forward)
+    } else {
+        forward.push(key);
+        (0, forward)
+    }
+}
+
 #[async_trait::async_trait]
 impl Page for Attach {
     async fn update(&mut self, message: Key) -> Result<MessageResponse> {
@@ -111,15 +218,25 @@ impl Page for Attach {
             return self.alert_modal.update(message).await;
         }
 
+        if let Some(session) = self.session.clone() {
+            if message == Key::Null {
+                if session.terminal.is_finished() {
+                    self.end_session(session).await?;
+                }
+            } else {
+                self.update_session(session, message).await?;
+            }
+            // All input belongs to the session, including keys which would
+            // otherwise quit the application
+            return Ok(MessageResponse::Consumed);
+        }
+
         let res = match message {
             Key::Enter => {
                 let exec = self.attach_input.get_value();
-                match self.attach(&exec).await {
-                    Ok(()) => self.to_containers().await?,
-                    Err(_) => {
-                        let msg = format!("Error in exec with command\n`{exec}`");
-                        self.alert_modal.initialise(msg)
-                    }
+                if self.start_session(&exec).await.is_err() {
+                    let msg = format!("Error in exec with command\n`{exec}`");
+                    self.alert_modal.initialise(msg)
                 }
 
                 MessageResponse::Consumed
@@ -135,15 +252,26 @@ impl Page for Attach {
 
     async fn initialise(&mut self, cx: AppContext) -> Result<()> {
         if let Some(container) = cx.clone().docker_container {
-            let page_name = format!("{NAME} ({})", container.get_name());
-            self.page_help = Arc::new(Mutex::new(Self::build_page_help(
-                self.config.clone(),
-                Some(page_name),
-            )));
             self.container = Some(container)
         } else {
             bail!("no docker container")
         }
+
+        // Resume the container's session if one was left running
+        self.session = None;
+        if let Some(container) = &self.container {
+            let mut sessions = self.sessions.lock().unwrap();
+            match sessions.get(&container.id) {
+                Some(s) if !s.terminal.is_finished() => self.session = Some(s.clone()),
+                Some(_) => {
+                    sessions.remove(&container.id);
+                }
+                None => {}
+            }
+        }
+        self.escape_progress = 0;
+        self.refresh_page_help();
+
         self.attach_input
             .set_input(self.config.default_exec.clone());
         self.next = cx.next();
@@ -160,16 +288,66 @@ impl Close for Attach {}
 
 impl Component for Attach {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
-        let height = area.height;
+        if let Some(session) = &self.session {
+            let title = format!(
+                " {} (<{}> to leave) ",
+                session.command, self.config.exec_escape_keys
+            );
+            f.render_widget(TerminalPaneWidget::new(&session.terminal, title), area);
+        } else {
+            let height = area.height;
 
-        let [_, text_area, _] = vertical![==((height-3)/2), ==3, >=0].areas(area);
+            let [_, text_area, _] = vertical![==((height-3)/2), ==3, >=0].areas(area);
 
-        let [_, text_area, _] = horizontal![==5%, ==90%, ==5%].areas(text_area);
+            let [_, text_area, _] = horizontal![==5%, ==90%, ==5%].areas(text_area);
 
-        self.attach_input.draw(f, text_area);
+            self.attach_input.draw(f, text_area);
+        }
 
         if let ModalState::Open(_) = self.alert_modal.state.clone() {
             self.alert_modal.draw(f, area)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESCAPE: [Key; 2] = [Key::Ctrl('p'), Key::Ctrl('q')];
+
+    #[test]
+    fn test_advance_escape_completes() {
+        assert_eq!(advance_escape(&ESCAPE, 0, Key::Ctrl('p')), (1, vec![]));
+        assert_eq!(advance_escape(&ESCAPE, 1, Key::Ctrl('q')), (2, vec![]));
+    }
+
+    #[test]
+    fn test_advance_escape_forwards_other_keys() {
+        assert_eq!(
+            advance_escape(&ESCAPE, 0, Key::Char('a')),
+            (0, vec![Key::Char('a')])
+        );
+        assert_eq!(
+            advance_escape(&ESCAPE, 1, Key::Char('a')),
+            (0, vec![Key::Ctrl('p'), Key::Char('a')])
+        );
+    }
+
+    #[test]
+    fn test_advance_escape_restarts() {
+        // A repeated first key may still be the start of the sequence
+        assert_eq!(
+            advance_escape(&ESCAPE, 1, Key::Ctrl('p')),
+            (1, vec![Key::Ctrl('p')])
+        );
+    }
+
+    #[test]
+    fn test_advance_escape_without_keys() {
+        assert_eq!(
+            advance_escape(&[], 0, Key::Ctrl('p')),
+            (0, vec![Key::Ctrl('p')])
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Result, bail};
+use ratatui::{Frame, layout::Rect};
+use tokio::sync::mpsc::Sender;
+
+use crate::config::Config;
+use crate::context::AppContext;
+use crate::docker::terminal::TerminalSession;
+use crate::docker::traits::Describe;
+use crate::traits::Close;
+use crate::widgets::terminal_pane::TerminalPaneWidget;
+use crate::{
+    components::help::{PageHelp, PageHelpBuilder},
+    docker::container::DockerContainer,
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Component, Page},
+};
+
+const NAME: &str = "Attach Process";
+
+/// Page attached to the stdin, stdout and stderr of a container's main process.
+///
+/// Unlike the `Attach` page no new process is started; all input is sent to
+/// the main process, and the daemon ends the session when the configured detach
+/// keys are entered.
+#[derive(Debug)]
+pub struct AttachProcess {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    container: Option<DockerContainer>,
+    page_help: Arc<Mutex<PageHelp>>,
+    session: Option<TerminalSession>,
+    next: Option<Transition>,
+}
+
+impl AttachProcess {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None);
+
+        Self {
+            config,
+            docker,
+            tx,
+            container: None,
+            page_help: Arc::new(Mutex::new(page_help)),
+            session: None,
+            next: None,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
+        PageHelpBuilder::new(
+            match name {
+                Some(n) => n,
+                None => NAME.into(),
+            },
+            config.clone(),
+        )
+        .add_input(format!("<{}>", config.attach_detach_keys), "detach".into())
+        .build()
+    }
+
+    async fn back(&self) -> Result<()> {
+        let transition = if let Some(t) = self.next.clone() {
+            t
+        } else {
+            Transition::ToContainerPage(AppContext {
+                docker_container: self.container.clone(),
+                ..Default::default()
+            })
+        };
+
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for AttachProcess {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        let Some(session) = &self.session else {
+            // Only reachable where attaching failed, so there is nothing to do but leave
+            if message == Key::Esc {
+                self.back().await?;
+                return Ok(MessageResponse::Consumed);
+            }
+            return Ok(MessageResponse::NotConsumed);
+        };
+
+        if message == Key::Null {
+            // The session ends when the user detaches or the process exits
+            if session.is_finished() {
+                let error = session.take_error();
+                self.session = None;
+                self.back().await?;
+                if let Some(err) = error {
+                    return Err(err);
+                }
+            }
+        } else {
+            session.send_key(message);
+        }
+
+        // All input belongs to the process, including keys which would
+        // otherwise quit the application
+        Ok(MessageResponse::Consumed)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let Some(container) = cx.clone().docker_container else {
+            bail!("no docker container")
+        };
+
+        if !container.running {
+            bail!("container {} must be running to attach", container.names);
+        }
+
+        let page_name = format!("{NAME} ({})", container.get_name());
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            Some(page_name),
+        )));
+
+        self.next = cx.next();
+        self.container = Some(container.clone());
+        self.session = Some(
+            container
+                .attach(
+                    &self.docker,
+                    &self.config.attach_detach_keys,
+                    self.tx.clone(),
+                )
+                .await?,
+        );
+
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for AttachProcess {
+    async fn close(&mut self) -> Result<()> {
+        self.session = None;
+        Ok(())
+    }
+}
+
+impl Component for AttachProcess {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let (Some(session), Some(container)) = (&self.session, &self.container) else {
+            return;
+        };
+
+        let title = format!(
+            " {} (<{}> to detach) ",
+            container.names, self.config.attach_detach_keys
+        );
+        f.render_widget(TerminalPaneWidget::new(session, title), area);
+    }
+}
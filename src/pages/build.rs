@@ -0,0 +1,352 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Result, bail};
+use futures::StreamExt;
+use ratatui::{Frame, layout::Rect, style::Style, widgets::Paragraph};
+use ratatui_macros::vertical;
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
+
+use crate::{
+    components::{
+        form_modal::{self, FormField, FormModal, FormValues},
+        help::{PageHelp, PageHelpBuilder},
+        log_view::LogView,
+    },
+    config::Config,
+    context::AppContext,
+    docker::{
+        build::{
+            self, DEFAULT_DOCKERFILE, ImageBuildConfig, validate_build_args, validate_context,
+            validate_tag,
+        },
+        files::expand_local_path,
+        image::DockerImage,
+        registry::split_reference,
+        util::format_bytes,
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, ModalComponent, Page},
+};
+
+const NAME: &str = "Build";
+
+const ESC_KEY: Key = Key::Esc;
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const SPACE_BAR: Key = Key::Char(' ');
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum BuildStatus {
+    /// The build options are being entered
+    #[default]
+    Configuring,
+    Running,
+    /// Contains the id of the image built
+    Done(Option<String>),
+    Cancelled,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModalTypes {
+    Build,
+}
+
+/// Page which builds an image from a local directory, streaming the build's
+/// output; leaving the page before the build completes cancels it
+#[derive(Debug)]
+pub struct Build {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    context: PathBuf,
+    tag: Option<String>,
+    status: Arc<Mutex<BuildStatus>>,
+    log_view: LogView,
+    handle: Option<JoinHandle<()>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+}
+
+impl Build {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = PageHelpBuilder::new(NAME.into(), config.clone()).build();
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            context: PathBuf::new(),
+            tag: None,
+            status: Arc::new(Mutex::new(BuildStatus::default())),
+            log_view: LogView::default(),
+            handle: None,
+            form_modal: None,
+        }
+    }
+
+    fn status(&self) -> BuildStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    fn refresh_page_help(&mut self) {
+        let esc_description = if self.status() == BuildStatus::Running {
+            "cancel"
+        } else {
+            "back"
+        };
+        let mut page_help = PageHelpBuilder::new(
+            format!("{NAME} ({})", self.context.display()),
+            self.config.clone(),
+        )
+        .add_input(format!("{ESC_KEY}"), esc_description.into())
+        .add_input(format!("{G_KEY}"), "top".into())
+        .add_input(format!("{SHIFT_G_KEY}"), "bottom".into());
+        if !self.log_view.auto_scroll() {
+            page_help = page_help.add_input(format!("{SPACE_BAR}"), "auto-scroll".into());
+        }
+        self.page_help = Arc::new(Mutex::new(page_help.build()));
+    }
+
+    fn open_build_modal(&mut self) {
+        let message = format!("Build an image from {}", self.context.display());
+        let fields = vec![
+            FormField::text("dockerfile", "Dockerfile").with_value(DEFAULT_DOCKERFILE),
+            FormField::text("tag", "Tag (name[:tag])").with_validator(validate_tag),
+            FormField::text("build_args", "Build args (KEY=value, ...)")
+                .with_validator(validate_build_args),
+            FormField::text("target", "Target stage"),
+            FormField::toggle("no_cache", "No cache", false),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Build".into(), ModalTypes::Build, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    /// Archives the build context and starts the build in the background
+    fn start(&mut self, values: FormValues) -> Result<()> {
+        let config = ImageBuildConfig {
+            context: self.context.clone(),
+            dockerfile: values.text_opt("dockerfile"),
+            tag: values.text_opt("tag"),
+            build_args: values.text_opt("build_args"),
+            target: values.text_opt("target"),
+            no_cache: values.toggle("no_cache"),
+        };
+        let options = config.to_options()?;
+        self.tag = config.tag.clone();
+
+        self.log_view.reset();
+        *self.status.lock().unwrap() = BuildStatus::Running;
+
+        let docker = self.docker.clone();
+        let tx = self.tx.clone();
+        let status = self.status.clone();
+        let lines = self.log_view.lines();
+
+        self.handle = Some(tokio::spawn(async move {
+            let result: Result<Option<String>> = async {
+                lines.lock().unwrap().push(format!(
+                    "Sending build context {}",
+                    config.context.display()
+                ));
+                let _ = tx.send(Message::Tick).await;
+
+                let archive =
+                    tokio::task::spawn_blocking(move || config.archive_context()).await??;
+                lines.lock().unwrap().push(format!(
+                    "Sent {} of build context",
+                    format_bytes(archive.len()).unwrap_or_default()
+                ));
+
+                let mut image_id = None;
+                let mut stream = std::pin::pin!(build::build(&docker, options, archive));
+                while let Some(info) = stream.next().await {
+                    let (output, id) = build::parse_output(info?)?;
+                    lines.lock().unwrap().extend(output);
+                    image_id = id.or(image_id);
+                    let _ = tx.send(Message::Tick).await;
+                }
+                Ok(image_id)
+            }
+            .await;
+
+            *status.lock().unwrap() = match result {
+                Ok(image_id) => BuildStatus::Done(image_id),
+                Err(e) => BuildStatus::Failed(format!("{e:#}")),
+            };
+            let _ = tx.send(Message::Tick).await;
+        }));
+        Ok(())
+    }
+
+    fn cancel(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+        let mut status = self.status.lock().unwrap();
+        if *status == BuildStatus::Running {
+            *status = BuildStatus::Cancelled;
+        }
+    }
+
+    /// Returns to the images page, selecting the built image if the build succeeded
+    async fn back(&self) -> Result<()> {
+        let docker_image = match self.status() {
+            BuildStatus::Done(Some(image_id)) => {
+                // Tags without a version are given the default by the daemon
+                let name = self.tag.as_deref().map(|tag| match split_reference(tag) {
+                    (image, Some(tag)) => format!("{image}:{tag}"),
+                    (image, None) => image,
+                });
+                let built = DockerImage::list(&self.docker, true)
+                    .await?
+                    .into_iter()
+                    .filter(|i| i.id == image_id)
+                    .collect::<Vec<_>>();
+                built
+                    .iter()
+                    .find(|i| Some(i.get_full_name()) == name)
+                    .or(built.first())
+                    .cloned()
+            }
+            _ => None,
+        };
+
+        self.tx
+            .send(Message::Transition(Transition::ToImagePage(AppContext {
+                docker_image,
+                ..Default::default()
+            })))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for Build {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            match values {
+                Some(values) => {
+                    self.start(values)?;
+                    self.refresh_page_help();
+                }
+                // Cancelling the form abandons the build
+                None if self.form_modal.is_none() => self.back().await?,
+                None => {}
+            }
+            return res;
+        }
+
+        let res = match message {
+            // The output is drawn straight from the shared lines, but the help
+            // changes once the build has finished
+            Key::Null => {
+                self.log_view.update(message);
+                self.refresh_page_help();
+                MessageResponse::NotConsumed
+            }
+            ESC_KEY => {
+                if self.status() == BuildStatus::Running {
+                    self.cancel();
+                    self.refresh_page_help();
+                } else {
+                    self.back().await?;
+                }
+                MessageResponse::Consumed
+            }
+            _ => {
+                let auto_scroll = self.log_view.auto_scroll();
+                let res = self.log_view.update(message);
+                if auto_scroll != self.log_view.auto_scroll() {
+                    self.refresh_page_help();
+                }
+                res
+            }
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let Some(context) = cx.build_context else {
+            bail!("no build context")
+        };
+        validate_context(&context)?;
+
+        self.context = expand_local_path(context.trim());
+        self.refresh_page_help();
+        self.open_build_modal();
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Build {
+    async fn close(&mut self) -> Result<()> {
+        self.cancel();
+        Ok(())
+    }
+}
+
+impl Component for Build {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [banner_area, output_area] = vertical![==2, >=0].areas(area);
+
+        let context = self.context.display();
+        let (banner, colour) = match self.status() {
+            BuildStatus::Configuring => (
+                format!("Configure the build of {context}"),
+                self.config.theme.title(),
+            ),
+            BuildStatus::Running => (format!("Building {context}…"), self.config.theme.title()),
+            BuildStatus::Done(image_id) => {
+                let image = self
+                    .tag
+                    .clone()
+                    .or(image_id)
+                    .unwrap_or_else(|| context.to_string());
+                (
+                    format!("Successfully built {image}"),
+                    self.config.theme.success(),
+                )
+            }
+            BuildStatus::Cancelled => (
+                format!("Cancelled building {context}"),
+                self.config.theme.error(),
+            ),
+            BuildStatus::Failed(e) => (format!("Build failed: {e}"), self.config.theme.error()),
+        };
+        f.render_widget(
+            Paragraph::new(banner).style(Style::new().fg(colour).bold()),
+            banner_area,
+        );
+
+        self.log_view.draw(f, output_area);
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
+    }
+}
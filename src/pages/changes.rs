@@ -0,0 +1,409 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Result, bail};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation},
+};
+use tokio::sync::mpsc::Sender;
+use tui_tree_widget::{Tree, TreeItem, TreeState};
+
+use crate::{
+    components::help::{PageHelp, PageHelpBuilder},
+    config::Config,
+    context::AppContext,
+    docker::{
+        changes::{ChangeKind, FileChange},
+        container::DockerContainer,
+        traits::Describe,
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, Page},
+};
+
+const NAME: &str = "Changes";
+
+const ESC_KEY: Key = Key::Esc;
+const ENTER_KEY: Key = Key::Enter;
+const SPACE_KEY: Key = Key::Char(' ');
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const R_KEY: Key = Key::Char('r');
+
+/// A node in the tree of changed paths; intermediate directories which
+/// weren't themselves changed are included so the tree can be navigated
+#[derive(Debug, Default)]
+struct ChangeNode {
+    name: String,
+    path: String,
+    changed: bool,
+    children: BTreeMap<String, ChangeNode>,
+}
+
+impl ChangeNode {
+    fn insert(&mut self, path: &str) {
+        let mut node = self;
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            let child_path = format!("{}/{component}", node.path);
+            node = node
+                .children
+                .entry(component.to_string())
+                .or_insert_with(|| ChangeNode {
+                    name: component.to_string(),
+                    path: child_path,
+                    ..Default::default()
+                });
+        }
+        node.changed = true;
+    }
+
+    /// Identifiers of every node with children, relative to this node
+    fn parents(&self, prefix: &[String], parents: &mut Vec<Vec<String>>) {
+        for child in self.children.values() {
+            if child.children.is_empty() {
+                continue;
+            }
+            let mut identifier = prefix.to_vec();
+            identifier.push(child.path.clone());
+            child.parents(&identifier, parents);
+            parents.push(identifier);
+        }
+    }
+
+    fn to_tree_items(&self, style: Style) -> Vec<TreeItem<'_, String>> {
+        self.children
+            .values()
+            .map(|child| {
+                let text = if child.changed {
+                    Span::from(&child.name).style(style)
+                } else {
+                    Span::from(&child.name)
+                };
+                TreeItem::new(child.path.clone(), text, child.to_tree_items(style))
+                    .expect("paths are unique")
+            })
+            .collect()
+    }
+}
+
+/// All changes of a single kind, as a tree keyed by path
+#[derive(Debug)]
+struct ChangeGroup {
+    kind: ChangeKind,
+    count: usize,
+    root: ChangeNode,
+}
+
+/// Groups changes by kind, leaving out kinds without any changes
+fn group_changes(changes: &[FileChange]) -> Vec<ChangeGroup> {
+    ChangeKind::ALL
+        .into_iter()
+        .filter_map(|kind| {
+            let mut root = ChangeNode::default();
+            let mut count = 0;
+            for change in changes.iter().filter(|c| c.kind == kind) {
+                root.insert(&change.path);
+                count += 1;
+            }
+            (count > 0).then_some(ChangeGroup { kind, count, root })
+        })
+        .collect()
+}
+
+/// Page showing the files and directories which have been added, modified
+/// or deleted in a container relative to its image
+#[derive(Debug)]
+pub struct Changes {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    container: Option<DockerContainer>,
+    next: Option<Transition>,
+    groups: Vec<ChangeGroup>,
+    tree_state: TreeState<String>,
+    height: u16,
+}
+
+impl Changes {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            container: None,
+            next: None,
+            groups: vec![],
+            tree_state: TreeState::default(),
+            height: 0,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
+        PageHelpBuilder::new(name.unwrap_or(NAME.into()), config)
+            .add_input(format!("{ESC_KEY}"), "back".into())
+            .add_input(format!("{ENTER_KEY}"), "fold".into())
+            .add_input(format!("{R_KEY}"), "refresh".into())
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .build()
+    }
+
+    /// Fetches the container's changes and rebuilds the tree, with every
+    /// directory opened
+    async fn refresh(&mut self) -> Result<()> {
+        let Some(container) = &self.container else {
+            bail!("no container selected")
+        };
+        let changes = FileChange::list(&self.docker, container).await?;
+        self.groups = group_changes(&changes);
+
+        for group in &self.groups {
+            let group_id = vec![group.kind.to_string()];
+            let mut parents = vec![];
+            group.root.parents(&group_id, &mut parents);
+            self.tree_state.open(group_id);
+            for identifier in parents {
+                self.tree_state.open(identifier);
+            }
+        }
+        Ok(())
+    }
+
+    fn kind_style(&self, kind: ChangeKind) -> Style {
+        let colour = match kind {
+            ChangeKind::Added => self.config.theme.positive_highlight(),
+            ChangeKind::Modified => self.config.theme.paused_highlight(),
+            ChangeKind::Deleted => self.config.theme.negative_highlight(),
+        };
+        Style::new().fg(colour)
+    }
+
+    async fn back(&self) -> Result<()> {
+        let transition = match &self.next {
+            Some(t) => t.clone(),
+            None => Transition::ToContainerPage(AppContext {
+                docker_container: self.container.clone(),
+                ..Default::default()
+            }),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(())
+    }
+
+    fn down(&mut self, amount: u16) {
+        for _ in 0..amount {
+            self.tree_state.key_down();
+        }
+    }
+
+    fn up(&mut self, amount: u16) {
+        for _ in 0..amount {
+            self.tree_state.key_up();
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for Changes {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        let res = match message {
+            UP_KEY | K_KEY => {
+                self.up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.up(self.height);
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.down(self.height);
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.tree_state.select_first();
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.tree_state.select_last();
+                MessageResponse::Consumed
+            }
+            ENTER_KEY | SPACE_KEY => {
+                self.tree_state.toggle_selected();
+                MessageResponse::Consumed
+            }
+            R_KEY => {
+                self.refresh().await?;
+                MessageResponse::Consumed
+            }
+            ESC_KEY => {
+                self.back().await?;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let Some(container) = cx.docker_container.clone() else {
+            bail!("no docker container")
+        };
+
+        let page_name = format!("{NAME} ({})", container.get_name());
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            Some(page_name),
+        )));
+
+        self.next = cx.next();
+        self.container = Some(container);
+        self.tree_state = TreeState::default();
+        self.refresh().await
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Changes {}
+
+impl Component for Changes {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        self.height = area.height.saturating_sub(1);
+
+        if self.groups.is_empty() {
+            let paragraph = Paragraph::new("No changes to the container's filesystem")
+                .style(Style::new().fg(self.config.theme.help()));
+            f.render_widget(paragraph, area);
+            return;
+        }
+
+        let tree = self
+            .groups
+            .iter()
+            .map(|group| {
+                let style = self.kind_style(group.kind);
+                let text = Line::from(vec![
+                    Span::from(group.kind.to_string()).style(style.bold()),
+                    Span::from(format!(" ({})", group.count)),
+                ]);
+                TreeItem::new(
+                    group.kind.to_string(),
+                    text,
+                    group.root.to_tree_items(style),
+                )
+                .expect("paths are unique")
+            })
+            .collect::<Vec<_>>();
+
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .track_symbol(None)
+            .end_symbol(None);
+        let widget = Tree::new(tree.as_slice())
+            .expect("all item identifiers are unique")
+            .experimental_scrollbar(Some(scrollbar))
+            .highlight_style(
+                Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::LightGreen)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("");
+
+        f.render_stateful_widget(widget, area, &mut self.tree_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, kind: ChangeKind) -> FileChange {
+        FileChange {
+            path: path.into(),
+            kind,
+        }
+    }
+
+    fn node<'a>(root: &'a ChangeNode, path: &str) -> &'a ChangeNode {
+        path.split('/')
+            .filter(|c| !c.is_empty())
+            .fold(root, |node, c| &node.children[c])
+    }
+
+    #[test]
+    fn test_group_changes() {
+        let groups = group_changes(&[
+            change("/etc", ChangeKind::Modified),
+            change("/etc/hosts", ChangeKind::Modified),
+            change("/tmp/a", ChangeKind::Added),
+            change("/var/log/app/out.log", ChangeKind::Added),
+        ]);
+
+        let kinds: Vec<_> = groups.iter().map(|g| (g.kind, g.count)).collect();
+        assert_eq!(
+            kinds,
+            vec![(ChangeKind::Added, 2), (ChangeKind::Modified, 2)]
+        );
+
+        let added = &groups[0].root;
+        let names: Vec<_> = added.children.keys().collect();
+        assert_eq!(names, vec!["tmp", "var"]);
+        // Directories which weren't changed themselves are still included
+        assert!(!node(added, "/var/log").changed);
+        assert_eq!(node(added, "/var/log/app").path, "/var/log/app");
+        assert!(node(added, "/var/log/app/out.log").changed);
+
+        let modified = &groups[1].root;
+        assert!(node(modified, "/etc").changed);
+        assert!(node(modified, "/etc/hosts").changed);
+    }
+
+    #[test]
+    fn test_group_changes_empty() {
+        assert!(group_changes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parents() {
+        let mut root = ChangeNode::default();
+        root.insert("/a/b/c");
+        root.insert("/a/d");
+        root.insert("/e");
+
+        let mut parents = vec![];
+        root.parents(&["Added".to_string()], &mut parents);
+        assert_eq!(
+            parents,
+            vec![
+                vec!["Added".to_string(), "/a".into(), "/a/b".into()],
+                vec!["Added".to_string(), "/a".into()],
+            ]
+        );
+    }
+}
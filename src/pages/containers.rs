@@ -18,11 +18,20 @@ use crate::{
     callbacks::{DeleteContainer, delete_all_containers::DeleteAllContainers},
     components::{
         boolean_modal::{BooleanModal, ModalState},
+        form_modal::{self, FormField, FormModal, FormValues},
         help::{PageHelp, PageHelpBuilder},
+        select_modal::{self, SelectModal},
     },
     config::Config,
     context::AppContext,
-    docker::container::DockerContainer,
+    docker::{
+        commit::{
+            ContainerCommitConfig, DEFAULT_TAG, validate_changes, validate_repository, validate_tag,
+        },
+        container::DockerContainer,
+        files::expand_local_path,
+        transfer::{Transfers, validate_output_path},
+    },
     events::{Key, Message, Transition, message::MessageResponse},
     sorting::{
         ContainerSortField, SortOrder, SortState, sort_containers_by_created,
@@ -40,16 +49,26 @@ const PAGE_UP_KEY: Key = Key::PageUp;
 const PAGE_DOWN_KEY: Key = Key::PageDown;
 
 const A_KEY: Key = Key::Char('a');
+const C_KEY: Key = Key::Char('c');
 const J_KEY: Key = Key::Char('j');
 const K_KEY: Key = Key::Char('k');
 const CTRL_D_KEY: Key = Key::Ctrl('d');
+const CTRL_K_KEY: Key = Key::Ctrl('k');
+const CTRL_R_KEY: Key = Key::Ctrl('r');
+const SHIFT_A_KEY: Key = Key::Char('A');
 const SHIFT_D_KEY: Key = Key::Char('D');
 const SHIFT_F_KEY: Key = Key::Char('F');
 const D_KEY: Key = Key::Char('d');
+const E_KEY: Key = Key::Char('e');
+const F_KEY: Key = Key::Char('f');
 const R_KEY: Key = Key::Char('r');
 const S_KEY: Key = Key::Char('s');
+const T_KEY: Key = Key::Char('t');
 const G_KEY: Key = Key::Char('g');
+const I_KEY: Key = Key::Char('i');
 const L_KEY: Key = Key::Char('l');
+const P_KEY: Key = Key::Char('p');
+const U_KEY: Key = Key::Char('u');
 const SHIFT_G_KEY: Key = Key::Char('G');
 
 // Sorting keys
@@ -59,9 +78,50 @@ const SHIFT_S_KEY: Key = Key::Char('S');
 const SHIFT_C_KEY: Key = Key::Char('C');
 const SHIFT_P_KEY: Key = Key::Char('P');
 
+/// Signals offered when killing a container; the first is the default
+const KILL_SIGNALS: [&str; 8] = [
+    "SIGKILL", "SIGTERM", "SIGINT", "SIGHUP", "SIGQUIT", "SIGUSR1", "SIGUSR2", "SIGSTOP",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ModalTypes {
     DeleteContainer,
+    KillContainer,
+    Commit,
+    Export,
+}
+
+/// Actions which change the state of a container; these are run in the
+/// background, with the container highlighted until the action completes
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ContainerAction {
+    Stop,
+    Restart,
+    Pause,
+    Unpause,
+    Kill(String),
+}
+
+impl ContainerAction {
+    async fn run(&self, container: &DockerContainer, docker: &Docker) -> Result<()> {
+        match self {
+            Self::Stop => container.stop(docker).await,
+            Self::Restart => container.restart(docker).await,
+            Self::Pause => container.pause(docker).await,
+            Self::Unpause => container.unpause(docker).await,
+            Self::Kill(signal) => container.kill(docker, signal).await,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+            Self::Pause => "pause",
+            Self::Unpause => "unpause",
+            Self::Kill(_) => "kill",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -74,7 +134,10 @@ pub struct Containers {
     containers: Vec<DockerContainer>,
     list_state: TableState,
     modal: Option<BooleanModal<ModalTypes>>,
-    stopping_containers: Arc<Mutex<HashSet<String>>>,
+    signal_modal: Option<SelectModal<ModalTypes, String>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    transfers: Transfers,
+    pending_containers: Arc<Mutex<HashSet<String>>>,
     sort_state: SortState<ContainerSortField>,
     table_height: u16,
 }
@@ -94,6 +157,39 @@ impl Page for Containers {
             return res;
         }
 
+        if let Some(m) = self.signal_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let signal = m.take_selected();
+            if let select_modal::ModalState::Closed = m.state {
+                self.signal_modal = None;
+            }
+            if let Some(signal) = signal {
+                self.container_action(ContainerAction::Kill(signal))
+                    .await
+                    .context("could not kill container")?;
+            }
+            return res;
+        }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let modal_type = m.discriminator;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            match (modal_type, values) {
+                (ModalTypes::Commit, Some(values)) => self.commit_container(values).await?,
+                (ModalTypes::Export, Some(values)) => self.export_container(values)?,
+                _ => {}
+            }
+            return res;
+        }
+
         let result = match message {
             UP_KEY | K_KEY => {
                 self.scroll_up(1);
@@ -130,11 +226,33 @@ impl Page for Containers {
                 MessageResponse::Consumed
             }
             S_KEY => {
-                self.stop_container()
+                self.container_action(ContainerAction::Stop)
                     .await
                     .context("could not stop container")?;
                 MessageResponse::Consumed
             }
+            CTRL_R_KEY => {
+                self.container_action(ContainerAction::Restart)
+                    .await
+                    .context("could not restart container")?;
+                MessageResponse::Consumed
+            }
+            P_KEY => {
+                self.container_action(ContainerAction::Pause)
+                    .await
+                    .context("could not pause container")?;
+                MessageResponse::Consumed
+            }
+            U_KEY => {
+                self.container_action(ContainerAction::Unpause)
+                    .await
+                    .context("could not unpause container")?;
+                MessageResponse::Consumed
+            }
+            CTRL_K_KEY => match self.kill_container() {
+                Ok(_) => MessageResponse::Consumed,
+                Err(_) => MessageResponse::NotConsumed,
+            },
             G_KEY => {
                 self.list_state.select(Some(0));
                 MessageResponse::Consumed
@@ -151,6 +269,18 @@ impl Page for Containers {
                     .await?;
                 MessageResponse::Consumed
             }
+            SHIFT_A_KEY => {
+                let container = self.get_container()?;
+                if !container.running {
+                    bail!("container {} must be running to attach", container.names);
+                }
+                self.tx
+                    .send(Message::Transition(Transition::ToAttachProcess(
+                        self.get_context()?,
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
             L_KEY => {
                 self.tx
                     .send(Message::Transition(Transition::ToLogPage(
@@ -159,6 +289,38 @@ impl Page for Containers {
                     .await?;
                 MessageResponse::Consumed
             }
+            T_KEY => {
+                self.tx
+                    .send(Message::Transition(Transition::ToStatsPage(
+                        self.get_context()?,
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            F_KEY => {
+                self.tx
+                    .send(Message::Transition(Transition::ToFilesPage(
+                        self.get_context()?,
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            C_KEY => {
+                self.tx
+                    .send(Message::Transition(Transition::ToChangesPage(
+                        self.get_context()?,
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            E_KEY => {
+                self.open_export_modal()?;
+                MessageResponse::Consumed
+            }
+            I_KEY => {
+                self.open_form_modal()?;
+                MessageResponse::Consumed
+            }
             D_KEY => {
                 self.tx
                     .send(Message::Transition(Transition::ToDescribeContainerPage(
@@ -242,16 +404,31 @@ impl Page for Containers {
 impl Close for Containers {}
 
 impl Containers {
-    pub fn new(docker: Docker, tx: Sender<Message<Key, Transition>>, config: Arc<Config>) -> Self {
+    pub fn new(
+        docker: Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+        transfers: Transfers,
+    ) -> Self {
         let page_help = PageHelpBuilder::new(NAME.to_string(), config.clone())
             .add_input(format!("{A_KEY}"), "exec".to_string())
+            .add_input(format!("{SHIFT_A_KEY}"), "attach".to_string())
             .add_input(format!("{CTRL_D_KEY}"), "delete".to_string())
             .add_input(format!("{SHIFT_D_KEY}"), "delete all".to_string())
             .add_input(format!("{R_KEY}"), "run".to_string())
             .add_input(format!("{S_KEY}"), "stop".to_string())
+            .add_input(format!("{CTRL_R_KEY}"), "restart".to_string())
+            .add_input(format!("{P_KEY}"), "pause".to_string())
+            .add_input(format!("{U_KEY}"), "unpause".to_string())
+            .add_input(format!("{CTRL_K_KEY}"), "kill".to_string())
             .add_input(format!("{G_KEY}"), "top".to_string())
             .add_input(format!("{SHIFT_G_KEY}"), "bottom".to_string())
             .add_input(format!("{L_KEY}"), "logs".to_string())
+            .add_input(format!("{T_KEY}"), "stats".to_string())
+            .add_input(format!("{F_KEY}"), "files".to_string())
+            .add_input(format!("{C_KEY}"), "changes".to_string())
+            .add_input(format!("{I_KEY}"), "commit".to_string())
+            .add_input(format!("{E_KEY}"), "export".to_string())
             .build();
 
         Self {
@@ -263,7 +440,10 @@ impl Containers {
             containers: vec![],
             list_state: TableState::default(),
             modal: None,
-            stopping_containers: Arc::new(Mutex::new(HashSet::new())),
+            signal_modal: None,
+            form_modal: None,
+            transfers,
+            pending_containers: Arc::new(Mutex::new(HashSet::new())),
             sort_state: SortState::new(ContainerSortField::Name),
             table_height: 0,
         }
@@ -331,9 +511,9 @@ impl Containers {
         Ok(None)
     }
 
-    async fn stop_container(&mut self) -> Result<Option<()>> {
+    async fn container_action(&mut self, action: ContainerAction) -> Result<Option<()>> {
         if let Ok(container) = self.get_container() {
-            self.stopping_containers
+            self.pending_containers
                 .lock()
                 .unwrap()
                 .insert(container.id.clone());
@@ -341,15 +521,15 @@ impl Containers {
             let c = container.clone();
             let docker = self.docker.clone();
             let tx = self.tx.clone();
-            let stopping_containers = self.stopping_containers.clone();
+            let pending_containers = self.pending_containers.clone();
             tokio::spawn(async move {
-                let message = if c.stop(&docker).await.is_ok() {
+                let message = if action.run(&c, &docker).await.is_ok() {
                     Message::Tick
                 } else {
-                    let msg = format!("Failed to delete container {}", c.id);
+                    let msg = format!("Failed to {} container {}", action.name(), c.id);
                     Message::Error(msg)
                 };
-                stopping_containers.lock().unwrap().remove(&c.id);
+                pending_containers.lock().unwrap().remove(&c.id);
                 let _ = tx.send(message).await;
             });
 
@@ -391,6 +571,121 @@ impl Containers {
         Ok(())
     }
 
+    fn kill_container(&mut self) -> Result<()> {
+        let container = self.get_container()?;
+        let message = format!("Select a signal to send to container {}", container.names);
+
+        let options = KILL_SIGNALS
+            .iter()
+            .map(|s| (s.to_string(), s.to_string()))
+            .collect();
+
+        let mut modal =
+            SelectModal::<ModalTypes, String>::new("Kill".into(), ModalTypes::KillContainer);
+        modal.initialise(message, options);
+        self.signal_modal = Some(modal);
+        Ok(())
+    }
+
+    fn open_form_modal(&mut self) -> Result<()> {
+        let container = self.get_container()?;
+        let message = format!("Commit container {} to a new image", container.names);
+        let repository = container
+            .names
+            .split(", ")
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let fields = vec![
+            FormField::text("repository", "Repository")
+                .with_value(&repository)
+                .with_validator(validate_repository),
+            FormField::text("tag", "Tag")
+                .with_value(DEFAULT_TAG)
+                .with_validator(validate_tag),
+            FormField::text("author", "Author"),
+            FormField::text("message", "Message"),
+            FormField::toggle("pause", "Pause during commit", true),
+            FormField::text("changes", "Changes (CMD ...; ENV ...; EXPOSE ...)")
+                .with_validator(validate_changes),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Commit".into(), ModalTypes::Commit, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    /// Commits the selected container from the submitted commit form, then moves
+    /// to the images page with the new image selected
+    async fn commit_container(&mut self, values: FormValues) -> Result<()> {
+        let container = self.get_container()?;
+
+        let config = ContainerCommitConfig {
+            repository: values.text("repository"),
+            tag: values.text_opt("tag"),
+            author: values.text_opt("author"),
+            message: values.text_opt("message"),
+            pause: values.toggle("pause"),
+            changes: values.text_opt("changes"),
+        };
+
+        let image = container
+            .commit(&self.docker, &config)
+            .await
+            .context("unable to commit container")?;
+
+        self.tx
+            .send(Message::Transition(Transition::ToImagePage(AppContext {
+                docker_image: Some(image),
+                ..Default::default()
+            })))
+            .await?;
+        Ok(())
+    }
+
+    fn open_export_modal(&mut self) -> Result<()> {
+        let container = self.get_container()?;
+        let message = format!(
+            "Export the filesystem of container {} as a tarball",
+            container.names
+        );
+        let name = container.names.split(", ").next().unwrap_or_default();
+
+        let fields = vec![
+            FormField::text("path", "Local file")
+                .with_value(&format!("./{name}.tar"))
+                .with_validator(validate_output_path),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Export".into(), ModalTypes::Export, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    /// Exports the selected container to the submitted path in the background
+    fn export_container(&mut self, values: FormValues) -> Result<()> {
+        let container = self.get_container()?.clone();
+        let path = expand_local_path(&values.text("path"));
+        let docker = self.docker.clone();
+
+        let description = format!("Exporting {} to {}", container.names, path.display());
+        self.transfers
+            .spawn(self.tx.clone(), description, move |transfer| async move {
+                container.export(&docker, &path, &transfer).await?;
+                Ok(format!(
+                    "Exported {} to {}",
+                    container.names,
+                    path.display()
+                ))
+            });
+        Ok(())
+    }
+
     fn delete_all_containers(&mut self, force: bool) -> Result<()> {
         let cb = Arc::new(FutureMutex::new(DeleteAllContainers::new(
             self.docker.clone(),
@@ -447,8 +742,10 @@ impl Component for Containers {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
         self.table_height = area.height.saturating_sub(2);
         let rows = self.containers.clone().into_iter().map(|c| {
-            let style = if self.stopping_containers.lock().unwrap().contains(&c.id) {
+            let style = if self.pending_containers.lock().unwrap().contains(&c.id) {
                 Style::default().fg(self.config.theme.negative_highlight())
+            } else if c.paused {
+                Style::default().fg(self.config.theme.paused_highlight())
             } else if c.running {
                 Style::default().fg(self.config.theme.positive_highlight())
             } else {
@@ -485,5 +782,37 @@ impl Component for Containers {
         {
             m.draw(f, area)
         }
+
+        if let Some(m) = self.signal_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_defaults_to_sigkill() {
+        assert_eq!(KILL_SIGNALS[0], "SIGKILL");
+        assert!(KILL_SIGNALS.contains(&"SIGTERM"));
+    }
+
+    #[test]
+    fn test_container_action_name() {
+        assert_eq!(ContainerAction::Stop.name(), "stop");
+        assert_eq!(ContainerAction::Restart.name(), "restart");
+        assert_eq!(ContainerAction::Pause.name(), "pause");
+        assert_eq!(ContainerAction::Unpause.name(), "unpause");
+        assert_eq!(ContainerAction::Kill("SIGTERM".into()).name(), "kill");
     }
 }
@@ -13,6 +13,7 @@ use tokio::sync::mpsc::Sender;
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 use uuid::Uuid;
 
+use crate::components::inspect_view::InspectView;
 use crate::config::Config;
 use crate::context::AppContext;
 use crate::docker::traits::{Describe, DescribeSection};
@@ -32,10 +33,12 @@ const PAGE_DOWN_KEY: Key = Key::PageDown;
 
 const J_KEY: Key = Key::Char('j');
 const K_KEY: Key = Key::Char('k');
+const R_KEY: Key = Key::Char('r');
+const F_KEY: Key = Key::Char('f');
 
 #[derive(Debug)]
 pub struct DescribeContainer {
-    _docker: Docker,
+    docker: Docker,
     config: Arc<Config>,
     thing: Option<Box<dyn Describe>>,
     thing_summary: Option<Vec<DescribeSection>>,
@@ -44,14 +47,17 @@ pub struct DescribeContainer {
     page_help: Arc<Mutex<PageHelp>>,
     tree_state: TreeState<Uuid>,
     height: u16,
+    inspect_view: InspectView,
+    show_raw: bool,
 }
 
 impl DescribeContainer {
     pub fn new(docker: Docker, tx: Sender<Message<Key, Transition>>, config: Arc<Config>) -> Self {
-        let page_help = Self::build_page_help(config.clone(), None);
+        let page_help = Self::build_page_help(config.clone(), None, false);
+        let inspect_view = InspectView::new(config.clone());
 
         Self {
-            _docker: docker,
+            docker,
             config,
             thing: None,
             thing_summary: None,
@@ -60,16 +66,57 @@ impl DescribeContainer {
             page_help: Arc::new(Mutex::new(page_help)),
             tree_state: TreeState::default(),
             height: 0,
+            inspect_view,
+            show_raw: false,
         }
     }
 
-    fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
+    fn build_page_help(config: Arc<Config>, name: Option<String>, show_raw: bool) -> PageHelp {
         let page_name = if let Some(name) = name {
             name
         } else {
             NAME.into()
         };
-        PageHelpBuilder::new(page_name, config).build()
+        let builder = PageHelpBuilder::new(page_name, config)
+            .add_input(format!("{}", Key::Esc), "back".into());
+
+        if show_raw {
+            builder
+                .add_input(format!("{R_KEY}"), "summary".into())
+                .add_input(format!("{F_KEY}"), "json/yaml".into())
+                .add_input(format!("{}", Key::Enter), "fold".into())
+                .add_input("/".into(), "search".into())
+                .build()
+        } else {
+            builder.add_input(format!("{R_KEY}"), "raw".into()).build()
+        }
+    }
+
+    fn refresh_page_help(&mut self) {
+        let page_name = self
+            .thing
+            .as_ref()
+            .map(|t| format!("{NAME} ({})", t.get_name()));
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            page_name,
+            self.show_raw,
+        )));
+    }
+
+    /// Switches between the summary tree and the raw inspect document, which is
+    /// only fetched the first time it is shown
+    async fn toggle_raw(&mut self) -> Result<()> {
+        if !self.show_raw
+            && !self.inspect_view.has_document()
+            && let Some(thing) = &self.thing
+        {
+            let document = thing.inspect(&self.docker).await?;
+            self.inspect_view.set_document(document);
+        }
+        self.show_raw = !self.show_raw;
+        self.refresh_page_help();
+        Ok(())
     }
 
     fn down(&mut self, amount: u16) {
@@ -88,7 +135,21 @@ impl DescribeContainer {
 #[async_trait::async_trait]
 impl Page for DescribeContainer {
     async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        if self.show_raw {
+            if let MessageResponse::Consumed = self.inspect_view.update(message)? {
+                return Ok(MessageResponse::Consumed);
+            }
+            if message == F_KEY {
+                self.inspect_view.toggle_format();
+                return Ok(MessageResponse::Consumed);
+            }
+        }
+
         let res = match message {
+            R_KEY => {
+                self.toggle_raw().await?;
+                MessageResponse::Consumed
+            }
             UP_KEY | K_KEY => {
                 self.up(1);
                 MessageResponse::Consumed
@@ -136,14 +197,12 @@ impl Page for DescribeContainer {
                 bail!("no docker container")
             }
         };
-        self.thing_summary = Some(thing.describe()?);
-        let page_name = format!("{NAME} ({})", thing.get_name());
-        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
-            self.config.clone(),
-            Some(page_name),
-        )));
+        self.thing_summary = Some(thing.describe(&self.docker).await?);
         self.thing = Some(thing);
         self.cx = Some(cx);
+        self.show_raw = false;
+        self.inspect_view = InspectView::new(self.config.clone());
+        self.refresh_page_help();
 
         Ok(())
     }
@@ -158,11 +217,15 @@ impl Close for DescribeContainer {}
 
 fn section_to_tree_item<'a>(
     state: &mut TreeState<Uuid>,
+    path: &[Uuid],
     section: &'a DescribeSection,
     section_style: &Style,
     key_style: &Style,
 ) -> TreeItem<'a, Uuid> {
-    let items: Vec<TreeItem<Uuid>> = section
+    let mut path = path.to_vec();
+    path.push(section.id);
+
+    let mut items: Vec<TreeItem<Uuid>> = section
         .items
         .iter()
         .map(|item| {
@@ -174,6 +237,12 @@ fn section_to_tree_item<'a>(
             TreeItem::new_leaf(item.id, line)
         })
         .collect();
+    items.extend(
+        section
+            .sections
+            .iter()
+            .map(|s| section_to_tree_item(state, &path, s, section_style, key_style)),
+    );
 
     let item = TreeItem::new(
         section.id,
@@ -181,12 +250,17 @@ fn section_to_tree_item<'a>(
         items,
     )
     .expect("all items should be unique");
-    state.open(vec![section.id]);
+    state.open(path);
     item
 }
 
 impl Component for DescribeContainer {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.show_raw {
+            self.inspect_view.draw(f, area);
+            return;
+        }
+
         self.height = area.height.saturating_sub(1);
         if self.thing_summary.is_none() {
             return;
@@ -197,7 +271,13 @@ impl Component for DescribeContainer {
             let tree = summary
                 .iter()
                 .map(|section| {
-                    section_to_tree_item(&mut self.tree_state, section, &section_style, &key_style)
+                    section_to_tree_item(
+                        &mut self.tree_state,
+                        &[],
+                        section,
+                        &section_style,
+                        &key_style,
+                    )
                 })
                 .collect_vec();
 
@@ -0,0 +1,464 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Context, Report, Result, eyre};
+use futures::StreamExt;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Row, Table, TableState},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+
+use crate::{
+    components::{
+        form_modal::{self, FormField, FormModal, FormValues},
+        help::{PageHelp, PageHelpBuilder},
+    },
+    config::Config,
+    context::AppContext,
+    docker::{
+        events::{DockerEvent, EVENT_TYPES, EventFilter, EventResource},
+        util::validate_labels,
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, ModalComponent, Page},
+};
+
+const NAME: &str = "Events";
+
+/// Number of events retained, after which the oldest are dropped
+const MAX_EVENTS: usize = 1000;
+
+/// Option of the type filter which shows events of every type
+const ALL_TYPES: &str = "all";
+
+const ESC_KEY: Key = Key::Esc;
+const ENTER_KEY: Key = Key::Enter;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const F_KEY: Key = Key::Char('f');
+const SPACE_BAR: Key = Key::Char(' ');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModalTypes {
+    Filter,
+}
+
+/// Page showing a live feed of events from the daemon, eg containers starting
+/// and dying, with the newest at the bottom.
+///
+/// Events keep arriving while the feed is paused, and are added to it once it
+/// is resumed.  Filters are applied to the events already received, so
+/// changing them doesn't lose any.
+#[derive(Debug)]
+pub struct Events {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    events: VecDeque<DockerEvent>,
+    /// Events received by the streamer which are yet to be added to the feed
+    pending: Arc<Mutex<VecDeque<DockerEvent>>>,
+    /// Why the stream of events ended, reported on the next update
+    error: Arc<Mutex<Option<Report>>>,
+    event_streamer_handle: Option<JoinHandle<()>>,
+    paused: bool,
+    filter: EventFilter,
+    form_modal: Option<FormModal<ModalTypes>>,
+    list_state: TableState,
+    table_height: u16,
+}
+
+impl Events {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), false, false);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            events: VecDeque::with_capacity(MAX_EVENTS),
+            pending: Arc::new(Mutex::new(VecDeque::new())),
+            error: Arc::new(Mutex::new(None)),
+            event_streamer_handle: None,
+            paused: false,
+            filter: EventFilter::default(),
+            form_modal: None,
+            list_state: TableState::default(),
+            table_height: 0,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, paused: bool, filtered: bool) -> PageHelp {
+        let mut builder = PageHelpBuilder::new(NAME.into(), config)
+            .add_input(format!("{ENTER_KEY}"), "show".into())
+            .add_input(
+                format!("{SPACE_BAR}"),
+                if paused { "resume" } else { "pause" }.into(),
+            )
+            .add_input(format!("{F_KEY}"), "filter".into());
+        if filtered {
+            builder = builder.add_input(format!("{ESC_KEY}"), "clear filter".into());
+        }
+        builder
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .build()
+    }
+
+    fn refresh_page_help(&mut self) {
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            self.paused,
+            !self.filter.is_empty(),
+        )));
+    }
+
+    fn abort(&mut self) {
+        if let Some(handle) = &self.event_streamer_handle {
+            handle.abort()
+        }
+        self.event_streamer_handle = None;
+    }
+
+    fn start_event_stream(&mut self) {
+        let mut event_stream = DockerEvent::get_event_stream(&self.docker);
+        let tx = self.tx.clone();
+        let pending = self.pending.clone();
+        let error = self.error.clone();
+
+        self.event_streamer_handle = Some(tokio::spawn(async move {
+            while let Some(res) = event_stream.next().await {
+                match res {
+                    Ok(event) => {
+                        {
+                            let mut pending = pending.lock().unwrap();
+                            if pending.len() >= MAX_EVENTS {
+                                pending.pop_front();
+                            }
+                            pending.push_back(event);
+                        }
+                        let _ = tx.send(Message::Tick).await;
+                    }
+                    Err(err) => {
+                        *error.lock().unwrap() = Some(err);
+                        break;
+                    }
+                }
+            }
+
+            // The daemon only ends the stream where it is shutting down
+            error
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| eyre!("the daemon stopped sending events"));
+            let _ = tx.send(Message::Tick).await;
+        }));
+    }
+
+    /// Adds any events received since the last update to the feed, following
+    /// the newest event where it was already selected
+    fn add_pending(&mut self) {
+        if self.paused {
+            return;
+        }
+        let pending: Vec<DockerEvent> = self.pending.lock().unwrap().drain(..).collect();
+        if pending.is_empty() {
+            return;
+        }
+
+        let len = self.visible().len();
+        let following = self.list_state.selected().is_none_or(|idx| idx + 1 >= len);
+
+        let mut dropped = 0;
+        for event in pending {
+            if self.events.len() >= MAX_EVENTS
+                && let Some(oldest) = self.events.pop_front()
+                && oldest.matches(&self.filter)
+            {
+                dropped += 1;
+            }
+            self.events.push_back(event);
+        }
+
+        let len = self.visible().len();
+        let idx = if following {
+            len.saturating_sub(1)
+        } else {
+            self.list_state
+                .selected()
+                .unwrap_or_default()
+                .saturating_sub(dropped)
+        };
+        self.list_state.select(Some(idx));
+    }
+
+    /// The events matching the filter, oldest first
+    fn visible(&self) -> Vec<&DockerEvent> {
+        self.events
+            .iter()
+            .filter(|e| e.matches(&self.filter))
+            .collect()
+    }
+
+    fn get_event(&self) -> Option<&DockerEvent> {
+        self.list_state
+            .selected()
+            .and_then(|idx| self.visible().get(idx).copied())
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.add_pending();
+        self.refresh_page_help();
+    }
+
+    fn open_filter_modal(&mut self) {
+        let types = std::iter::once(ALL_TYPES)
+            .chain(EVENT_TYPES)
+            .map(String::from)
+            .collect();
+        let fields = vec![
+            FormField::choice("type", "Type", types)
+                .with_value(self.filter.typ.as_deref().unwrap_or(ALL_TYPES)),
+            FormField::text("labels", "Labels (KEY or KEY=value, ...)")
+                .with_value(self.filter.labels.as_deref().unwrap_or_default())
+                .with_validator(validate_labels),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Filter".into(), ModalTypes::Filter, self.config.clone());
+        modal.initialise("Show only events matching".into(), fields);
+        self.form_modal = Some(modal);
+    }
+
+    fn set_filter(&mut self, filter: EventFilter) {
+        self.filter = filter;
+        self.list_state
+            .select(Some(self.visible().len().saturating_sub(1)));
+        self.refresh_page_help();
+    }
+
+    fn filter_from_values(values: FormValues) -> EventFilter {
+        EventFilter {
+            typ: values.text_opt("type").filter(|t| t != ALL_TYPES),
+            labels: values.text_opt("labels"),
+        }
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let len = self.visible().len();
+        let idx = self.list_state.selected().unwrap_or_default() + amount;
+        self.list_state.select(Some(idx.min(len.saturating_sub(1))));
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(idx.saturating_sub(amount)));
+    }
+
+    /// Shows the resource affected by the selected event on its own page
+    async fn show_resource(&self) -> Result<MessageResponse> {
+        let Some(event) = self.get_event() else {
+            return Ok(MessageResponse::NotConsumed);
+        };
+        let resource = event
+            .resource(&self.docker)
+            .await
+            .with_context(|| format!("unable to show {} {}", event.typ, event.name))?;
+
+        let transition = match resource {
+            EventResource::Container(container) => Transition::ToContainerPage(AppContext {
+                docker_container: Some(container),
+                ..Default::default()
+            }),
+            EventResource::Image(image) => Transition::ToImagePage(AppContext {
+                docker_image: Some(image),
+                ..Default::default()
+            }),
+            EventResource::Volume(volume) => Transition::ToVolumePage(AppContext {
+                docker_volume: Some(volume),
+                ..Default::default()
+            }),
+            EventResource::Network(network) => Transition::ToNetworkPage(AppContext {
+                docker_network: Some(network),
+                ..Default::default()
+            }),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(MessageResponse::Consumed)
+    }
+
+    fn event_style(&self, event: &DockerEvent) -> Style {
+        let theme = &self.config.theme;
+        match event.action.as_str() {
+            "die" | "kill" | "oom" | "destroy" | "health_status: unhealthy" => {
+                Style::new().fg(theme.negative_highlight())
+            }
+            "start" | "health_status: healthy" => Style::new().fg(theme.positive_highlight()),
+            "pause" => Style::new().fg(theme.paused_highlight()),
+            _ => Style::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for Events {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        self.add_pending();
+
+        if message == Key::Null
+            && let Some(err) = self.error.lock().unwrap().take()
+        {
+            return Err(err);
+        }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            if let Some(values) = values {
+                self.set_filter(Self::filter_from_values(values));
+            }
+            return res;
+        }
+
+        let res = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.list_state.select(Some(0));
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state
+                    .select(Some(self.visible().len().saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            SPACE_BAR => {
+                self.toggle_pause();
+                MessageResponse::Consumed
+            }
+            F_KEY => {
+                self.open_filter_modal();
+                MessageResponse::Consumed
+            }
+            ESC_KEY if !self.filter.is_empty() => {
+                self.set_filter(EventFilter::default());
+                MessageResponse::Consumed
+            }
+            ENTER_KEY => self.show_resource().await?,
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, _cx: AppContext) -> Result<()> {
+        self.abort();
+        self.events.clear();
+        self.pending.lock().unwrap().clear();
+        self.error = Arc::new(Mutex::new(None));
+        self.list_state = TableState::default();
+        self.start_event_stream();
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Events {
+    async fn close(&mut self) -> Result<()> {
+        self.abort();
+        self.error = Arc::new(Mutex::new(None));
+        Ok(())
+    }
+}
+
+impl Component for Events {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [summary_area, table_area] = vertical![==2, >=0].areas(area);
+        self.table_height = table_area.height.saturating_sub(1);
+
+        let visible = self.visible();
+        let mut summary = format!("{} events", visible.len());
+        if let Some(typ) = &self.filter.typ {
+            summary.push_str(&format!("; type {typ}"));
+        }
+        if let Some(labels) = &self.filter.labels {
+            summary.push_str(&format!("; labels {labels}"));
+        }
+        let mut summary =
+            vec![Line::from(summary).style(Style::new().fg(self.config.theme.title()))];
+        if self.paused {
+            let pending = self.pending.lock().unwrap().len();
+            summary.push(
+                Line::from(format!("Paused, {pending} new events"))
+                    .style(Style::new().fg(self.config.theme.paused_highlight())),
+            );
+        }
+
+        let rows = visible
+            .iter()
+            .map(|e| {
+                Row::new(vec![
+                    e.time(),
+                    e.typ.clone(),
+                    e.action.clone(),
+                    e.name.clone(),
+                    e.detail(),
+                ])
+                .style(self.event_style(e))
+            })
+            .collect::<Vec<_>>();
+        let columns = Row::new(vec!["Time", "Type", "Action", "Name", "Detail"]);
+        let table = Table::new(rows, constraints![==20%, ==10%, ==20%, ==30%, ==20%])
+            .header(columns.style(Style::new().bold()))
+            .row_highlight_style(Style::new().reversed());
+
+        f.render_widget(Paragraph::new(summary), summary_area);
+        f.render_stateful_widget(table, table_area, &mut self.list_state);
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+    }
+}
@@ -0,0 +1,596 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Context, Result, bail};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
+
+use crate::{
+    components::{
+        alert_modal::{self, AlertModal},
+        form_modal::{self, FormField, FormModal, FormValues},
+        help::{PageHelp, PageHelpBuilder},
+    },
+    config::Config,
+    context::AppContext,
+    docker::{
+        container::DockerContainer,
+        files::{
+            self, ContainerFile, FileKind, FilePreview, expand_local_path, validate_local_path,
+        },
+        traits::Describe,
+        util::format_bytes,
+        volume::DockerVolume,
+        volume_browser,
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, ModalComponent, Page},
+};
+
+const NAME: &str = "Files";
+
+const ESC_KEY: Key = Key::Esc;
+const ENTER_KEY: Key = Key::Enter;
+const BACKSPACE_KEY: Key = Key::Backspace;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const C_KEY: Key = Key::Char('c');
+const U_KEY: Key = Key::Char('u');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModalTypes {
+    CopyOut,
+    Upload,
+    Alert,
+}
+
+/// The result of work run in the background, so that reading large directories
+/// or copying large files doesn't block the UI
+#[derive(Debug)]
+enum JobOutput {
+    Listing {
+        dir: String,
+        files: Vec<ContainerFile>,
+        /// Path of the file to select once listed, eg the directory just left
+        select: Option<String>,
+    },
+    Preview {
+        path: String,
+        preview: FilePreview,
+    },
+    CopiedOut(String),
+    Uploaded {
+        dir: String,
+        message: String,
+    },
+}
+
+#[derive(Debug)]
+struct Job {
+    description: String,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Debug)]
+struct Preview {
+    path: String,
+    preview: FilePreview,
+    scroll: u16,
+}
+
+/// Page for browsing the filesystem of a container, and copying files and
+/// directories between the container and the local machine.
+///
+/// Volumes are browsed read-only through a helper container, which is removed
+/// when the page is closed.
+#[derive(Debug)]
+pub struct Files {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    container: Option<DockerContainer>,
+    /// The volume being browsed, in which case the container is its helper
+    volume: Option<DockerVolume>,
+    next: Option<Transition>,
+    root: String,
+    cwd: String,
+    files: Vec<ContainerFile>,
+    list_state: TableState,
+    table_height: u16,
+    preview: Option<Preview>,
+    job: Option<Job>,
+    job_output: Arc<Mutex<Option<Result<JobOutput>>>>,
+    transfer_modal: Option<FormModal<ModalTypes>>,
+    alert_modal: AlertModal<ModalTypes>,
+}
+
+impl Files {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None, false);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            container: None,
+            volume: None,
+            next: None,
+            root: "/".into(),
+            cwd: "/".into(),
+            files: vec![],
+            list_state: TableState::default(),
+            table_height: 0,
+            preview: None,
+            job: None,
+            job_output: Arc::new(Mutex::new(None)),
+            transfer_modal: None,
+            alert_modal: AlertModal::new(NAME.into(), ModalTypes::Alert),
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: Option<String>, read_only: bool) -> PageHelp {
+        let mut page_help = PageHelpBuilder::new(name.unwrap_or(NAME.into()), config)
+            .add_input(format!("{ESC_KEY}"), "back".into())
+            .add_input(format!("{ENTER_KEY}"), "open".into())
+            .add_input(format!("{BACKSPACE_KEY}"), "parent".into())
+            .add_input(format!("{C_KEY}"), "copy out".into());
+        if !read_only {
+            page_help = page_help.add_input(format!("{U_KEY}"), "upload".into());
+        }
+        page_help
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .build()
+    }
+
+    fn get_container(&self) -> Result<DockerContainer> {
+        match &self.container {
+            Some(c) => Ok(c.clone()),
+            None => bail!("no container selected"),
+        }
+    }
+
+    fn get_file(&self) -> Option<&ContainerFile> {
+        self.list_state
+            .selected()
+            .and_then(|idx| self.files.get(idx))
+    }
+
+    /// Runs the future in the background, replacing any job already running
+    fn spawn_job<F>(&mut self, description: String, job: F)
+    where
+        F: Future<Output = Result<JobOutput>> + Send + 'static,
+    {
+        self.cancel_job();
+
+        let output = self.job_output.clone();
+        let tx = self.tx.clone();
+        let handle = tokio::spawn(async move {
+            let result = job.await;
+            *output.lock().unwrap() = Some(result);
+            let _ = tx.send(Message::Tick).await;
+        });
+
+        self.job = Some(Job {
+            description,
+            handle,
+        });
+    }
+
+    fn cancel_job(&mut self) {
+        if let Some(job) = self.job.take() {
+            job.handle.abort();
+        }
+        *self.job_output.lock().unwrap() = None;
+    }
+
+    fn list(&mut self, dir: String, select: Option<String>) -> Result<()> {
+        let container = self.get_container()?;
+        let docker = self.docker.clone();
+
+        self.spawn_job(format!("Reading {dir}"), async move {
+            let files = ContainerFile::list(&docker, &container, &dir).await?;
+            Ok(JobOutput::Listing { dir, files, select })
+        });
+        Ok(())
+    }
+
+    fn preview(&mut self, path: String) -> Result<()> {
+        let container = self.get_container()?;
+        let docker = self.docker.clone();
+
+        self.spawn_job(format!("Reading {path}"), async move {
+            let preview = ContainerFile::preview(&docker, &container, &path).await?;
+            Ok(JobOutput::Preview { path, preview })
+        });
+        Ok(())
+    }
+
+    fn open_selected(&mut self) -> Result<()> {
+        let Some(file) = self.get_file().cloned() else {
+            return Ok(());
+        };
+
+        match file.kind {
+            FileKind::Directory => self.list(file.path, None),
+            FileKind::File => self.preview(file.path),
+            FileKind::Symlink(target) => {
+                // The target may be either a file or directory, so try to list it first
+                let target = files::resolve_link(&file.path, &target);
+                let container = self.get_container()?;
+                let docker = self.docker.clone();
+
+                self.spawn_job(format!("Reading {target}"), async move {
+                    match ContainerFile::list(&docker, &container, &target).await {
+                        Ok(files) => Ok(JobOutput::Listing {
+                            dir: target,
+                            files,
+                            select: None,
+                        }),
+                        Err(_) => {
+                            let preview =
+                                ContainerFile::preview(&docker, &container, &target).await?;
+                            Ok(JobOutput::Preview {
+                                path: target,
+                                preview,
+                            })
+                        }
+                    }
+                });
+                Ok(())
+            }
+            FileKind::Other => Ok(()),
+        }
+    }
+
+    fn open_copy_out_modal(&mut self) {
+        let Some(file) = self.get_file() else {
+            return;
+        };
+        let message = format!("Copy {} to a local directory", file.path);
+        let fields = vec![FormField::text("dest", "Local directory").with_value(".")];
+
+        let mut modal = FormModal::new("Copy Out".into(), ModalTypes::CopyOut, self.config.clone());
+        modal.initialise(message, fields);
+        self.transfer_modal = Some(modal);
+    }
+
+    fn open_upload_modal(&mut self) {
+        let message = "Copy a local file or directory into the container".to_string();
+        let fields = vec![
+            FormField::text("source", "Local path").with_validator(validate_local_path),
+            FormField::text("dir", "Container directory").with_value(&self.cwd),
+        ];
+
+        let mut modal = FormModal::new("Upload".into(), ModalTypes::Upload, self.config.clone());
+        modal.initialise(message, fields);
+        self.transfer_modal = Some(modal);
+    }
+
+    fn submit_transfer(&mut self, modal_type: ModalTypes, values: FormValues) -> Result<()> {
+        let container = self.get_container()?;
+        let docker = self.docker.clone();
+
+        match modal_type {
+            ModalTypes::CopyOut => {
+                let Some(file) = self.get_file().cloned() else {
+                    return Ok(());
+                };
+                let dest = expand_local_path(&values.text_opt("dest").unwrap_or(".".into()));
+
+                self.spawn_job(format!("Copying {}", file.path), async move {
+                    ContainerFile::download(&docker, &container, &file.path, &dest).await?;
+                    Ok(JobOutput::CopiedOut(format!(
+                        "Copied {} to {}",
+                        file.path,
+                        dest.display()
+                    )))
+                });
+            }
+            ModalTypes::Upload => {
+                let source = expand_local_path(&values.text("source"));
+                let dir = values.text_opt("dir").unwrap_or(self.cwd.clone());
+
+                self.spawn_job(format!("Uploading {}", source.display()), async move {
+                    ContainerFile::upload(&docker, &container, &source, &dir).await?;
+                    let message = format!("Copied {} to {dir}", source.display());
+                    Ok(JobOutput::Uploaded { dir, message })
+                });
+            }
+            ModalTypes::Alert => {}
+        }
+        Ok(())
+    }
+
+    /// Applies the output of a finished background job
+    fn handle_job_output(&mut self) -> Result<()> {
+        let Some(output) = self.job_output.lock().unwrap().take() else {
+            return Ok(());
+        };
+        self.job = None;
+
+        match output {
+            Ok(JobOutput::Listing { dir, files, select }) => {
+                self.cwd = dir;
+                self.files = files;
+                let idx = select
+                    .and_then(|s| self.files.iter().position(|f| f.path == s))
+                    .unwrap_or(0);
+                self.list_state.select(Some(idx));
+            }
+            Ok(JobOutput::Preview { path, preview }) => {
+                self.preview = Some(Preview {
+                    path,
+                    preview,
+                    scroll: 0,
+                });
+            }
+            Ok(JobOutput::CopiedOut(message)) => self.alert_modal.initialise(message),
+            Ok(JobOutput::Uploaded { dir, message }) => {
+                self.alert_modal.initialise(message);
+                if dir.trim_end_matches('/') == self.cwd.trim_end_matches('/') {
+                    let select = self.get_file().map(|f| f.path.clone());
+                    self.list(self.cwd.clone(), select)?;
+                }
+            }
+            Err(e) => self.alert_modal.initialise(format!("{e:#}")),
+        }
+        Ok(())
+    }
+
+    async fn back(&self) -> Result<()> {
+        let transition = match (&self.next, &self.volume) {
+            (Some(t), _) => t.clone(),
+            (None, Some(volume)) => Transition::ToVolumePage(AppContext {
+                docker_volume: Some(volume.clone()),
+                ..Default::default()
+            }),
+            (None, None) => Transition::ToContainerPage(AppContext {
+                docker_container: self.container.clone(),
+                ..Default::default()
+            }),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(())
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        if let Some(preview) = self.preview.as_mut() {
+            preview.scroll = preview.scroll.saturating_add(amount as u16);
+            return;
+        }
+        let last = self.files.len().saturating_sub(1);
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some((idx + amount).min(last)));
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        if let Some(preview) = self.preview.as_mut() {
+            preview.scroll = preview.scroll.saturating_sub(amount as u16);
+            return;
+        }
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(idx.saturating_sub(amount)));
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for Files {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        if message == Key::Null {
+            self.handle_job_output()?;
+        }
+
+        if let alert_modal::ModalState::Open(_) = self.alert_modal.state {
+            return self.alert_modal.update(message).await;
+        }
+
+        if let Some(m) = self.transfer_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let modal_type = m.discriminator;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.transfer_modal = None;
+            }
+            if let Some(values) = values {
+                self.submit_transfer(modal_type, values)?;
+            }
+            return res;
+        }
+
+        let result = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            ESC_KEY => {
+                if self.job.is_some() {
+                    self.cancel_job();
+                } else if self.preview.is_some() {
+                    self.preview = None;
+                } else {
+                    self.back().await?;
+                }
+                MessageResponse::Consumed
+            }
+            // Everything below acts on the directory listing, so is ignored while
+            // a file is being previewed
+            _ if self.preview.is_some() => MessageResponse::NotConsumed,
+            G_KEY => {
+                self.list_state.select_first();
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state
+                    .select(Some(self.files.len().saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            ENTER_KEY => {
+                self.open_selected()?;
+                MessageResponse::Consumed
+            }
+            BACKSPACE_KEY => {
+                if self.cwd != self.root {
+                    self.list(files::parent(&self.cwd), Some(self.cwd.clone()))?;
+                }
+                MessageResponse::Consumed
+            }
+            C_KEY => {
+                self.open_copy_out_modal();
+                MessageResponse::Consumed
+            }
+            // Volumes are mounted read-only into their helper container
+            U_KEY if self.volume.is_some() => MessageResponse::NotConsumed,
+            U_KEY => {
+                self.open_upload_modal();
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(result)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let (page_name, container) = match (cx.docker_container.clone(), &cx.docker_volume) {
+            (Some(container), _) => (format!("{NAME} ({})", container.get_name()), container),
+            (None, Some(volume)) => {
+                let helper = volume_browser::create_helper(&self.docker, volume, true).await?;
+                self.volume = Some(volume.clone());
+                self.root = volume_browser::MOUNT_PATH.into();
+                (format!("{NAME} (volume {})", volume.name), helper)
+            }
+            (None, None) => bail!("no docker container"),
+        };
+
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            Some(page_name),
+            self.volume.is_some(),
+        )));
+
+        self.next = cx.next();
+        self.container = Some(container);
+        self.preview = None;
+        self.list(self.root.clone(), None)
+            .context("unable to list container files")
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Files {
+    async fn close(&mut self) -> Result<()> {
+        self.cancel_job();
+        if self.volume.is_some()
+            && let Some(helper) = self.container.take()
+        {
+            volume_browser::remove_helper(&self.docker, &helper).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for Files {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [header_area, body_area] = vertical![==1, >=0].areas(area);
+        self.table_height = body_area.height.saturating_sub(2);
+
+        let header = match &self.job {
+            Some(job) => format!("{} ({}…)", self.cwd, job.description),
+            None => self.cwd.clone(),
+        };
+        f.render_widget(
+            Paragraph::new(header).style(Style::new().fg(self.config.theme.title()).bold()),
+            header_area,
+        );
+
+        if let Some(preview) = &self.preview {
+            let mut title = format!(" {} ", preview.path);
+            if preview.preview.truncated {
+                title = format!(
+                    "{title}(first {} only) ",
+                    format_bytes(files::PREVIEW_LIMIT).unwrap_or_default()
+                );
+            }
+            let paragraph = Paragraph::new(preview.preview.content.as_str())
+                .block(Block::bordered().title(Line::from(title)))
+                .scroll((preview.scroll, 0));
+            f.render_widget(paragraph, body_area);
+        } else {
+            let rows = self.files.iter().map(|file| {
+                let (name, style) = match &file.kind {
+                    FileKind::Directory => (
+                        format!("{}/", file.name),
+                        Style::new().fg(self.config.theme.positive_highlight()),
+                    ),
+                    FileKind::Symlink(target) => (
+                        format!("{} -> {target}", file.name),
+                        Style::new().fg(self.config.theme.paused_highlight()),
+                    ),
+                    _ => (file.name.clone(), Style::new()),
+                };
+                let size = if file.is_dir() {
+                    String::new()
+                } else {
+                    format_bytes(file.size).unwrap_or_default()
+                };
+
+                Row::new(vec![name, size, file.mode.clone(), file.modified.clone()]).style(style)
+            });
+
+            let columns = Row::new(vec!["Name", "Size", "Mode", "Modified"]);
+            let widths = constraints![>=20, ==12, ==12, ==20];
+            let table = Table::new(rows, widths)
+                .header(columns.style(Style::new().bold()))
+                .row_highlight_style(Style::new().reversed());
+
+            f.render_stateful_widget(table, body_area, &mut self.list_state);
+        }
+
+        if let Some(m) = self.transfer_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
+
+        if let alert_modal::ModalState::Open(_) = self.alert_modal.state {
+            self.alert_modal.draw(f, area)
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Result, bail};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    components::help::{PageHelp, PageHelpBuilder},
+    config::Config,
+    context::AppContext,
+    docker::{
+        history::{ImageLayer, cumulative_sizes},
+        image::DockerImage,
+        util::{format_age, format_bytes},
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, Page},
+};
+
+const NAME: &str = "History";
+
+const ESC_KEY: Key = Key::Esc;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+
+/// Width of the cumulative size bars
+const BAR_WIDTH: usize = 20;
+
+/// Height of the panel showing the selected layer's full instruction
+const DETAIL_HEIGHT: u16 = 5;
+
+/// Page listing the layers of an image along with the instruction which
+/// created each, to show which steps contribute most to the image's size
+#[derive(Debug)]
+pub struct History {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    image: Option<DockerImage>,
+    next: Option<Transition>,
+    layers: Vec<ImageLayer>,
+    cumulative: Vec<i64>,
+    list_state: TableState,
+    table_height: u16,
+}
+
+impl History {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            image: None,
+            next: None,
+            layers: vec![],
+            cumulative: vec![],
+            list_state: TableState::default(),
+            table_height: 0,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
+        PageHelpBuilder::new(name.unwrap_or(NAME.into()), config)
+            .add_input(format!("{ESC_KEY}"), "back".into())
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .build()
+    }
+
+    fn total_size(&self) -> i64 {
+        self.cumulative.first().copied().unwrap_or_default()
+    }
+
+    async fn back(&self) -> Result<()> {
+        let transition = match &self.next {
+            Some(t) => t.clone(),
+            None => Transition::ToImagePage(AppContext {
+                docker_image: self.image.clone(),
+                ..Default::default()
+            }),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(())
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default() + amount;
+        self.list_state
+            .select(Some(idx.min(self.layers.len().saturating_sub(1))));
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(idx.saturating_sub(amount)));
+    }
+
+    /// A bar showing the size of the layers beneath this one, followed by the
+    /// size this layer adds, as a proportion of the whole image
+    fn size_bar(&self, cumulative: i64, size: i64) -> Line<'static> {
+        let total = self.total_size();
+        if total <= 0 {
+            return Line::default();
+        }
+        let width = |bytes: i64| (bytes as f64 / total as f64 * BAR_WIDTH as f64).round() as usize;
+        let filled = width(cumulative).min(BAR_WIDTH);
+        let below = width(cumulative - size.max(0)).min(filled);
+
+        Line::from(vec![
+            Span::from("█".repeat(below)).fg(self.config.theme.help()),
+            Span::from("█".repeat(filled - below)).fg(self.config.theme.paused_highlight()),
+            Span::from("░".repeat(BAR_WIDTH - filled)),
+            Span::from(format!(
+                " {:>3.0}%",
+                cumulative as f64 / total as f64 * 100.0
+            )),
+        ])
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for History {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        let res = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.list_state.select(Some(0));
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state
+                    .select(Some(self.layers.len().saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            ESC_KEY => {
+                self.back().await?;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let Some(image) = cx.docker_image.clone() else {
+            bail!("no docker image")
+        };
+
+        let page_name = format!("{NAME} ({})", image.get_full_name());
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            Some(page_name),
+        )));
+
+        self.layers = ImageLayer::list(&self.docker, &image).await?;
+        self.cumulative = cumulative_sizes(&self.layers);
+        self.list_state = TableState::default();
+        self.list_state.select(Some(0));
+        self.next = cx.next();
+        self.image = Some(image);
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for History {}
+
+impl Component for History {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [summary_area, table_area, detail_area] =
+            vertical![==2, >=0, ==DETAIL_HEIGHT].areas(area);
+        self.table_height = table_area.height.saturating_sub(1);
+
+        let largest = self.layers.iter().max_by_key(|l| l.size);
+        let mut summary = vec![Span::from(format!(
+            "{} layers, {} in total",
+            self.layers.len(),
+            format_bytes(self.total_size()).unwrap_or_default()
+        ))];
+        if let Some(largest) = largest.filter(|l| l.size > 0) {
+            summary.push(Span::from(format!(
+                "; largest is {} from {}",
+                format_bytes(largest.size).unwrap_or_default(),
+                largest.instruction()
+            )));
+        }
+        f.render_widget(
+            Paragraph::new(Line::from(summary)).style(Style::new().fg(self.config.theme.title())),
+            summary_area,
+        );
+
+        let rows = self
+            .layers
+            .iter()
+            .zip(&self.cumulative)
+            .map(|(layer, cumulative)| {
+                let id_style = if layer.is_missing() {
+                    Style::new().fg(self.config.theme.help())
+                } else {
+                    Style::new()
+                };
+                Row::new(vec![
+                    Cell::from(layer.id.clone()).style(id_style),
+                    Cell::from(format_age(layer.created)),
+                    Cell::from(format_bytes(layer.size).unwrap_or_default()),
+                    Cell::from(self.size_bar(*cumulative, layer.size)),
+                    Cell::from(layer.instruction()),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let columns = Row::new(vec!["Layer", "Created", "Size", "Cumulative", "Created By"]);
+        let widths = constraints![==12, ==16, ==12, ==(BAR_WIDTH as u16 + 5), >=20];
+        let table = Table::new(rows, widths)
+            .header(columns.style(Style::new().bold()))
+            .row_highlight_style(Style::new().reversed());
+        f.render_stateful_widget(table, table_area, &mut self.list_state);
+
+        if let Some(layer) = self
+            .list_state
+            .selected()
+            .and_then(|idx| self.layers.get(idx))
+        {
+            let mut lines = vec![Line::from(layer.instruction())];
+            if !layer.tags.is_empty() {
+                lines.push(Line::from(format!("Tags: {}", layer.tags.join(", "))));
+            }
+            if !layer.comment.is_empty() {
+                lines.push(Line::from(format!("Comment: {}", layer.comment)));
+            }
+            let detail = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::new().borders(Borders::TOP));
+            f.render_widget(detail, detail_area);
+        }
+    }
+}
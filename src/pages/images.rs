@@ -15,11 +15,26 @@ use crate::{
     callbacks::delete_image::DeleteImage,
     components::{
         boolean_modal::{BooleanModal, ModalState},
+        form_modal::{self, FormField, FormModal, FormValues},
         help::{PageHelp, PageHelpBuilder},
+        select_modal::{self, SelectModal},
     },
     config::Config,
     context::AppContext,
-    docker::image::DockerImage,
+    docker::{
+        commit::{DEFAULT_TAG, validate_repository, validate_tag},
+        container::DockerContainer,
+        files::{expand_local_path, validate_local_path},
+        image::DockerImage,
+        network::DockerNetwork,
+        registry::validate_reference,
+        run::{
+            ContainerRunConfig, DEFAULT_NETWORK, RESTART_POLICIES, validate_command, validate_env,
+            validate_ports, validate_volumes,
+        },
+        transfer::{Transfers, validate_output_path},
+        usage::{ContainerUsage, format_used_by, used_by_options},
+    },
     events::{Key, Message, Transition, message::MessageResponse},
     sorting::{
         ImageSortField, SortOrder, SortState, sort_images_by_created, sort_images_by_id,
@@ -42,6 +57,14 @@ const D_KEY: Key = Key::Char('d');
 const G_KEY: Key = Key::Char('g');
 const SHIFT_G_KEY: Key = Key::Char('G');
 const ALT_D_KEY: Key = Key::Alt('d');
+const R_KEY: Key = Key::Char('r');
+const S_KEY: Key = Key::Char('s');
+const L_KEY: Key = Key::Char('l');
+const P_KEY: Key = Key::Char('p');
+const SHIFT_P_KEY: Key = Key::Char('P');
+const T_KEY: Key = Key::Char('t');
+const H_KEY: Key = Key::Char('h');
+const U_KEY: Key = Key::Char('u');
 
 // Sort keys
 const SHIFT_N_KEY: Key = Key::Char('N');
@@ -55,17 +78,28 @@ type ImageSortState = SortState<ImageSortField>;
 enum ModalTypes {
     DeleteImage,
     ForceDeleteImage,
+    Run,
+    Save,
+    Load,
+    Pull,
+    Tag,
+    UsedBy,
 }
 
 #[derive(Debug)]
 pub struct Images {
     pub name: String,
+    config: Arc<Config>,
     tx: Sender<Message<Key, Transition>>,
     page_help: Arc<Mutex<PageHelp>>,
     docker: Docker,
     images: Vec<DockerImage>,
     list_state: TableState,
     modal: Option<BooleanModal<ModalTypes>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    used_by_modal: Option<SelectModal<ModalTypes, DockerContainer>>,
+    transfers: Transfers,
+    usage: ContainerUsage,
     show_dangling: bool,
     sort_state: ImageSortState,
     table_height: u16,
@@ -81,6 +115,47 @@ impl Page for Images {
             return Ok(res);
         }
 
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let modal_type = m.discriminator;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            match (modal_type, values) {
+                (ModalTypes::Run, Some(values)) => self.run_image(values).await?,
+                (ModalTypes::Save, Some(values)) => self.save_image(values)?,
+                (ModalTypes::Load, Some(values)) => self.load_images(values),
+                (ModalTypes::Pull, Some(values)) => self.pull_image(values).await?,
+                (ModalTypes::Tag, Some(values)) => self.tag_image(values).await?,
+                _ => {}
+            }
+            return res;
+        }
+
+        if let Some(m) = self.used_by_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let container = m.take_selected();
+            if let select_modal::ModalState::Closed = m.state {
+                self.used_by_modal = None;
+            }
+            if let Some(container) = container {
+                self.tx
+                    .send(Message::Transition(Transition::ToContainerPage(
+                        AppContext {
+                            docker_container: Some(container),
+                            ..Default::default()
+                        },
+                    )))
+                    .await?;
+            }
+            return res;
+        }
+
         let result = match message {
             UP_KEY | K_KEY => {
                 self.scroll_up(1);
@@ -134,6 +209,44 @@ impl Page for Images {
                 self.show_dangling = !self.show_dangling;
                 MessageResponse::Consumed
             }
+            R_KEY => {
+                self.open_run_modal().await?;
+                MessageResponse::Consumed
+            }
+            S_KEY => {
+                self.open_save_modal()?;
+                MessageResponse::Consumed
+            }
+            L_KEY => {
+                self.open_load_modal();
+                MessageResponse::Consumed
+            }
+            P_KEY => {
+                self.open_pull_modal();
+                MessageResponse::Consumed
+            }
+            SHIFT_P_KEY => {
+                self.push_image().await?;
+                MessageResponse::Consumed
+            }
+            T_KEY => {
+                self.open_tag_modal()?;
+                MessageResponse::Consumed
+            }
+            H_KEY => {
+                let image = self.get_image()?.clone();
+                self.tx
+                    .send(Message::Transition(Transition::ToHistoryPage(AppContext {
+                        docker_image: Some(image),
+                        ..Default::default()
+                    })))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            U_KEY => {
+                self.open_used_by_modal()?;
+                MessageResponse::Consumed
+            }
             D_KEY => {
                 self.tx
                     .send(Message::Transition(Transition::ToDescribeContainerPage(
@@ -151,26 +264,17 @@ impl Page for Images {
         self.list_state = TableState::default();
         self.list_state.select(Some(0));
 
+        self.usage.invalidate();
         self.refresh().await.context("unable to refresh images")?;
 
         // If a context has been passed in, choose that item in list
         // this is to allow logs, attach etc to appear to revert to previous
         // state
         // I'm sure there is a more sensible way of doing this...
-        let image_id: String;
         if let Some(image) = cx.docker_image {
-            image_id = image.id;
+            self.select_image(&image.id, Some(image.get_full_name()));
         } else if let Some(thing) = cx.describable {
-            image_id = thing.get_id();
-        } else {
-            return Ok(());
-        }
-
-        for (idx, c) in self.images.iter().enumerate() {
-            if c.id == image_id {
-                self.list_state.select(Some(idx));
-                break;
-            }
+            self.select_image(&thing.get_id(), None);
         }
 
         Ok(())
@@ -185,23 +289,41 @@ impl Page for Images {
 impl Close for Images {}
 
 impl Images {
-    pub fn new(docker: Docker, tx: Sender<Message<Key, Transition>>, config: Arc<Config>) -> Self {
+    pub fn new(
+        docker: Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+        transfers: Transfers,
+    ) -> Self {
         let page_help = PageHelpBuilder::new(NAME.to_string(), config.clone())
             .add_input(format!("{CTRL_D_KEY}"), "delete".to_string())
             .add_input(format!("{ALT_D_KEY}"), "dangling".to_string())
             .add_input(format!("{G_KEY}"), "top".to_string())
             .add_input(format!("{SHIFT_G_KEY}"), "bottom".to_string())
             .add_input(format!("{D_KEY}"), "describe".to_string())
+            .add_input(format!("{R_KEY}"), "run".to_string())
+            .add_input(format!("{S_KEY}"), "save".to_string())
+            .add_input(format!("{L_KEY}"), "load".to_string())
+            .add_input(format!("{P_KEY}"), "pull".to_string())
+            .add_input(format!("{SHIFT_P_KEY}"), "push".to_string())
+            .add_input(format!("{T_KEY}"), "tag".to_string())
+            .add_input(format!("{H_KEY}"), "history".to_string())
+            .add_input(format!("{U_KEY}"), "used by".to_string())
             .build();
 
         Self {
             name: String::from(NAME),
+            config,
             tx,
             page_help: Arc::new(Mutex::new(page_help)),
             docker,
             images: vec![],
             list_state: TableState::default(),
             modal: None,
+            form_modal: None,
+            used_by_modal: None,
+            transfers,
+            usage: ContainerUsage::default(),
             show_dangling: false,
             sort_state: ImageSortState::new(ImageSortField::Name),
             table_height: 0,
@@ -212,6 +334,7 @@ impl Images {
         self.images = DockerImage::list(&self.docker, self.show_dangling)
             .await
             .context("unable to retrieve list of images")?;
+        self.usage.refresh(&self.docker).await?;
 
         self.sort_images();
         Ok(())
@@ -290,6 +413,19 @@ impl Images {
         }
     }
 
+    /// Selects an image by id; images are listed once per tag, so the entry
+    /// with the given name is preferred
+    fn select_image(&mut self, image_id: &str, image_name: Option<String>) {
+        let idx = self
+            .images
+            .iter()
+            .position(|i| i.id == image_id && Some(i.get_full_name()) == image_name)
+            .or_else(|| self.images.iter().position(|i| i.id == image_id));
+        if let Some(idx) = idx {
+            self.list_state.select(Some(idx));
+        }
+    }
+
     fn get_image(&self) -> Result<&DockerImage> {
         if let Some(image_idx) = self.list_state.selected()
             && let Some(image) = self.images.get(image_idx)
@@ -337,6 +473,234 @@ impl Images {
         Ok(())
     }
 
+    async fn open_run_modal(&mut self) -> Result<()> {
+        let image = self.get_image()?;
+        let message = format!("Run a new container from {}", image.get_full_name());
+
+        let networks = std::iter::once(DEFAULT_NETWORK.to_string())
+            .chain(
+                DockerNetwork::list(&self.docker)
+                    .await
+                    .context("unable to retrieve list of networks")?
+                    .into_iter()
+                    .map(|n| n.name),
+            )
+            .collect();
+        let restart_policies = RESTART_POLICIES.iter().map(|p| p.to_string()).collect();
+
+        let fields = vec![
+            FormField::text("name", "Name"),
+            FormField::text("command", "Command").with_validator(validate_command),
+            FormField::text("env", "Env (KEY=value, ...)").with_validator(validate_env),
+            FormField::text("ports", "Ports (host:container, ...)").with_validator(validate_ports),
+            FormField::text("volumes", "Volumes (source:target, ...)")
+                .with_validator(validate_volumes),
+            FormField::choice("network", "Network", networks),
+            FormField::choice("restart_policy", "Restart policy", restart_policies),
+            FormField::toggle("auto_remove", "Auto-remove", false),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Run".into(), ModalTypes::Run, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    /// Creates and starts a container from the submitted run form, then moves to
+    /// the containers page with the new container selected
+    async fn run_image(&mut self, values: FormValues) -> Result<()> {
+        let image = self.get_image()?;
+
+        let config = ContainerRunConfig {
+            image: image.get_full_name(),
+            name: values.text_opt("name"),
+            command: values.text_opt("command"),
+            env: values.text_opt("env"),
+            ports: values.text_opt("ports"),
+            volumes: values.text_opt("volumes"),
+            network: values.text_opt("network"),
+            restart_policy: values.text_opt("restart_policy"),
+            auto_remove: values.toggle("auto_remove"),
+        };
+
+        let container = DockerContainer::run(&self.docker, &config)
+            .await
+            .context("unable to run image")?;
+
+        self.tx
+            .send(Message::Transition(Transition::ToContainerPage(
+                AppContext {
+                    docker_container: Some(container),
+                    ..Default::default()
+                },
+            )))
+            .await?;
+        Ok(())
+    }
+
+    fn open_save_modal(&mut self) -> Result<()> {
+        let image = self.get_image()?;
+        let name = image.get_full_name();
+        let message = format!("Save {name} as a tarball");
+        let default_path = format!("./{}.tar", name.replace(['/', ':'], "_"));
+
+        let fields = vec![
+            FormField::text("path", "Local file")
+                .with_value(&default_path)
+                .with_validator(validate_output_path),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Save".into(), ModalTypes::Save, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    fn open_load_modal(&mut self) {
+        let message = "Load images from a tarball created by docker save".to_string();
+        let fields =
+            vec![FormField::text("path", "Local file").with_validator(validate_local_path)];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Load".into(), ModalTypes::Load, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    fn open_pull_modal(&mut self) {
+        let message = "Pull an image from a registry".to_string();
+        let fields =
+            vec![FormField::text("image", "Image (name[:tag])").with_validator(validate_reference)];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Pull".into(), ModalTypes::Pull, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    async fn pull_image(&mut self, values: FormValues) -> Result<()> {
+        self.tx
+            .send(Message::Transition(Transition::ToPullPage(AppContext {
+                image_reference: Some(values.text("image")),
+                ..Default::default()
+            })))
+            .await?;
+        Ok(())
+    }
+
+    fn open_tag_modal(&mut self) -> Result<()> {
+        let image = self.get_image()?;
+        let message = format!("Tag {}", image.get_full_name());
+        let (repository, tag) = if image.name == "<none>" {
+            (String::new(), DEFAULT_TAG.to_string())
+        } else {
+            (image.name.clone(), image.tag.clone())
+        };
+
+        let fields = vec![
+            FormField::text("repository", "Repository")
+                .with_value(&repository)
+                .with_validator(validate_repository),
+            FormField::text("tag", "Tag")
+                .with_value(&tag)
+                .with_validator(validate_tag),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Tag".into(), ModalTypes::Tag, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    /// Tags the selected image with the submitted repository and tag, then
+    /// selects the new tag in the list
+    async fn tag_image(&mut self, values: FormValues) -> Result<()> {
+        let image = self.get_image()?.clone();
+        let tag = values.text_opt("tag").unwrap_or(DEFAULT_TAG.into());
+        let reference = image
+            .tag(&self.docker, &values.text("repository"), &tag)
+            .await?;
+
+        self.refresh().await?;
+        self.select_image(&image.id, Some(reference));
+        Ok(())
+    }
+
+    /// Pushes the selected image to its registry, showing the progress on the
+    /// push page
+    async fn push_image(&mut self) -> Result<()> {
+        let image = self.get_image()?;
+        if image.name == "<none>" {
+            bail!("the image must be tagged before it can be pushed");
+        }
+
+        self.tx
+            .send(Message::Transition(Transition::ToPushPage(AppContext {
+                image_reference: Some(image.get_full_name()),
+                ..Default::default()
+            })))
+            .await?;
+        Ok(())
+    }
+
+    /// Saves the selected image to the submitted path in the background
+    fn save_image(&mut self, values: FormValues) -> Result<()> {
+        let image = self.get_image()?.clone();
+        let path = expand_local_path(&values.text("path"));
+        let docker = self.docker.clone();
+
+        let description = format!("Saving {} to {}", image.get_full_name(), path.display());
+        self.transfers
+            .spawn(self.tx.clone(), description, move |transfer| async move {
+                image.save(&docker, &path, &transfer).await?;
+                Ok(format!(
+                    "Saved {} to {}",
+                    image.get_full_name(),
+                    path.display()
+                ))
+            });
+        Ok(())
+    }
+
+    /// Loads images from the submitted path in the background; the image list
+    /// is refreshed on the tick sent once the load finishes
+    fn load_images(&mut self, values: FormValues) {
+        let path = expand_local_path(&values.text("path"));
+        let docker = self.docker.clone();
+
+        let description = format!("Loading {}", path.display());
+        self.transfers
+            .spawn(self.tx.clone(), description, move |transfer| async move {
+                let loaded = DockerImage::load(&docker, &path, transfer).await?;
+                if loaded.is_empty() {
+                    Ok(format!("Loaded {}", path.display()))
+                } else {
+                    Ok(format!("Loaded {}", loaded.join(", ")))
+                }
+            });
+    }
+
+    /// Lists the containers created from the selected image, allowing one to
+    /// be picked and shown on the containers page
+    fn open_used_by_modal(&mut self) -> Result<()> {
+        let image = self.get_image()?;
+        let containers = self.usage.image(image);
+        if containers.is_empty() {
+            bail!("no containers use {}", image.get_full_name());
+        }
+        let message = format!("Containers using {}", image.get_full_name());
+        let options = used_by_options(&containers);
+
+        let mut modal =
+            SelectModal::<ModalTypes, DockerContainer>::new("Used By".into(), ModalTypes::UsedBy);
+        modal.initialise(message, options);
+        self.used_by_modal = Some(modal);
+        Ok(())
+    }
+
     fn get_context(&self) -> Result<AppContext> {
         let image = self.get_image()?;
 
@@ -358,16 +722,17 @@ impl Images {
 impl Component for Images {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
         self.table_height = area.height.saturating_sub(2);
-        let rows = get_image_rows(&self.images);
+        let rows = get_image_rows(&self.images, &self.usage);
         let columns = Row::new(vec![
             get_header_with_sort_indicator("ID", ImageSortField::Id, &self.sort_state),
             get_header_with_sort_indicator("Name", ImageSortField::Name, &self.sort_state),
             get_header_with_sort_indicator("Tag", ImageSortField::Tag, &self.sort_state),
             get_header_with_sort_indicator("Created", ImageSortField::Created, &self.sort_state),
             get_header_with_sort_indicator("Size", ImageSortField::Size, &self.sort_state),
+            "Used By".to_string(),
         ]);
 
-        let widths = constraints![==20%, ==20%, ==20%, ==20%, ==20%];
+        let widths = constraints![==15%, ==20%, ==15%, ==15%, ==10%, ==25%];
 
         let table = Table::new(rows.clone(), widths)
             .header(columns.clone().style(Style::new().bold()))
@@ -380,10 +745,22 @@ impl Component for Images {
         {
             m.draw(f, area)
         }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
+
+        if let Some(m) = self.used_by_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
     }
 }
 
-fn get_image_rows(containers: &'_ [DockerImage]) -> Vec<Row<'_>> {
+fn get_image_rows<'a>(containers: &'a [DockerImage], usage: &ContainerUsage) -> Vec<Row<'a>> {
     containers
         .iter()
         .map(|c| {
@@ -393,6 +770,7 @@ fn get_image_rows(containers: &'_ [DockerImage]) -> Vec<Row<'_>> {
                 c.tag.clone(),
                 c.created.clone(),
                 c.size.clone(),
+                format_used_by(&usage.image(c)),
             ])
         })
         .collect()
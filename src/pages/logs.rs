@@ -1,7 +1,4 @@
-use ansi_to_tui::IntoText;
 use futures::StreamExt;
-use ratatui::text::Text;
-use ratatui::widgets::{List, ListState};
 use std::sync::{Arc, Mutex};
 use tokio::task::JoinHandle;
 
@@ -13,7 +10,10 @@ use crate::config::Config;
 use crate::context::AppContext;
 use crate::docker::logs::StreamOptions;
 use crate::{
-    components::help::{PageHelp, PageHelpBuilder},
+    components::{
+        help::{PageHelp, PageHelpBuilder},
+        log_view::LogView,
+    },
     docker::logs::DockerLogs,
     events::{Key, Message, Transition, message::MessageResponse},
     traits::{Close, Component, Page},
@@ -22,13 +22,7 @@ use crate::{
 const NAME: &str = "Logs";
 
 const ESC_KEY: Key = Key::Esc;
-const J_KEY: Key = Key::Char('j');
 const A_KEY: Key = Key::Char('a');
-const UP_KEY: Key = Key::Up;
-const K_KEY: Key = Key::Char('k');
-const DOWN_KEY: Key = Key::Down;
-const PAGE_UP_KEY: Key = Key::PageUp;
-const PAGE_DOWN_KEY: Key = Key::PageDown;
 const SPACE_BAR: Key = Key::Char(' ');
 const G_KEY: Key = Key::Char('g');
 const SHIFT_G_KEY: Key = Key::Char('G');
@@ -40,13 +34,10 @@ pub struct Logs {
     tx: Sender<Message<Key, Transition>>,
     logs: Option<DockerLogs>,
     page_help: Arc<Mutex<PageHelp>>,
-    log_messages: Arc<Mutex<Vec<String>>>,
+    log_view: LogView,
     log_streamer_handle: Option<JoinHandle<()>>,
-    list_state: ListState,
-    auto_scroll: bool,
     next: Option<Transition>,
     stream_options: StreamOptions,
-    list_height: u16,
 }
 
 impl Logs {
@@ -63,13 +54,10 @@ impl Logs {
             logs: None,
             tx,
             page_help: Arc::new(Mutex::new(page_help)),
-            log_messages: Arc::new(Mutex::new(vec![])),
+            log_view: LogView::default(),
             log_streamer_handle: None,
-            list_state: ListState::default(),
-            auto_scroll: true,
             next: None,
             stream_options: StreamOptions::default(),
-            list_height: 0,
         }
     }
 
@@ -81,42 +69,18 @@ impl Logs {
             .add_input(format!("{A_KEY}"), "<all>".into())
     }
 
-    fn activate_auto_scroll(&mut self) {
-        if self.auto_scroll {
-            return;
-        }
-        self.auto_scroll = true;
-
-        self.page_help = Arc::new(Mutex::new(
-            Self::build_page_help(
-                if let Some(l) = &self.logs {
-                    &l.container.names
-                } else {
-                    ""
-                },
-                self.config.clone(),
-            )
-            .build(),
-        ));
-    }
-
-    fn deactivate_auto_scroll(&mut self) {
-        if !self.auto_scroll {
-            return;
+    /// Rebuilds the help, which includes re-enabling auto-scroll when it is off
+    fn refresh_page_help(&mut self) {
+        let name = if let Some(l) = &self.logs {
+            l.container.names.clone()
+        } else {
+            String::new()
+        };
+        let mut page_help = Self::build_page_help(&name, self.config.clone());
+        if !self.log_view.auto_scroll() {
+            page_help = page_help.add_input(format!("{SPACE_BAR}"), "auto-scroll".into());
         }
-        self.auto_scroll = false;
-        self.page_help = Arc::new(Mutex::new(
-            Self::build_page_help(
-                if let Some(l) = &self.logs {
-                    &l.container.names
-                } else {
-                    ""
-                },
-                self.config.clone(),
-            )
-            .add_input(format!("{SPACE_BAR}"), "auto-scroll".into())
-            .build(),
-        ));
+        self.page_help = Arc::new(Mutex::new(page_help.build()));
     }
 
     fn abort(&mut self) {
@@ -124,35 +88,15 @@ impl Logs {
             handle.abort()
         }
         self.log_streamer_handle = None;
-        self.log_messages = Arc::new(Mutex::new(vec![String::new()]));
+        self.log_view.reset();
         self.logs = None;
     }
 
-    fn scroll_down(&mut self, amount: usize) {
-        let len = self.log_messages.lock().unwrap().len();
-        if len == 0 {
-            self.list_state.select(Some(0));
-            return;
-        }
-        let current = self.list_state.selected().unwrap_or(0);
-        let next = (current + amount).min(len - 1);
-        self.list_state.select(Some(next));
-        self.deactivate_auto_scroll();
-    }
-
-    fn scroll_up(&mut self, amount: usize) {
-        let current = self.list_state.selected().unwrap_or(0);
-        let next = current.saturating_sub(amount);
-        self.list_state.select(Some(next));
-        self.deactivate_auto_scroll();
-    }
-
     async fn start_log_stream(&mut self) -> Result<()> {
-        self.auto_scroll = true;
         if let Some(logs) = self.logs.clone() {
             let mut logs_stream = logs.get_log_stream(&self.docker, self.stream_options.clone());
             let tx = self.tx.clone();
-            let log_messages = self.log_messages.clone();
+            let log_messages = self.log_view.lines();
 
             self.log_streamer_handle = Some(tokio::spawn(async move {
                 while let Some(v) = logs_stream.next().await {
@@ -189,34 +133,6 @@ impl Page for Logs {
                 self.tx.send(Message::Transition(transition)).await?;
                 MessageResponse::Consumed
             }
-            G_KEY => {
-                self.list_state.select_first();
-                MessageResponse::Consumed
-            }
-            SHIFT_G_KEY => {
-                self.list_state.select_last();
-                MessageResponse::Consumed
-            }
-            J_KEY | DOWN_KEY => {
-                self.scroll_down(1);
-                MessageResponse::Consumed
-            }
-            PAGE_DOWN_KEY => {
-                self.scroll_down(self.list_height.into());
-                MessageResponse::Consumed
-            }
-            K_KEY | UP_KEY => {
-                self.scroll_up(1);
-                MessageResponse::Consumed
-            }
-            PAGE_UP_KEY => {
-                self.scroll_up(self.list_height.into());
-                MessageResponse::Consumed
-            }
-            SPACE_BAR => {
-                self.activate_auto_scroll();
-                MessageResponse::Consumed
-            }
             A_KEY => {
                 self.stream_options.all = true;
                 let logs = self.logs.clone();
@@ -225,14 +141,18 @@ impl Page for Logs {
                     self.logs = Some(DockerLogs::from(l.container.clone()));
                 }
                 self.start_log_stream().await?;
+                self.refresh_page_help();
                 MessageResponse::Consumed
             }
-            _ => MessageResponse::NotConsumed,
+            _ => {
+                let auto_scroll = self.log_view.auto_scroll();
+                let res = self.log_view.update(message);
+                if auto_scroll != self.log_view.auto_scroll() {
+                    self.refresh_page_help();
+                }
+                res
+            }
         };
-
-        if self.auto_scroll {
-            self.list_state.select_last();
-        }
         Ok(res)
     }
 
@@ -261,29 +181,12 @@ impl Page for Logs {
 impl Close for Logs {
     async fn close(&mut self) -> Result<()> {
         self.abort();
-        self.logs = None;
-        self.log_messages = Arc::new(Mutex::new(vec![]));
         Ok(())
     }
 }
 
 impl Component for Logs {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
-        self.list_height = area.height.saturating_sub(1);
-        let logs: Vec<Text> = self
-            .log_messages
-            .lock()
-            .unwrap()
-            .clone()
-            .into_iter()
-            .map(|s| s.into_text().unwrap())
-            .collect();
-        let mut list = List::new(logs);
-
-        if !self.auto_scroll {
-            list = list.highlight_symbol("> ");
-        }
-
-        f.render_stateful_widget(list, area, &mut self.list_state)
+        self.log_view.draw(f, area)
     }
 }
@@ -1,8 +1,20 @@
 pub mod attach;
+pub mod attach_process;
+pub mod build;
+pub mod changes;
 pub mod containers;
 pub mod describe;
+pub mod events;
+pub mod files;
 pub mod help;
+pub mod history;
 pub mod images;
 pub mod logs;
+pub mod network_detail;
 pub mod networks;
+pub mod prune;
+pub mod registry;
+pub mod stats;
+pub mod system;
+pub mod top;
 pub mod volumes;
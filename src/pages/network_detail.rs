@@ -0,0 +1,446 @@
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Context, ContextCompat, Result, bail};
+use futures::lock::Mutex as FutureMutex;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    widgets::{Paragraph, Row, Table, TableState},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    callbacks::disconnect_network::DisconnectNetwork,
+    components::{
+        boolean_modal::{BooleanModal, ModalState},
+        form_modal::{self, FormField, FormModal, FormValues},
+        help::{PageHelp, PageHelpBuilder},
+    },
+    config::Config,
+    context::AppContext,
+    docker::{
+        container::DockerContainer,
+        create::validate_ip,
+        network::{DockerNetwork, NetworkConnectConfig, NetworkEndpoint},
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, ModalComponent, Page},
+};
+
+const NAME: &str = "Network";
+
+const ESC_KEY: Key = Key::Esc;
+const ENTER_KEY: Key = Key::Enter;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const C_KEY: Key = Key::Char('c');
+const CTRL_D_KEY: Key = Key::Ctrl('d');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModalTypes {
+    Connect,
+    Disconnect,
+    ForceDisconnect,
+}
+
+/// Page listing the containers attached to a network along with their
+/// addresses, from which containers can be connected and disconnected
+#[derive(Debug)]
+pub struct NetworkDetail {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    network: Option<DockerNetwork>,
+    next: Option<Transition>,
+    endpoints: Vec<NetworkEndpoint>,
+    list_state: TableState,
+    modal: Option<BooleanModal<ModalTypes>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    table_height: u16,
+}
+
+impl NetworkDetail {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            network: None,
+            next: None,
+            endpoints: vec![],
+            list_state: TableState::default(),
+            modal: None,
+            form_modal: None,
+            table_height: 0,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
+        PageHelpBuilder::new(name.unwrap_or(NAME.into()), config)
+            .add_input(format!("{ESC_KEY}"), "back".into())
+            .add_input(format!("{C_KEY}"), "connect".into())
+            .add_input(format!("{CTRL_D_KEY}"), "disconnect".into())
+            .add_input(format!("{ENTER_KEY}"), "container".into())
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .build()
+    }
+
+    fn get_network(&self) -> Result<&DockerNetwork> {
+        self.network.as_ref().context("no network")
+    }
+
+    fn get_endpoint(&self) -> Result<&NetworkEndpoint> {
+        self.list_state
+            .selected()
+            .and_then(|idx| self.endpoints.get(idx))
+            .context("no container selected")
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        let network = self.get_network()?;
+        self.endpoints = network
+            .endpoints(&self.docker)
+            .await
+            .with_context(|| format!("unable to retrieve containers on {}", network.name))?;
+
+        let last = self.endpoints.len().saturating_sub(1);
+        if self.list_state.selected().is_some_and(|idx| idx > last) {
+            self.list_state.select(Some(last));
+        }
+        Ok(())
+    }
+
+    async fn back(&self) -> Result<()> {
+        let transition = match &self.next {
+            Some(t) => t.clone(),
+            None => Transition::ToNetworkPage(AppContext {
+                docker_network: self.network.clone(),
+                ..Default::default()
+            }),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(())
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default() + amount;
+        self.list_state
+            .select(Some(idx.min(self.endpoints.len().saturating_sub(1))));
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(idx.saturating_sub(amount)));
+    }
+
+    async fn update_modal(&mut self, message: Key) -> Result<MessageResponse> {
+        let Some(m) = self.modal.as_mut() else {
+            return Ok(MessageResponse::NotConsumed);
+        };
+
+        if let ModalState::Open(_) = m.state {
+            match m.update(message).await {
+                Ok(_) => {
+                    if let ModalState::Closed = m.state {
+                        self.modal = None;
+                    }
+                }
+                Err(e) => {
+                    if let ModalTypes::Disconnect = m.discriminator {
+                        let msg = "An error occurred disconnecting this container; would you like to try to force disconnect?";
+                        self.disconnect(true, Some(msg.into()), Some(ModalTypes::ForceDisconnect))?;
+                    } else {
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(MessageResponse::Consumed)
+        } else {
+            Ok(MessageResponse::NotConsumed)
+        }
+    }
+
+    /// Opens a form to connect one of the containers not yet on the network
+    async fn open_connect_modal(&mut self) -> Result<()> {
+        let network = self.get_network()?;
+        let message = format!("Connect a container to {}", network.name);
+
+        let containers = self.connectable_containers().await?;
+        if containers.is_empty() {
+            bail!("all containers are already connected to {}", network.name);
+        }
+        let names = containers.into_iter().map(|c| c.names).collect();
+
+        let fields = vec![
+            FormField::choice("container", "Container", names),
+            FormField::text("alias", "Alias"),
+            FormField::text("ip", "IP address").with_validator(validate_ip),
+        ];
+
+        let mut modal = FormModal::<ModalTypes>::new(
+            "Connect".into(),
+            ModalTypes::Connect,
+            self.config.clone(),
+        );
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    async fn connectable_containers(&self) -> Result<Vec<DockerContainer>> {
+        let containers = DockerContainer::list(&self.docker)
+            .await
+            .context("unable to retrieve list of containers")?
+            .into_iter()
+            .filter(|c| !self.endpoints.iter().any(|e| e.container_id == c.id))
+            .collect();
+        Ok(containers)
+    }
+
+    /// Connects the container chosen in the submitted form, then selects it
+    async fn connect(&mut self, values: FormValues) -> Result<()> {
+        let name = values.text("container");
+        let container = self
+            .connectable_containers()
+            .await?
+            .into_iter()
+            .find(|c| c.names == name)
+            .with_context(|| format!("no container found named {name}"))?;
+
+        let config = NetworkConnectConfig {
+            container_id: container.id.clone(),
+            alias: values.text_opt("alias"),
+            ip: values.text_opt("ip"),
+        };
+        self.get_network()?.connect(&self.docker, &config).await?;
+
+        self.refresh().await?;
+        if let Some(idx) = self
+            .endpoints
+            .iter()
+            .position(|e| e.container_id == container.id)
+        {
+            self.list_state.select(Some(idx));
+        }
+        Ok(())
+    }
+
+    fn disconnect(
+        &mut self,
+        force: bool,
+        message_override: Option<String>,
+        type_override: Option<ModalTypes>,
+    ) -> Result<()> {
+        let network = self.get_network()?.clone();
+        let endpoint = self.get_endpoint()?.clone();
+        let message = match message_override {
+            Some(m) => m,
+            None => format!(
+                "Are you sure you wish to disconnect {} from {}?",
+                endpoint.name, network.name
+            ),
+        };
+
+        let cb = Arc::new(FutureMutex::new(DisconnectNetwork::new(
+            self.docker.clone(),
+            network,
+            endpoint,
+            force,
+        )));
+
+        let mut modal = BooleanModal::<ModalTypes>::new(
+            "Disconnect".into(),
+            type_override.unwrap_or(ModalTypes::Disconnect),
+        );
+        modal.initialise(message, Some(cb));
+        self.modal = Some(modal);
+        Ok(())
+    }
+
+    /// Shows the selected container on the containers page
+    async fn show_container(&self) -> Result<()> {
+        let endpoint = self.get_endpoint()?;
+        let container = DockerContainer::get(&self.docker, &endpoint.container_id).await?;
+        self.tx
+            .send(Message::Transition(Transition::ToContainerPage(
+                AppContext {
+                    docker_container: Some(container),
+                    ..Default::default()
+                },
+            )))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for NetworkDetail {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        self.refresh().await?;
+
+        let res = self.update_modal(message).await?;
+        if res == MessageResponse::Consumed {
+            return Ok(res);
+        }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            if let Some(values) = values {
+                self.connect(values).await?;
+            }
+            return res;
+        }
+
+        let res = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.list_state.select(Some(0));
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state
+                    .select(Some(self.endpoints.len().saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            C_KEY => {
+                self.open_connect_modal().await?;
+                MessageResponse::Consumed
+            }
+            CTRL_D_KEY => match self.disconnect(false, None, None) {
+                Ok(()) => MessageResponse::Consumed,
+                Err(_) => MessageResponse::NotConsumed,
+            },
+            ENTER_KEY => {
+                self.show_container().await?;
+                MessageResponse::Consumed
+            }
+            ESC_KEY => {
+                self.back().await?;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let Some(network) = cx.docker_network.clone() else {
+            bail!("no docker network")
+        };
+
+        let page_name = format!("{NAME} ({})", network.name);
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            Some(page_name),
+        )));
+
+        self.network = Some(network);
+        self.next = cx.next();
+        self.list_state = TableState::default();
+        self.list_state.select(Some(0));
+        self.refresh().await
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for NetworkDetail {}
+
+impl Component for NetworkDetail {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [summary_area, table_area] = vertical![==2, >=0].areas(area);
+        self.table_height = table_area.height.saturating_sub(1);
+
+        if let Some(network) = &self.network {
+            let summary = format!(
+                "{} ({} driver, {} scope): {} containers attached",
+                network.name,
+                network.driver,
+                network.scope,
+                self.endpoints.len()
+            );
+            f.render_widget(
+                Paragraph::new(summary).style(Style::new().fg(self.config.theme.title())),
+                summary_area,
+            );
+        }
+
+        let rows = self
+            .endpoints
+            .iter()
+            .map(|e| {
+                Row::new(vec![
+                    e.name.clone(),
+                    e.ipv4_address.clone(),
+                    e.ipv6_address.clone(),
+                    e.mac_address.clone(),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let columns = Row::new(vec![
+            "Container",
+            "IPv4 Address",
+            "IPv6 Address",
+            "MAC Address",
+        ]);
+        let widths = constraints![==30%, ==20%, ==30%, ==20%];
+        let table = Table::new(rows, widths)
+            .header(columns.style(Style::new().bold()))
+            .row_highlight_style(Style::new().reversed());
+        f.render_stateful_widget(table, table_area, &mut self.list_state);
+
+        if let Some(m) = self.modal.as_mut()
+            && let ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+    }
+}
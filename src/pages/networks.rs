@@ -18,11 +18,21 @@ use crate::{
     },
     components::{
         boolean_modal::{BooleanModal, ModalState},
+        form_modal::{self, FormField, FormModal, FormValues},
         help::{PageHelp, PageHelpBuilder},
+        select_modal::{self, SelectModal},
     },
     config::Config,
     context::AppContext,
-    docker::network::DockerNetwork,
+    docker::{
+        container::DockerContainer,
+        create::{
+            NETWORK_DRIVERS, NetworkCreateConfig, validate_ip, validate_key_values,
+            validate_network_name, validate_subnet,
+        },
+        network::DockerNetwork,
+        usage::{ContainerUsage, format_used_by, used_by_options},
+    },
     events::{Key, Message, Transition, message::MessageResponse},
     sorting::{
         NetworkSortField, SortOrder, SortState, sort_networks_by_created, sort_networks_by_driver,
@@ -43,7 +53,10 @@ const K_KEY: Key = Key::Char('k');
 const CTRL_D_KEY: Key = Key::Ctrl('d');
 const SHIFT_D_KEY: Key = Key::Char('D');
 const D_KEY: Key = Key::Char('d');
+const C_KEY: Key = Key::Char('c');
+const ENTER_KEY: Key = Key::Enter;
 const G_KEY: Key = Key::Char('g');
+const U_KEY: Key = Key::Char('u');
 const SHIFT_G_KEY: Key = Key::Char('G');
 const CTRL_P_KEY: Key = Key::Ctrl('p');
 
@@ -58,17 +71,23 @@ type NetworkSortState = SortState<NetworkSortField>;
 enum ModalTypes {
     DeleteNetwork,
     FailedToDeleteNetwork,
+    UsedBy,
+    Create,
 }
 
 #[derive(Debug)]
 pub struct Network {
     pub name: String,
+    config: Arc<Config>,
     tx: Sender<Message<Key, Transition>>,
     page_help: Arc<Mutex<PageHelp>>,
     docker: Docker,
     networks: Vec<DockerNetwork>,
+    usage: ContainerUsage,
     list_state: TableState,
     modal: Option<BooleanModal<ModalTypes>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    used_by_modal: Option<SelectModal<ModalTypes, DockerContainer>>,
     sort_state: NetworkSortState,
     table_height: u16,
 }
@@ -83,6 +102,41 @@ impl Page for Network {
             return Ok(res);
         }
 
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            if let Some(values) = values {
+                self.create_network(values).await?;
+            }
+            return res;
+        }
+
+        if let Some(m) = self.used_by_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let container = m.take_selected();
+            if let select_modal::ModalState::Closed = m.state {
+                self.used_by_modal = None;
+            }
+            if let Some(container) = container {
+                self.tx
+                    .send(Message::Transition(Transition::ToContainerPage(
+                        AppContext {
+                            docker_container: Some(container),
+                            ..Default::default()
+                        },
+                    )))
+                    .await?;
+            }
+            return res;
+        }
+
         let result = match message {
             UP_KEY | K_KEY => {
                 self.scroll_up(1);
@@ -136,6 +190,26 @@ impl Page for Network {
                 Ok(()) => MessageResponse::Consumed,
                 Err(_) => MessageResponse::NotConsumed,
             },
+            C_KEY => {
+                self.open_create_modal();
+                MessageResponse::Consumed
+            }
+            ENTER_KEY => {
+                let network = self.get_network()?.clone();
+                self.tx
+                    .send(Message::Transition(Transition::ToNetworkDetailPage(
+                        AppContext {
+                            docker_network: Some(network),
+                            ..Default::default()
+                        },
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            U_KEY => {
+                self.open_used_by_modal()?;
+                MessageResponse::Consumed
+            }
             D_KEY => {
                 self.tx
                     .send(Message::Transition(Transition::ToDescribeContainerPage(
@@ -153,6 +227,7 @@ impl Page for Network {
         self.list_state = TableState::default();
         self.list_state.select(Some(0));
 
+        self.usage.invalidate();
         self.refresh().await.context("unable to refresh networks")?;
 
         let network_id: String;
@@ -191,16 +266,23 @@ impl Network {
             .add_input(format!("{G_KEY}"), "top".to_string())
             .add_input(format!("{SHIFT_G_KEY}"), "bottom".to_string())
             .add_input(format!("{D_KEY}"), "describe".to_string())
+            .add_input(format!("{C_KEY}"), "create".to_string())
+            .add_input(format!("{ENTER_KEY}"), "containers".to_string())
+            .add_input(format!("{U_KEY}"), "used by".to_string())
             .build();
 
         Self {
             name: String::from(NAME),
+            config,
             tx,
             page_help: Arc::new(Mutex::new(page_help)),
             docker,
             networks: vec![],
+            usage: ContainerUsage::default(),
             list_state: TableState::default(),
             modal: None,
+            form_modal: None,
+            used_by_modal: None,
             sort_state: NetworkSortState::new(NetworkSortField::Name),
             table_height: 0,
         }
@@ -210,6 +292,7 @@ impl Network {
         self.networks = DockerNetwork::list(&self.docker)
             .await
             .context("unable to retrieve list of networks")?;
+        self.usage.refresh(&self.docker).await?;
 
         self.sort_networks();
         Ok(())
@@ -302,6 +385,64 @@ impl Network {
         bail!("no container id found");
     }
 
+    fn open_create_modal(&mut self) {
+        let message = "Create a new network".to_string();
+        let drivers = NETWORK_DRIVERS.iter().map(|d| d.to_string()).collect();
+        let fields = vec![
+            FormField::text("name", "Name").with_validator(validate_network_name),
+            FormField::choice("driver", "Driver", drivers),
+            FormField::text("subnet", "Subnet (CIDR)").with_validator(validate_subnet),
+            FormField::text("gateway", "Gateway").with_validator(validate_ip),
+            FormField::toggle("internal", "Internal", false),
+            FormField::toggle("attachable", "Attachable", false),
+            FormField::text("labels", "Labels (KEY=value, ...)")
+                .with_validator(validate_key_values),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Create".into(), ModalTypes::Create, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    /// Creates a network from the submitted form, then selects it in the list
+    async fn create_network(&mut self, values: FormValues) -> Result<()> {
+        let config = NetworkCreateConfig {
+            name: values.text("name"),
+            driver: values.text_opt("driver"),
+            subnet: values.text_opt("subnet"),
+            gateway: values.text_opt("gateway"),
+            internal: values.toggle("internal"),
+            attachable: values.toggle("attachable"),
+            labels: values.text_opt("labels"),
+        };
+        let network = DockerNetwork::create(&self.docker, &config).await?;
+
+        self.refresh().await?;
+        if let Some(idx) = self.networks.iter().position(|n| n.id == network.id) {
+            self.list_state.select(Some(idx));
+        }
+        Ok(())
+    }
+
+    /// Lists the containers using the selected network, allowing one to be
+    /// picked and shown on the containers page
+    fn open_used_by_modal(&mut self) -> Result<()> {
+        let network = self.get_network()?;
+        let containers = self.usage.network(network);
+        if containers.is_empty() {
+            bail!("no containers use network {}", network.name);
+        }
+        let message = format!("Containers using network {}", network.name);
+        let options = used_by_options(&containers);
+
+        let mut modal =
+            SelectModal::<ModalTypes, DockerContainer>::new("Used By".into(), ModalTypes::UsedBy);
+        modal.initialise(message, options);
+        self.used_by_modal = Some(modal);
+        Ok(())
+    }
+
     fn get_context(&self) -> Result<AppContext> {
         let network = self.get_network()?;
 
@@ -362,16 +503,17 @@ impl Network {
 impl Component for Network {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
         self.table_height = area.height.saturating_sub(2);
-        let rows = get_network_rows(&self.networks);
+        let rows = get_network_rows(&self.networks, &self.usage);
         let columns = Row::new(vec![
             get_header_with_sort_indicator("Id", NetworkSortField::Id, &self.sort_state),
             get_header_with_sort_indicator("Name", NetworkSortField::Name, &self.sort_state),
             get_header_with_sort_indicator("Driver", NetworkSortField::Driver, &self.sort_state),
             get_header_with_sort_indicator("Created", NetworkSortField::Created, &self.sort_state),
             get_header_with_sort_indicator("Scope", NetworkSortField::Scope, &self.sort_state),
+            "Used By".to_string(),
         ]);
 
-        let widths = constraints![==30%, ==25%, ==15%, ==15%, ==15%];
+        let widths = constraints![==20%, ==20%, ==10%, ==20%, ==10%, ==20%];
 
         let table = Table::new(rows.clone(), widths)
             .header(columns.clone().style(Style::new().bold()))
@@ -384,19 +526,32 @@ impl Component for Network {
         {
             m.draw(f, area);
         }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+
+        if let Some(m) = self.used_by_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
     }
 }
 
-fn get_network_rows<'a>(networks: &'a [DockerNetwork]) -> Vec<Row<'a>> {
+fn get_network_rows<'a>(networks: &'a [DockerNetwork], usage: &ContainerUsage) -> Vec<Row<'a>> {
     networks
         .iter()
         .map(|c| {
             Row::new(vec![
-                c.id.as_str(),
-                c.name.as_str(),
-                c.driver.as_str(),
-                c.created_at.as_str(),
-                c.scope.as_str(),
+                c.id.clone(),
+                c.name.clone(),
+                c.driver.clone(),
+                c.created_at.clone(),
+                c.scope.clone(),
+                format_used_by(&usage.network(c)),
             ])
         })
         .collect::<Vec<Row<'a>>>()
@@ -0,0 +1,419 @@
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Context, Result, bail};
+use futures::lock::Mutex as FutureMutex;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Row, Table, TableState},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    callbacks::prune_resources::{PruneResources, PruneStatus},
+    components::{
+        boolean_modal::{self, BooleanModal},
+        form_modal::{self, FormField, FormModal, FormValues},
+        help::{PageHelp, PageHelpBuilder},
+    },
+    config::Config,
+    context::AppContext,
+    docker::{
+        prune::{
+            PruneCandidate, PruneConfig, PruneReport, estimated_size, preview, validate_until,
+        },
+        util::{format_bytes, validate_labels},
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, ModalComponent, Page},
+};
+
+const NAME: &str = "Prune";
+
+const ESC_KEY: Key = Key::Esc;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const O_KEY: Key = Key::Char('o');
+const P_KEY: Key = Key::Char('p');
+const R_KEY: Key = Key::Char('r');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModalTypes {
+    Options,
+    Confirm,
+}
+
+/// Page for pruning several kinds of unused resource at once.  The options
+/// are chosen in a form, after which the resources which would be removed are
+/// listed so they can be checked before anything is pruned.
+#[derive(Debug)]
+pub struct Prune {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    prune_config: PruneConfig,
+    candidates: Vec<PruneCandidate>,
+    status: Arc<Mutex<PruneStatus>>,
+    /// The result of the last prune run from this page
+    report: Option<PruneReport>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    modal: Option<BooleanModal<ModalTypes>>,
+    next: Option<Transition>,
+    list_state: TableState,
+    table_height: u16,
+}
+
+impl Prune {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = PageHelpBuilder::new(NAME.into(), config.clone())
+            .add_input(format!("{ESC_KEY}"), "back".into())
+            .add_input(format!("{O_KEY}"), "options".into())
+            .add_input(format!("{P_KEY}"), "prune".into())
+            .add_input(format!("{R_KEY}"), "refresh".into())
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .build();
+
+        // The same defaults as `docker system prune`
+        let prune_config = PruneConfig {
+            containers: true,
+            images: true,
+            networks: true,
+            build_cache: true,
+            ..Default::default()
+        };
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            prune_config,
+            candidates: vec![],
+            status: Arc::new(Mutex::new(PruneStatus::Idle)),
+            report: None,
+            form_modal: None,
+            modal: None,
+            next: None,
+            list_state: TableState::default(),
+            table_height: 0,
+        }
+    }
+
+    fn open_options_modal(&mut self) {
+        let c = &self.prune_config;
+        let message = "Choose what to prune".to_string();
+        let fields = vec![
+            FormField::toggle("containers", "Stopped containers", c.containers),
+            FormField::toggle("images", "Dangling images", c.images),
+            FormField::toggle("all_images", "Include unused tagged images", c.all_images),
+            FormField::toggle("volumes", "Unused anonymous volumes", c.volumes),
+            FormField::toggle("all_volumes", "Include unused named volumes", c.all_volumes),
+            FormField::toggle("networks", "Unused networks", c.networks),
+            FormField::toggle("build_cache", "Build cache", c.build_cache),
+            FormField::text("labels", "Labels (KEY or KEY=value, ...)")
+                .with_value(c.labels.as_deref().unwrap_or_default())
+                .with_validator(validate_labels),
+            FormField::text("until", "Created before (eg 24h)")
+                .with_value(c.until.as_deref().unwrap_or_default())
+                .with_validator(validate_until),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Prune".into(), ModalTypes::Options, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    async fn set_options(&mut self, values: FormValues) -> Result<()> {
+        self.prune_config = PruneConfig {
+            containers: values.toggle("containers"),
+            images: values.toggle("images"),
+            all_images: values.toggle("all_images"),
+            volumes: values.toggle("volumes"),
+            all_volumes: values.toggle("all_volumes"),
+            networks: values.toggle("networks"),
+            build_cache: values.toggle("build_cache"),
+            labels: values.text_opt("labels"),
+            until: values.text_opt("until"),
+        };
+        self.report = None;
+        self.refresh().await
+    }
+
+    /// Lists the resources which the current options would remove
+    async fn refresh(&mut self) -> Result<()> {
+        self.candidates = preview(&self.docker, &self.prune_config)
+            .await
+            .context("unable to preview prune")?;
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state
+            .select(Some(idx.min(self.candidates.len().saturating_sub(1))));
+        Ok(())
+    }
+
+    fn open_confirm_modal(&mut self) -> Result<()> {
+        if self.candidates.is_empty() {
+            bail!("there is nothing to prune");
+        }
+        if *self.status.lock().unwrap() == PruneStatus::Running {
+            bail!("a prune is already running");
+        }
+
+        let cb = Arc::new(FutureMutex::new(PruneResources::new(
+            self.docker.clone(),
+            self.prune_config.clone(),
+            self.status.clone(),
+            self.tx.clone(),
+        )));
+
+        let mut modal = BooleanModal::<ModalTypes>::new("Prune".into(), ModalTypes::Confirm);
+        modal.initialise(
+            format!(
+                "Are you sure you wish to remove these {} resources, reclaiming an estimated {}?",
+                self.candidates.len(),
+                format_bytes(estimated_size(&self.candidates)).unwrap_or_default()
+            ),
+            Some(cb),
+        );
+        self.modal = Some(modal);
+        Ok(())
+    }
+
+    /// Picks up the report of a prune once it has finished, listing what
+    /// remains to be pruned; a failed prune is returned as an error once the
+    /// list has been refreshed, as some resources may have been removed
+    async fn check_status(&mut self) -> Result<()> {
+        let finished = {
+            let mut status = self.status.lock().unwrap();
+            match &*status {
+                PruneStatus::Finished(_) | PruneStatus::Failed(_) => {
+                    Some(std::mem::take(&mut *status))
+                }
+                _ => None,
+            }
+        };
+        match finished {
+            Some(PruneStatus::Finished(report)) => {
+                self.report = Some(report);
+                self.refresh().await?;
+            }
+            Some(PruneStatus::Failed(err)) => {
+                self.report = None;
+                self.refresh().await?;
+                bail!("failed to prune: {err}");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn back(&self) -> Result<()> {
+        let transition = match &self.next {
+            Some(t) => t.clone(),
+            None => Transition::ToSystemPage(AppContext::default()),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(())
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default() + amount;
+        self.list_state
+            .select(Some(idx.min(self.candidates.len().saturating_sub(1))));
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let idx = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(idx.saturating_sub(amount)));
+    }
+
+    /// Describes the chosen options, eg `containers, images; until 24h`
+    fn describe_options(&self) -> String {
+        let c = &self.prune_config;
+        let selected = [
+            (c.containers, "stopped containers"),
+            (c.images && !c.all_images, "dangling images"),
+            (c.images && c.all_images, "unused images"),
+            (c.volumes && !c.all_volumes, "anonymous volumes"),
+            (c.volumes && c.all_volumes, "unused volumes"),
+            (c.networks, "unused networks"),
+            (c.build_cache, "build cache"),
+        ]
+        .into_iter()
+        .filter(|(selected, _)| *selected)
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+
+        let mut description = if selected.is_empty() {
+            "Nothing selected".to_string()
+        } else {
+            format!("Pruning {}", selected.join(", "))
+        };
+        if let Some(labels) = &c.labels {
+            description.push_str(&format!("; labels {labels}"));
+        }
+        if let Some(until) = &c.until {
+            description.push_str(&format!("; created before {until}"));
+        }
+        for skipped in c.skipped() {
+            description.push_str(&format!("; {skipped} can't be filtered so are skipped"));
+        }
+        description
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for Prune {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        self.check_status().await?;
+
+        if let Some(m) = self.modal.as_mut()
+            && let boolean_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await?;
+            if let boolean_modal::ModalState::Closed = m.state {
+                self.modal = None;
+            }
+            return Ok(res);
+        }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            if let Some(values) = values {
+                self.set_options(values).await?;
+            }
+            return res;
+        }
+
+        let res = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.list_state.select(Some(0));
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state
+                    .select(Some(self.candidates.len().saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            O_KEY => {
+                self.open_options_modal();
+                MessageResponse::Consumed
+            }
+            P_KEY => {
+                self.open_confirm_modal()?;
+                MessageResponse::Consumed
+            }
+            R_KEY => {
+                self.refresh().await?;
+                MessageResponse::Consumed
+            }
+            ESC_KEY => {
+                self.back().await?;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        self.next = cx.next();
+        self.list_state = TableState::default();
+        self.refresh().await?;
+        self.open_options_modal();
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Prune {}
+
+impl Component for Prune {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [summary_area, table_area] = vertical![==4, >=0].areas(area);
+        self.table_height = table_area.height.saturating_sub(1);
+
+        let theme = &self.config.theme;
+        let mut summary = vec![
+            Line::from(self.describe_options()).style(Style::new().fg(theme.title())),
+            Line::from(format!(
+                "{} resources will be removed, reclaiming an estimated {}",
+                self.candidates.len(),
+                format_bytes(estimated_size(&self.candidates)).unwrap_or_default()
+            )),
+        ];
+        if *self.status.lock().unwrap() == PruneStatus::Running {
+            summary.push(Line::from("Pruning...").style(Style::new().fg(theme.footer())));
+        } else if let Some(report) = &self.report {
+            summary.push(Line::from(report.to_string()).style(Style::new().fg(theme.success())));
+        }
+        f.render_widget(Paragraph::new(summary), summary_area);
+
+        let rows = self.candidates.iter().map(|c| {
+            Row::new(vec![
+                c.category.to_string(),
+                c.name.clone(),
+                c.id.clone(),
+                c.size.and_then(format_bytes).unwrap_or("-".into()),
+            ])
+        });
+        let columns = Row::new(vec!["Type", "Name", "ID", "Estimated Size"]);
+        let table = Table::new(rows, constraints![==20%, ==35%, ==30%, ==15%])
+            .header(columns.style(Style::new().bold()))
+            .row_highlight_style(Style::new().reversed());
+        f.render_stateful_widget(table, table_area, &mut self.list_state);
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+
+        if let Some(m) = self.modal.as_mut()
+            && let boolean_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use std::sync::{Arc, Mutex};
+
+use bollard::auth::DockerCredentials;
+use color_eyre::eyre::{Result, bail};
+use futures::{Stream, StreamExt};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Row, Table, TableState},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
+
+use crate::{
+    components::{
+        form_modal::{self, FormField, FormModal, FormValues},
+        help::{PageHelp, PageHelpBuilder},
+    },
+    config::Config,
+    context::AppContext,
+    docker::{
+        credentials,
+        image::DockerImage,
+        registry::{self, LayerPhase, PullProgress, PushProgress},
+        util::format_bytes,
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, ModalComponent, Page},
+};
+
+const ESC_KEY: Key = Key::Esc;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+
+/// Width of the text progress bars
+const BAR_WIDTH: usize = 20;
+
+/// Whether an image is being pulled from or pushed to its registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryOperation {
+    Pull,
+    Push,
+}
+
+impl RegistryOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Pull => "Pull",
+            Self::Push => "Push",
+        }
+    }
+
+    fn verb(&self) -> &'static str {
+        match self {
+            Self::Pull => "pull",
+            Self::Push => "push",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum RegistryStatus {
+    #[default]
+    Running,
+    Done,
+    Cancelled,
+    Failed(String),
+    /// The registry rejected the request; the user is asked to log in
+    Unauthorised(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModalTypes {
+    Login,
+}
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    status: RegistryStatus,
+    pull: PullProgress,
+    push: PushProgress,
+}
+
+/// Page showing the progress of an image being pulled or pushed; leaving the
+/// page before it completes cancels it
+#[derive(Debug)]
+pub struct Registry {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    operation: RegistryOperation,
+    reference: String,
+    state: Arc<Mutex<RegistryState>>,
+    handle: Option<JoinHandle<()>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    list_state: TableState,
+}
+
+impl Registry {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+        operation: RegistryOperation,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), operation.name().into(), true);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            operation,
+            reference: String::new(),
+            state: Arc::new(Mutex::new(RegistryState::default())),
+            handle: None,
+            form_modal: None,
+            list_state: TableState::default(),
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: String, running: bool) -> PageHelp {
+        let esc_description = if running { "cancel" } else { "back" };
+        PageHelpBuilder::new(name, config)
+            .add_input(format!("{ESC_KEY}"), esc_description.into())
+            .build()
+    }
+
+    fn refresh_page_help(&mut self) {
+        let running = self.status() == RegistryStatus::Running;
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            format!("{} ({})", self.operation.name(), self.reference),
+            running,
+        )));
+    }
+
+    fn status(&self) -> RegistryStatus {
+        self.state.lock().unwrap().status.clone()
+    }
+
+    fn set_status(&self, status: RegistryStatus) {
+        self.state.lock().unwrap().status = status;
+    }
+
+    /// Starts the pull or push in the background.  Pushes use the given
+    /// credentials, or those stored by the docker CLI where none are given.
+    fn start(&mut self, login: Option<DockerCredentials>) {
+        *self.state.lock().unwrap() = RegistryState::default();
+
+        let docker = self.docker.clone();
+        let tx = self.tx.clone();
+        let state = self.state.clone();
+        let reference = self.reference.clone();
+        let operation = self.operation;
+
+        self.handle = Some(tokio::spawn(async move {
+            let result = match operation {
+                RegistryOperation::Pull => {
+                    let stream = registry::pull(&docker, &reference);
+                    follow(stream, &state, &tx, |s, info| s.pull.update(info)).await
+                }
+                RegistryOperation::Push => {
+                    let credentials = match login {
+                        Some(login) => Some(login),
+                        None => {
+                            let lookup_reference = reference.clone();
+                            match tokio::task::spawn_blocking(move || {
+                                credentials::lookup(&lookup_reference)
+                            })
+                            .await
+                            {
+                                Ok(Ok(found)) => found,
+                                // Push anonymously; the user is asked to log in
+                                // if the registry requires it
+                                Ok(Err(e)) => {
+                                    state.lock().unwrap().push.messages.push(format!("{e:#}"));
+                                    None
+                                }
+                                Err(_) => None,
+                            }
+                        }
+                    };
+                    let stream = registry::push(&docker, &reference, credentials);
+                    follow(stream, &state, &tx, |s, info| s.push.update(info)).await
+                }
+            };
+
+            let status = match result {
+                Ok(()) => RegistryStatus::Done,
+                Err(e) => {
+                    let message = format!("{e:#}");
+                    if operation == RegistryOperation::Push && registry::is_auth_error(&message) {
+                        RegistryStatus::Unauthorised(message)
+                    } else {
+                        RegistryStatus::Failed(message)
+                    }
+                }
+            };
+            state.lock().unwrap().status = status;
+            let _ = tx.send(Message::Tick).await;
+        }));
+    }
+
+    fn cancel(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+        if self.status() == RegistryStatus::Running {
+            self.set_status(RegistryStatus::Cancelled);
+        }
+    }
+
+    fn open_login_modal(&mut self) {
+        let server = credentials::registry_server(&self.reference);
+        let message = format!("Log in to {server} to push {}", self.reference);
+        let fields = vec![
+            FormField::text("username", "Username").with_validator(validate_username),
+            FormField::secret("password", "Password"),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Login".into(), ModalTypes::Login, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    fn login(&mut self, values: FormValues) {
+        let server = credentials::registry_server(&self.reference);
+        let login =
+            credentials::from_login(&server, &values.text("username"), &values.text("password"));
+        self.start(Some(login));
+        self.refresh_page_help();
+    }
+
+    /// Returns to the images page, selecting the image if it exists
+    async fn back(&self) -> Result<()> {
+        let (image, tag) = registry::split_reference(&self.reference);
+        let reference = match tag {
+            Some(tag) => format!("{image}:{tag}"),
+            None => image,
+        };
+        let docker_image = DockerImage::get(&self.docker, &reference).await.ok();
+
+        self.tx
+            .send(Message::Transition(Transition::ToImagePage(AppContext {
+                docker_image,
+                ..Default::default()
+            })))
+            .await?;
+        Ok(())
+    }
+
+    fn draw_pull(&mut self, f: &mut Frame<'_>, area: Rect, progress: &PullProgress) {
+        let rows = progress.layers.iter().map(|layer| {
+            let style = match layer.phase {
+                LayerPhase::Complete => Style::new().fg(self.config.theme.positive_highlight()),
+                LayerPhase::Downloading | LayerPhase::Extracting | LayerPhase::Downloaded => {
+                    Style::new().fg(self.config.theme.paused_highlight())
+                }
+                LayerPhase::Waiting => Style::new(),
+            };
+
+            let bar = layer.ratio().map(progress_bar).unwrap_or_default();
+            let size = match (layer.phase, layer.current, layer.total) {
+                (LayerPhase::Downloading | LayerPhase::Extracting, Some(current), Some(total)) => {
+                    format!(
+                        "{} / {}",
+                        format_bytes(current).unwrap_or_default(),
+                        format_bytes(total).unwrap_or_default()
+                    )
+                }
+                _ => String::new(),
+            };
+
+            Row::new(vec![layer.id.clone(), layer.phase.to_string(), bar, size]).style(style)
+        });
+
+        let columns = Row::new(vec!["Layer", "Status", "Progress", "Size"]);
+        let widths = constraints![==14, ==12, ==(BAR_WIDTH as u16 + 5), >=20];
+        let table = Table::new(rows, widths).header(columns.style(Style::new().bold()));
+        f.render_stateful_widget(table, area, &mut self.list_state);
+    }
+
+    fn draw_push(&self, f: &mut Frame<'_>, area: Rect, progress: &PushProgress) {
+        let mut lines = vec![Line::from(format!(
+            "Layers pushed: {} / {}",
+            progress.complete, progress.layers
+        ))];
+        if progress.layers > 0 && progress.complete < progress.layers {
+            let upload = match (progress.ratio(), progress.current) {
+                (Some(ratio), Some((current, total))) => {
+                    format!(
+                        "{}  {} / {}",
+                        progress_bar(ratio),
+                        format_bytes(current).unwrap_or_default(),
+                        format_bytes(total).unwrap_or_default()
+                    )
+                }
+                _ => "Preparing…".into(),
+            };
+            lines.push(Line::from(format!("Uploading:     {upload}")));
+        }
+
+        let style = if progress.layers > 0 && progress.complete == progress.layers {
+            Style::new().fg(self.config.theme.positive_highlight())
+        } else {
+            Style::new()
+        };
+        f.render_widget(Paragraph::new(lines).style(style), area);
+    }
+}
+
+/// Applies each message of a progress stream to the shared state, redrawing
+/// the page after each one
+async fn follow<T>(
+    stream: impl Stream<Item = Result<T>>,
+    state: &Mutex<RegistryState>,
+    tx: &Sender<Message<Key, Transition>>,
+    apply: impl Fn(&mut RegistryState, T) -> Result<()>,
+) -> Result<()> {
+    let mut stream = std::pin::pin!(stream);
+    while let Some(info) = stream.next().await {
+        info.and_then(|info| apply(&mut state.lock().unwrap(), info))?;
+        let _ = tx.send(Message::Tick).await;
+    }
+    Ok(())
+}
+
+fn progress_bar(ratio: f64) -> String {
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    format!(
+        "{}{} {:>3.0}%",
+        "█".repeat(filled),
+        "░".repeat(BAR_WIDTH - filled),
+        ratio * 100.0
+    )
+}
+
+fn validate_username(username: &str) -> Result<()> {
+    if username.trim().is_empty() {
+        bail!("a username is required");
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl Page for Registry {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            match (self.status(), values) {
+                (_, Some(values)) => self.login(values),
+                // Declining to log in leaves the push failed
+                (RegistryStatus::Unauthorised(e), None) if self.form_modal.is_none() => {
+                    self.set_status(RegistryStatus::Failed(e));
+                    self.refresh_page_help();
+                }
+                _ => {}
+            }
+            return res;
+        }
+
+        let res = match message {
+            // Progress is drawn straight from the shared state, but the help
+            // changes once the operation has finished
+            Key::Null => {
+                if let RegistryStatus::Unauthorised(_) = self.status() {
+                    self.open_login_modal();
+                }
+                self.refresh_page_help();
+                MessageResponse::NotConsumed
+            }
+            ESC_KEY => {
+                if self.status() == RegistryStatus::Running {
+                    self.cancel();
+                    self.refresh_page_help();
+                } else {
+                    self.back().await?;
+                }
+                MessageResponse::Consumed
+            }
+            UP_KEY | K_KEY => {
+                self.list_state.scroll_up_by(1);
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.list_state.scroll_down_by(1);
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        let Some(reference) = cx.image_reference else {
+            bail!("no image to {}", self.operation.verb())
+        };
+        registry::validate_reference(&reference)?;
+
+        self.reference = reference.trim().to_string();
+        self.start(None);
+        self.refresh_page_help();
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Registry {
+    async fn close(&mut self) -> Result<()> {
+        self.cancel();
+        Ok(())
+    }
+}
+
+impl Component for Registry {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let state = self.state.clone();
+        let state = state.lock().unwrap();
+        let messages = match self.operation {
+            RegistryOperation::Pull => &state.pull.messages,
+            RegistryOperation::Push => &state.push.messages,
+        };
+        let message_count = messages.len().min(5) as u16;
+        let [header_area, progress_area, messages_area] =
+            vertical![==2, >=0, ==message_count].areas(area);
+
+        let (name, verb) = (self.operation.name(), self.operation.verb());
+        let (header, colour) = match &state.status {
+            RegistryStatus::Running => (
+                format!("{}ing {}…", name, self.reference),
+                self.config.theme.title(),
+            ),
+            RegistryStatus::Done => (
+                format!("{}ed {}", name, self.reference),
+                self.config.theme.success(),
+            ),
+            RegistryStatus::Cancelled => (
+                format!("Cancelled {verb}ing {}", self.reference),
+                self.config.theme.error(),
+            ),
+            RegistryStatus::Failed(e) | RegistryStatus::Unauthorised(e) => (
+                format!("Failed to {verb} {}: {e}", self.reference),
+                self.config.theme.error(),
+            ),
+        };
+        f.render_widget(
+            Paragraph::new(header).style(Style::new().fg(colour).bold()),
+            header_area,
+        );
+
+        match self.operation {
+            RegistryOperation::Pull => self.draw_pull(f, progress_area, &state.pull),
+            RegistryOperation::Push => self.draw_push(f, progress_area, &state.push),
+        }
+
+        let lines = messages
+            .iter()
+            .rev()
+            .take(message_count as usize)
+            .rev()
+            .map(|m| Line::from(m.as_str()))
+            .collect::<Vec<_>>();
+        f.render_widget(
+            Paragraph::new(lines).style(Style::new().fg(self.config.theme.help())),
+            messages_area,
+        );
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area)
+        }
+    }
+}
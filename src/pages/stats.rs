@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Report, Result, bail, eyre};
+use futures::StreamExt;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, Sparkline},
+};
+use ratatui_macros::{horizontal, vertical};
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+
+use crate::config::Config;
+use crate::context::AppContext;
+use crate::docker::traits::Describe;
+use crate::docker::{stats::DockerStats, util::format_bytes};
+use crate::{
+    components::help::{PageHelp, PageHelpBuilder},
+    docker::container::DockerContainer,
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, Page},
+};
+
+const NAME: &str = "Stats";
+
+/// Number of samples retained for the graphs; the daemon emits roughly one
+/// sample per second so this is approximately two minutes of history
+const WINDOW_SIZE: usize = 120;
+
+const ESC_KEY: Key = Key::Esc;
+
+#[derive(Debug)]
+pub struct Stats {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    container: Option<DockerContainer>,
+    page_help: Arc<Mutex<PageHelp>>,
+    samples: Arc<Mutex<VecDeque<DockerStats>>>,
+    /// Why the stats stream ended, reported on the next tick
+    error: Arc<Mutex<Option<Report>>>,
+    stats_streamer_handle: Option<JoinHandle<()>>,
+    next: Option<Transition>,
+}
+
+impl Stats {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None);
+
+        Self {
+            config,
+            docker,
+            tx,
+            container: None,
+            page_help: Arc::new(Mutex::new(page_help)),
+            samples: Arc::new(Mutex::new(VecDeque::with_capacity(WINDOW_SIZE + 1))),
+            error: Arc::new(Mutex::new(None)),
+            stats_streamer_handle: None,
+            next: None,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, name: Option<String>) -> PageHelp {
+        PageHelpBuilder::new(
+            match name {
+                Some(n) => n,
+                None => NAME.into(),
+            },
+            config,
+        )
+        .add_input(format!("{ESC_KEY}"), "back".into())
+        .build()
+    }
+
+    fn abort(&mut self) {
+        if let Some(handle) = &self.stats_streamer_handle {
+            handle.abort()
+        }
+        self.stats_streamer_handle = None;
+    }
+
+    fn start_stats_stream(&mut self) -> Result<()> {
+        let container = match &self.container {
+            Some(c) => c,
+            None => bail!("unable to stream stats without a container"),
+        };
+
+        let mut stats_stream = DockerStats::get_stats_stream(&self.docker, container);
+        let name = container.get_name();
+        let tx = self.tx.clone();
+        let samples = self.samples.clone();
+        let error = self.error.clone();
+
+        self.stats_streamer_handle = Some(tokio::spawn(async move {
+            while let Some(res) = stats_stream.next().await {
+                match res {
+                    Ok(s) => {
+                        let mut samples = samples.lock().unwrap();
+                        // One more than the window is kept so that rates can be
+                        // derived for every sample in the window
+                        if samples.len() > WINDOW_SIZE {
+                            samples.pop_front();
+                        }
+                        samples.push_back(s);
+                    }
+                    Err(err) => {
+                        *error.lock().unwrap() = Some(err);
+                        break;
+                    }
+                }
+                let _ = tx.send(Message::Tick).await;
+            }
+
+            // The daemon ends the stream once the container stops
+            error
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| eyre!("stats are no longer available for {name}"));
+            let _ = tx.send(Message::Tick).await;
+        }));
+
+        Ok(())
+    }
+
+    fn draw_sparkline(&self, f: &mut Frame<'_>, area: Rect, title: String, data: Vec<u64>) {
+        let sparkline = Sparkline::default()
+            .block(Block::bordered().title(title))
+            .style(Style::default().fg(self.config.theme.positive_highlight()))
+            .data(fit(data, area));
+        f.render_widget(sparkline, area);
+    }
+
+    fn draw_percent_sparkline(&self, f: &mut Frame<'_>, area: Rect, title: String, data: Vec<f64>) {
+        // Sparklines only support integer values, so percentages are scaled
+        // to hundredths of a percent to retain some precision
+        let data = data
+            .into_iter()
+            .map(|v| (v * 100.0).round() as u64)
+            .collect::<Vec<u64>>();
+        let max = data
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+            .max(100 * 100);
+
+        let sparkline = Sparkline::default()
+            .block(Block::bordered().title(title))
+            .style(Style::default().fg(self.config.theme.positive_highlight()))
+            .max(max)
+            .data(fit(data, area));
+        f.render_widget(sparkline, area);
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for Stats {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        if message == Key::Null
+            && let Some(err) = self.error.lock().unwrap().take()
+        {
+            return Err(err);
+        }
+
+        let res = match message {
+            ESC_KEY => {
+                let transition = if let Some(t) = self.next.clone() {
+                    t
+                } else {
+                    Transition::ToContainerPage(AppContext {
+                        docker_container: self.container.clone(),
+                        ..Default::default()
+                    })
+                };
+
+                self.tx.send(Message::Transition(transition)).await?;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        if let Some(container) = cx.clone().docker_container {
+            let page_name = format!("{NAME} ({})", container.get_name());
+            self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+                self.config.clone(),
+                Some(page_name),
+            )));
+            self.container = Some(container);
+        } else {
+            bail!("no docker container")
+        }
+
+        self.next = cx.next();
+
+        self.start_stats_stream()?;
+
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Stats {
+    async fn close(&mut self) -> Result<()> {
+        self.abort();
+        self.samples = Arc::new(Mutex::new(VecDeque::new()));
+        self.error = Arc::new(Mutex::new(None));
+        Ok(())
+    }
+}
+
+/// Gets the per-sample increase of a cumulative counter, dropping the first sample
+/// as it has nothing to be compared against
+fn rates(samples: &VecDeque<DockerStats>, value: fn(&DockerStats) -> u64) -> Vec<u64> {
+    samples
+        .iter()
+        .zip(samples.iter().skip(1))
+        .map(|(prev, curr)| value(curr).saturating_sub(value(prev)))
+        .collect()
+}
+
+/// Trims a series so that only the most recent values which fit in the area are drawn
+fn fit(values: Vec<u64>, area: Rect) -> Vec<u64> {
+    let width = area.width.saturating_sub(2) as usize;
+    let skip = values.len().saturating_sub(width);
+    values.into_iter().skip(skip).collect()
+}
+
+impl Component for Stats {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let samples = self.samples.lock().unwrap().clone();
+        let latest = samples.back().cloned().unwrap_or_default();
+
+        let [cpu_area, memory_area, net_area, block_area] =
+            vertical![==25%, ==25%, ==25%, ==25%].areas(area);
+        let [net_rx_area, net_tx_area] = horizontal![==50%, ==50%].areas(net_area);
+        let [block_read_area, block_write_area] = horizontal![==50%, ==50%].areas(block_area);
+
+        let last_rate =
+            |v: &[u64]| format_bytes(v.last().copied().unwrap_or_default()).unwrap_or_default();
+
+        self.draw_percent_sparkline(
+            f,
+            cpu_area,
+            format!(" CPU {:.2}% ", latest.cpu_percent),
+            samples.iter().map(|s| s.cpu_percent).collect(),
+        );
+
+        self.draw_percent_sparkline(
+            f,
+            memory_area,
+            format!(
+                " Memory {} / {} ({:.2}%) ",
+                format_bytes(latest.memory_usage).unwrap_or_default(),
+                format_bytes(latest.memory_limit).unwrap_or_default(),
+                latest.memory_percent
+            ),
+            samples.iter().map(|s| s.memory_percent).collect(),
+        );
+
+        let net_rx = rates(&samples, |s| s.net_rx);
+        let net_tx = rates(&samples, |s| s.net_tx);
+        self.draw_sparkline(
+            f,
+            net_rx_area,
+            format!(
+                " Net RX {}/s (total {}) ",
+                last_rate(&net_rx),
+                format_bytes(latest.net_rx).unwrap_or_default()
+            ),
+            net_rx,
+        );
+        self.draw_sparkline(
+            f,
+            net_tx_area,
+            format!(
+                " Net TX {}/s (total {}) ",
+                last_rate(&net_tx),
+                format_bytes(latest.net_tx).unwrap_or_default()
+            ),
+            net_tx,
+        );
+
+        let block_read = rates(&samples, |s| s.block_read);
+        let block_write = rates(&samples, |s| s.block_write);
+        self.draw_sparkline(
+            f,
+            block_read_area,
+            format!(
+                " Block Read {}/s (total {}) ",
+                last_rate(&block_read),
+                format_bytes(latest.block_read).unwrap_or_default()
+            ),
+            block_read,
+        );
+        self.draw_sparkline(
+            f,
+            block_write_area,
+            format!(
+                " Block Write {}/s (total {}) ",
+                last_rate(&block_write),
+                format_bytes(latest.block_write).unwrap_or_default()
+            ),
+            block_write,
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::{Context, Result};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    widgets::{Paragraph, Row, Table, TableState},
+};
+use ratatui_macros::{constraints, vertical};
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    components::help::{PageHelp, PageHelpBuilder},
+    config::Config,
+    context::AppContext,
+    docker::{
+        system::{CategoryUsage, DiskUsage, UsageResource},
+        util::format_bytes,
+    },
+    events::{Key, Message, Transition, message::MessageResponse},
+    traits::{Close, Component, Page},
+};
+
+const NAME: &str = "System";
+
+const ESC_KEY: Key = Key::Esc;
+const ENTER_KEY: Key = Key::Enter;
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const P_KEY: Key = Key::Char('p');
+const R_KEY: Key = Key::Char('r');
+
+/// Page summarising the disk space used by images, containers, volumes and the
+/// build cache, from which the largest items of each can be listed.
+///
+/// Calculating disk usage can take the daemon a while, so it is only fetched
+/// when the page is opened or refreshed rather than on every tick.
+#[derive(Debug)]
+pub struct System {
+    config: Arc<Config>,
+    docker: bollard::Docker,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    usage: Option<DiskUsage>,
+    /// Index of the category whose items are listed, if any
+    category: Option<usize>,
+    list_state: TableState,
+    item_state: TableState,
+    table_height: u16,
+}
+
+impl System {
+    pub fn new(
+        docker: bollard::Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+    ) -> Self {
+        let page_help = Self::build_page_help(config.clone(), None);
+
+        Self {
+            config,
+            docker,
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            usage: None,
+            category: None,
+            list_state: TableState::default(),
+            item_state: TableState::default(),
+            table_height: 0,
+        }
+    }
+
+    fn build_page_help(config: Arc<Config>, category: Option<&CategoryUsage>) -> PageHelp {
+        let builder = match category {
+            Some(category) => {
+                PageHelpBuilder::new(format!("{NAME} ({})", category.category), config)
+                    .add_input(format!("{ESC_KEY}"), "back".into())
+                    .add_input(format!("{ENTER_KEY}"), "show".into())
+            }
+            None => PageHelpBuilder::new(NAME.into(), config)
+                .add_input(format!("{ENTER_KEY}"), "largest".into()),
+        };
+        builder
+            .add_input(format!("{G_KEY}"), "top".into())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".into())
+            .add_input(format!("{R_KEY}"), "refresh".into())
+            .add_input(format!("{P_KEY}"), "prune".into())
+            .build()
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        self.usage = Some(DiskUsage::get(&self.docker).await?);
+        Ok(())
+    }
+
+    fn categories(&self) -> &[CategoryUsage] {
+        match &self.usage {
+            Some(usage) => &usage.categories,
+            None => &[],
+        }
+    }
+
+    fn get_category(&self) -> Option<&CategoryUsage> {
+        self.category.and_then(|idx| self.categories().get(idx))
+    }
+
+    /// Lists the items of the selected category, or returns to the summary
+    fn set_category(&mut self, category: Option<usize>) {
+        self.category = category;
+        self.item_state = TableState::default();
+        self.item_state.select(Some(0));
+        self.page_help = Arc::new(Mutex::new(Self::build_page_help(
+            self.config.clone(),
+            self.get_category(),
+        )));
+    }
+
+    /// The table which is currently shown, along with the number of rows in it
+    fn current_table(&mut self) -> (&mut TableState, usize) {
+        match self.category {
+            Some(_) => {
+                let len = self.get_category().map_or(0, |c| c.items.len());
+                (&mut self.item_state, len)
+            }
+            None => {
+                let len = self.categories().len();
+                (&mut self.list_state, len)
+            }
+        }
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let (state, len) = self.current_table();
+        let idx = state.selected().unwrap_or_default() + amount;
+        state.select(Some(idx.min(len.saturating_sub(1))));
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let (state, _) = self.current_table();
+        let idx = state.selected().unwrap_or_default();
+        state.select(Some(idx.saturating_sub(amount)));
+    }
+
+    /// Shows the selected item on the page for its type of resource; build
+    /// cache records have no such page
+    async fn show_item(&self) -> Result<MessageResponse> {
+        let Some(resource) = self
+            .get_category()
+            .zip(self.item_state.selected())
+            .and_then(|(category, idx)| category.items.get(idx))
+            .and_then(|item| item.resource.clone())
+        else {
+            return Ok(MessageResponse::NotConsumed);
+        };
+
+        let transition = match resource {
+            UsageResource::Image(image) => Transition::ToImagePage(AppContext {
+                docker_image: Some(image),
+                ..Default::default()
+            }),
+            UsageResource::Container(container) => Transition::ToContainerPage(AppContext {
+                docker_container: Some(container),
+                ..Default::default()
+            }),
+            UsageResource::Volume(volume) => Transition::ToVolumePage(AppContext {
+                docker_volume: Some(volume),
+                ..Default::default()
+            }),
+        };
+        self.tx.send(Message::Transition(transition)).await?;
+        Ok(MessageResponse::Consumed)
+    }
+}
+
+#[async_trait::async_trait]
+impl Page for System {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        let res = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.current_table().0.select(Some(0));
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                let (state, len) = self.current_table();
+                state.select(Some(len.saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            R_KEY => {
+                self.refresh().await?;
+                MessageResponse::Consumed
+            }
+            P_KEY => {
+                self.tx
+                    .send(Message::Transition(Transition::ToPrunePage(
+                        AppContext::default(),
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            ENTER_KEY => match self.category {
+                Some(_) => self.show_item().await?,
+                None => {
+                    self.set_category(self.list_state.selected());
+                    MessageResponse::Consumed
+                }
+            },
+            ESC_KEY if self.category.is_some() => {
+                self.set_category(None);
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(res)
+    }
+
+    async fn initialise(&mut self, _cx: AppContext) -> Result<()> {
+        self.list_state = TableState::default();
+        self.list_state.select(Some(0));
+        self.set_category(None);
+        self.refresh()
+            .await
+            .context("unable to refresh disk usage")?;
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for System {}
+
+impl Component for System {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let [summary_area, table_area] = vertical![==2, >=0].areas(area);
+        self.table_height = table_area.height.saturating_sub(1);
+
+        let summary = match (&self.usage, self.get_category()) {
+            (_, Some(category)) => format!(
+                "{} items, {} in total, {} reclaimable",
+                category.items.len(),
+                format_bytes(category.size).unwrap_or("-".into()),
+                format_reclaimable(category.reclaimable, category.reclaimable_percent())
+            ),
+            (Some(usage), None) => format!(
+                "{} in total, {} reclaimable",
+                format_bytes(usage.size()).unwrap_or("-".into()),
+                format_bytes(usage.reclaimable()).unwrap_or("-".into())
+            ),
+            (None, None) => String::new(),
+        };
+        f.render_widget(
+            Paragraph::new(summary).style(Style::new().fg(self.config.theme.title())),
+            summary_area,
+        );
+
+        let table = match self.get_category() {
+            Some(category) => {
+                let rows = category.items.iter().map(|item| {
+                    Row::new(vec![
+                        item.name.clone(),
+                        format_bytes(item.size).unwrap_or("-".into()),
+                        if item.active { "Yes" } else { "No" }.into(),
+                        item.detail.clone(),
+                    ])
+                });
+                let columns = Row::new(vec!["Name", "Size", "In Use", "Detail"]);
+                Table::new(rows, constraints![==40%, ==15%, ==10%, ==35%])
+                    .header(columns.style(Style::new().bold()))
+            }
+            None => {
+                let rows = self.categories().iter().map(|category| {
+                    Row::new(vec![
+                        category.category.to_string(),
+                        category.total_count.to_string(),
+                        category.active_count.to_string(),
+                        format_bytes(category.size).unwrap_or("-".into()),
+                        format_reclaimable(category.reclaimable, category.reclaimable_percent()),
+                    ])
+                });
+                let columns = Row::new(vec!["Type", "Total", "Active", "Size", "Reclaimable"]);
+                Table::new(rows, constraints![==25%, ==15%, ==15%, ==20%, ==25%])
+                    .header(columns.style(Style::new().bold()))
+            }
+        };
+        let table = table.row_highlight_style(Style::new().reversed());
+
+        let state = match self.category {
+            Some(_) => &mut self.item_state,
+            None => &mut self.list_state,
+        };
+        f.render_stateful_widget(table, table_area, state);
+    }
+}
+
+fn format_reclaimable(size: i64, percent: f64) -> String {
+    format!(
+        "{} ({percent:.0}%)",
+        format_bytes(size).unwrap_or("-".into())
+    )
+}
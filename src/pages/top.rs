@@ -0,0 +1,386 @@
+use bollard::Docker;
+use color_eyre::eyre::{Context, Result, bail};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    widgets::{Row, Table, TableState},
+};
+use ratatui_macros::constraints;
+use std::sync::{Arc, Mutex};
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
+
+use crate::{
+    components::help::{PageHelp, PageHelpBuilder},
+    config::Config,
+    context::AppContext,
+    docker::{stats::DockerContainerStats, util::format_bytes},
+    events::{Key, Message, Transition, message::MessageResponse},
+    sorting::{
+        SortOrder, SortState, StatsSortField, sort_stats_by_block_io, sort_stats_by_cpu,
+        sort_stats_by_memory, sort_stats_by_name, sort_stats_by_net_io,
+    },
+    traits::{Close, Component, Page},
+    ui::{get_field_sort_order, is_field_sorted, render_column_header},
+};
+
+const NAME: &str = "Top";
+
+const UP_KEY: Key = Key::Up;
+const DOWN_KEY: Key = Key::Down;
+const PAGE_UP_KEY: Key = Key::PageUp;
+const PAGE_DOWN_KEY: Key = Key::PageDown;
+
+const J_KEY: Key = Key::Char('j');
+const K_KEY: Key = Key::Char('k');
+const G_KEY: Key = Key::Char('g');
+const SHIFT_G_KEY: Key = Key::Char('G');
+const T_KEY: Key = Key::Char('t');
+const ENTER_KEY: Key = Key::Enter;
+
+// Sort keys
+const SHIFT_N_KEY: Key = Key::Char('N');
+const SHIFT_C_KEY: Key = Key::Char('C');
+const SHIFT_M_KEY: Key = Key::Char('M');
+const SHIFT_I_KEY: Key = Key::Char('I');
+const SHIFT_B_KEY: Key = Key::Char('B');
+
+type StatsSortState = SortState<StatsSortField>;
+
+#[derive(Debug)]
+pub struct Top {
+    pub name: String,
+    tx: Sender<Message<Key, Transition>>,
+    page_help: Arc<Mutex<PageHelp>>,
+    docker: Docker,
+    stats: Vec<DockerContainerStats>,
+    /// Results of the most recent stats request, or why it failed
+    latest_stats: Arc<Mutex<Option<Result<Vec<DockerContainerStats>>>>>,
+    refresh_handle: Option<JoinHandle<()>>,
+    list_state: TableState,
+    sort_state: StatsSortState,
+    table_height: u16,
+}
+
+#[async_trait::async_trait]
+impl Page for Top {
+    async fn update(&mut self, message: Key) -> Result<MessageResponse> {
+        // A failed request is reported on the next tick, after which the
+        // stats are requested again
+        if message == Key::Null
+            && let Some(Err(err)) = self
+                .latest_stats
+                .lock()
+                .unwrap()
+                .take_if(|latest| latest.is_err())
+        {
+            return Err(err);
+        }
+        self.refresh();
+
+        let result = match message {
+            UP_KEY | K_KEY => {
+                self.scroll_up(1);
+                MessageResponse::Consumed
+            }
+            PAGE_UP_KEY => {
+                self.scroll_up(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            DOWN_KEY | J_KEY => {
+                self.scroll_down(1);
+                MessageResponse::Consumed
+            }
+            PAGE_DOWN_KEY => {
+                self.scroll_down(self.table_height.into());
+                MessageResponse::Consumed
+            }
+            G_KEY => {
+                self.list_state.select(Some(0));
+                MessageResponse::Consumed
+            }
+            SHIFT_G_KEY => {
+                self.list_state
+                    .select(Some(self.stats.len().saturating_sub(1)));
+                MessageResponse::Consumed
+            }
+            SHIFT_N_KEY => {
+                self.sort_state.toggle_or_set(StatsSortField::Name);
+                self.sort_stats();
+                MessageResponse::Consumed
+            }
+            SHIFT_C_KEY => {
+                self.sort_state.toggle_or_set(StatsSortField::Cpu);
+                self.sort_stats();
+                MessageResponse::Consumed
+            }
+            SHIFT_M_KEY => {
+                self.sort_state.toggle_or_set(StatsSortField::Memory);
+                self.sort_stats();
+                MessageResponse::Consumed
+            }
+            SHIFT_I_KEY => {
+                self.sort_state.toggle_or_set(StatsSortField::NetIo);
+                self.sort_stats();
+                MessageResponse::Consumed
+            }
+            SHIFT_B_KEY => {
+                self.sort_state.toggle_or_set(StatsSortField::BlockIo);
+                self.sort_stats();
+                MessageResponse::Consumed
+            }
+            ENTER_KEY | T_KEY => {
+                self.tx
+                    .send(Message::Transition(Transition::ToStatsPage(
+                        self.get_context()?,
+                    )))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            _ => MessageResponse::NotConsumed,
+        };
+        Ok(result)
+    }
+
+    async fn initialise(&mut self, cx: AppContext) -> Result<()> {
+        self.list_state = TableState::default();
+        self.list_state.select(Some(0));
+
+        // Stats take around a second to be gathered, so the selection from the
+        // context is retained until the first set of results arrives
+        self.stats = match cx.docker_container {
+            Some(container) => vec![DockerContainerStats {
+                container,
+                stats: Default::default(),
+            }],
+            None => vec![],
+        };
+
+        self.refresh();
+
+        Ok(())
+    }
+
+    fn get_help(&self) -> Arc<Mutex<PageHelp>> {
+        self.page_help.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Close for Top {
+    async fn close(&mut self) -> Result<()> {
+        if let Some(handle) = &self.refresh_handle {
+            handle.abort()
+        }
+        self.refresh_handle = None;
+        self.latest_stats = Arc::new(Mutex::new(None));
+        Ok(())
+    }
+}
+
+impl Top {
+    #[must_use]
+    pub fn new(docker: Docker, tx: Sender<Message<Key, Transition>>, config: Arc<Config>) -> Self {
+        let page_help = PageHelpBuilder::new(NAME.to_string(), config.clone())
+            .add_input(format!("{G_KEY}"), "top".to_string())
+            .add_input(format!("{SHIFT_G_KEY}"), "bottom".to_string())
+            .add_input(format!("{T_KEY}"), "stats".to_string())
+            .build();
+
+        Self {
+            name: String::from(NAME),
+            tx,
+            page_help: Arc::new(Mutex::new(page_help)),
+            docker,
+            stats: vec![],
+            latest_stats: Arc::new(Mutex::new(None)),
+            refresh_handle: None,
+            list_state: TableState::default(),
+            sort_state: StatsSortState {
+                field: StatsSortField::Cpu,
+                order: SortOrder::Descending,
+            },
+            table_height: 0,
+        }
+    }
+
+    /// Takes the results of the most recently completed stats request, and starts
+    /// a new request if one isn't already in flight.
+    ///
+    /// Gathering stats takes around a second, so this is done in the background to
+    /// avoid blocking input; a tick is sent once new results are available.
+    fn refresh(&mut self) {
+        let latest = self
+            .latest_stats
+            .lock()
+            .unwrap()
+            .take_if(|latest| latest.is_ok());
+        if let Some(Ok(latest)) = latest {
+            let selected = self.get_stats().ok().map(|s| s.container.id.clone());
+            self.stats = latest;
+            self.sort_stats();
+            self.select_container(selected);
+        }
+
+        // Nothing is requested while a request is in flight, or while a failure
+        // is yet to be reported
+        if let Some(handle) = &self.refresh_handle
+            && !handle.is_finished()
+        {
+            return;
+        }
+        if self.latest_stats.lock().unwrap().is_some() {
+            return;
+        }
+
+        let docker = self.docker.clone();
+        let tx = self.tx.clone();
+        let latest_stats = self.latest_stats.clone();
+        self.refresh_handle = Some(tokio::spawn(async move {
+            let stats = DockerContainerStats::list(&docker)
+                .await
+                .context("failed to get container stats");
+            *latest_stats.lock().unwrap() = Some(stats);
+            let _ = tx.send(Message::Tick).await;
+        }));
+    }
+
+    fn sort_stats(&mut self) {
+        let field = self.sort_state.field;
+        let order = self.sort_state.order;
+
+        self.stats.sort_by(|a, b| match field {
+            StatsSortField::Name => sort_stats_by_name(a, b, order),
+            StatsSortField::Cpu => sort_stats_by_cpu(a, b, order),
+            StatsSortField::Memory => sort_stats_by_memory(a, b, order),
+            StatsSortField::NetIo => sort_stats_by_net_io(a, b, order),
+            StatsSortField::BlockIo => sort_stats_by_block_io(a, b, order),
+        });
+    }
+
+    /// Keeps the same container selected as rows are re-ordered between refreshes
+    fn select_container(&mut self, container_id: Option<String>) {
+        if let Some(id) = container_id
+            && let Some(idx) = self.stats.iter().position(|s| s.container.id == id)
+        {
+            self.list_state.select(Some(idx));
+        } else if let Some(idx) = self.list_state.selected() {
+            self.list_state
+                .select(Some(idx.min(self.stats.len().saturating_sub(1))));
+        }
+    }
+
+    fn scroll_down(&mut self, amount: usize) {
+        let current_idx = self.list_state.selected();
+        match current_idx {
+            None => self.list_state.select(Some(0)),
+            Some(current_idx) => {
+                if !self.stats.is_empty() {
+                    let len = self.stats.len();
+                    let new_idx = (current_idx + amount).min(len.saturating_sub(1));
+                    self.list_state.select(Some(new_idx));
+                }
+            }
+        }
+    }
+
+    fn scroll_up(&mut self, amount: usize) {
+        let current_idx = self.list_state.selected();
+        match current_idx {
+            None => self.list_state.select(Some(0)),
+            Some(current_idx) => {
+                let new_idx = current_idx.saturating_sub(amount);
+                self.list_state.select(Some(new_idx));
+            }
+        }
+    }
+
+    fn get_stats(&self) -> Result<&DockerContainerStats> {
+        if let Some(idx) = self.list_state.selected()
+            && let Some(stats) = self.stats.get(idx)
+        {
+            return Ok(stats);
+        }
+        bail!("no container id found");
+    }
+
+    fn get_context(&self) -> Result<AppContext> {
+        let container = self.get_stats()?.container.clone();
+
+        let then = Some(Box::new(Transition::ToTopPage(AppContext {
+            docker_container: Some(container.clone()),
+            ..Default::default()
+        })));
+
+        let cx = AppContext {
+            describable: Some(Box::new(container.clone())),
+            then,
+            docker_container: Some(container),
+            ..Default::default()
+        };
+
+        Ok(cx)
+    }
+}
+
+impl Component for Top {
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
+        self.table_height = area.height.saturating_sub(2);
+        let rows = get_stats_rows(&self.stats);
+        let columns = get_header_row(&self.sort_state);
+
+        let widths = constraints![==25%, ==10%, ==25%, ==20%, ==20%];
+
+        let table = Table::new(rows, widths)
+            .header(columns.style(Style::new().bold()))
+            .row_highlight_style(Style::new().reversed());
+
+        f.render_stateful_widget(table, area, &mut self.list_state);
+    }
+}
+
+fn get_stats_rows(stats: &[DockerContainerStats]) -> Vec<Row<'static>> {
+    stats
+        .iter()
+        .map(|s| {
+            Row::new(vec![
+                s.container.names.clone(),
+                format!("{:.2}%", s.stats.cpu_percent),
+                format!(
+                    "{} / {} ({:.2}%)",
+                    format_bytes(s.stats.memory_usage).unwrap_or_default(),
+                    format_bytes(s.stats.memory_limit).unwrap_or_default(),
+                    s.stats.memory_percent
+                ),
+                format!(
+                    "{} / {}",
+                    format_bytes(s.stats.net_rx).unwrap_or_default(),
+                    format_bytes(s.stats.net_tx).unwrap_or_default()
+                ),
+                format!(
+                    "{} / {}",
+                    format_bytes(s.stats.block_read).unwrap_or_default(),
+                    format_bytes(s.stats.block_write).unwrap_or_default()
+                ),
+            ])
+        })
+        .collect()
+}
+
+fn get_header_row(sort_state: &StatsSortState) -> Row<'static> {
+    let header = |name: &str, field: StatsSortField| {
+        render_column_header(
+            name,
+            is_field_sorted(sort_state, &field),
+            get_field_sort_order(sort_state, &field).unwrap_or(SortOrder::Ascending),
+        )
+    };
+
+    Row::new(vec![
+        header("Name", StatsSortField::Name),
+        header("CPU %", StatsSortField::Cpu),
+        header("Memory", StatsSortField::Memory),
+        header("Net I/O (rx / tx)", StatsSortField::NetIo),
+        header("Block I/O (read / write)", StatsSortField::BlockIo),
+    ])
+}
@@ -16,17 +16,26 @@ use std::{
 use tokio::sync::mpsc::Sender;
 
 use crate::{
-    callbacks::delete_volume::DeleteVolume,
+    callbacks::{delete_volume::DeleteVolume, restore_volume::RestoreVolume},
     components::{
         boolean_modal::{BooleanModal, ModalState},
+        form_modal::{self, FormField, FormModal, FormValues},
         help::{PageHelp, PageHelpBuilder},
+        select_modal::{self, SelectModal},
     },
     config::Config,
     context::AppContext,
-    docker::volume::DockerVolume,
+    docker::{
+        container::DockerContainer,
+        create::{DEFAULT_VOLUME_DRIVER, VolumeCreateConfig, validate_key_values, validate_name},
+        files::{expand_local_path, validate_local_path},
+        transfer::{Transfers, validate_output_path},
+        usage::{ContainerUsage, format_used_by, used_by_options},
+        volume::DockerVolume,
+    },
     events::{Key, Message, Transition, message::MessageResponse},
     sorting::{SortOrder, SortState, VolumeSortField},
-    traits::{Close, Component, ModalComponent, Page},
+    traits::{Callback, Close, Component, ModalComponent, Page},
     ui::{get_field_sort_order, is_field_sorted, render_column_header},
 };
 
@@ -42,7 +51,12 @@ const K_KEY: Key = Key::Char('k');
 const CTRL_D_KEY: Key = Key::Ctrl('d');
 const SHIFT_D_KEY: Key = Key::Char('D');
 const D_KEY: Key = Key::Char('d');
+const B_KEY: Key = Key::Char('b');
+const C_KEY: Key = Key::Char('c');
 const G_KEY: Key = Key::Char('g');
+const L_KEY: Key = Key::Char('l');
+const S_KEY: Key = Key::Char('s');
+const U_KEY: Key = Key::Char('u');
 const SHIFT_G_KEY: Key = Key::Char('G');
 const ALT_D_KEY: Key = Key::Alt('d');
 
@@ -57,20 +71,33 @@ type VolumeSortState = SortState<VolumeSortField>;
 enum ModalTypes {
     DeleteVolume,
     ForceDeleteVolume,
+    UsedBy,
+    Create,
+    Backup,
+    Restore,
+    ConfirmRestore,
 }
 
 #[derive(Debug)]
 pub struct Volume {
     pub name: String,
+    config: Arc<Config>,
     tx: Sender<Message<Key, Transition>>,
     page_help: Arc<Mutex<PageHelp>>,
     docker: Docker,
     volumes: Vec<DockerVolume>,
+    usage: ContainerUsage,
     list_state: TableState,
     modal: Option<BooleanModal<ModalTypes>>,
+    form_modal: Option<FormModal<ModalTypes>>,
+    used_by_modal: Option<SelectModal<ModalTypes, DockerContainer>>,
     sort_state: VolumeSortState,
     show_dangling: bool,
     table_height: u16,
+    transfers: Transfers,
+    /// The outcome of checking whether a volume being restored is empty; a
+    /// restore waiting to be confirmed where it isn't
+    restore_check: Arc<Mutex<Option<Result<RestoreVolume>>>>,
 }
 
 #[async_trait::async_trait]
@@ -78,11 +105,57 @@ impl Page for Volume {
     async fn update(&mut self, message: Key) -> Result<MessageResponse> {
         self.refresh().await?;
 
+        if message == Key::Null {
+            let checked = self.restore_check.lock().unwrap().take();
+            if let Some(checked) = checked {
+                self.confirm_restore(checked?);
+            }
+        }
+
         let res = self.update_modal(message).await?;
         if res == MessageResponse::Consumed {
             return Ok(res);
         }
 
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let modal_type = m.discriminator;
+            let values = m.take_submitted();
+            if let form_modal::ModalState::Closed = m.state {
+                self.form_modal = None;
+            }
+            match (modal_type, values) {
+                (ModalTypes::Create, Some(values)) => self.create_volume(values).await?,
+                (ModalTypes::Backup, Some(values)) => self.backup_volume(values)?,
+                (ModalTypes::Restore, Some(values)) => self.restore_volume(values)?,
+                _ => {}
+            }
+            return res;
+        }
+
+        if let Some(m) = self.used_by_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            let res = m.update(message).await;
+            let container = m.take_selected();
+            if let select_modal::ModalState::Closed = m.state {
+                self.used_by_modal = None;
+            }
+            if let Some(container) = container {
+                self.tx
+                    .send(Message::Transition(Transition::ToContainerPage(
+                        AppContext {
+                            docker_container: Some(container),
+                            ..Default::default()
+                        },
+                    )))
+                    .await?;
+            }
+            return res;
+        }
+
         let result = match message {
             UP_KEY | K_KEY => {
                 self.scroll_up(1);
@@ -136,6 +209,32 @@ impl Page for Volume {
                 self.show_dangling = !self.show_dangling;
                 MessageResponse::Consumed
             }
+            C_KEY => {
+                self.open_create_modal();
+                MessageResponse::Consumed
+            }
+            B_KEY => {
+                let volume = self.get_volume()?.clone();
+                self.tx
+                    .send(Message::Transition(Transition::ToFilesPage(AppContext {
+                        docker_volume: Some(volume),
+                        ..Default::default()
+                    })))
+                    .await?;
+                MessageResponse::Consumed
+            }
+            S_KEY => {
+                self.open_backup_modal()?;
+                MessageResponse::Consumed
+            }
+            L_KEY => {
+                self.open_restore_modal()?;
+                MessageResponse::Consumed
+            }
+            U_KEY => {
+                self.open_used_by_modal()?;
+                MessageResponse::Consumed
+            }
             D_KEY => {
                 self.tx
                     .send(Message::Transition(Transition::ToDescribeContainerPage(
@@ -153,6 +252,7 @@ impl Page for Volume {
         self.list_state = TableState::default();
         self.list_state.select(Some(0));
 
+        self.usage.invalidate();
         self.refresh().await.context("unable to refresh volumes")?;
 
         let volume_id: String;
@@ -180,30 +280,51 @@ impl Page for Volume {
 }
 
 #[async_trait::async_trait]
-impl Close for Volume {}
+impl Close for Volume {
+    async fn close(&mut self) -> Result<()> {
+        self.restore_check = Arc::new(Mutex::new(None));
+        Ok(())
+    }
+}
 
 impl Volume {
     #[must_use]
-    pub fn new(docker: Docker, tx: Sender<Message<Key, Transition>>, config: Arc<Config>) -> Self {
+    pub fn new(
+        docker: Docker,
+        tx: Sender<Message<Key, Transition>>,
+        config: Arc<Config>,
+        transfers: Transfers,
+    ) -> Self {
         let page_help = PageHelpBuilder::new(NAME.to_string(), config.clone())
             .add_input(format!("{CTRL_D_KEY}"), "delete".to_string())
             .add_input(format!("{ALT_D_KEY}"), "dangling".to_string())
             .add_input(format!("{G_KEY}"), "top".to_string())
             .add_input(format!("{SHIFT_G_KEY}"), "bottom".to_string())
             .add_input(format!("{D_KEY}"), "describe".to_string())
+            .add_input(format!("{C_KEY}"), "create".to_string())
+            .add_input(format!("{B_KEY}"), "browse".to_string())
+            .add_input(format!("{S_KEY}"), "backup".to_string())
+            .add_input(format!("{L_KEY}"), "restore".to_string())
+            .add_input(format!("{U_KEY}"), "used by".to_string())
             .build();
 
         Self {
             name: String::from(NAME),
+            config,
             tx,
             page_help: Arc::new(Mutex::new(page_help)),
             docker,
             volumes: vec![],
+            usage: ContainerUsage::default(),
             list_state: TableState::default(),
             modal: None,
+            form_modal: None,
+            used_by_modal: None,
             sort_state: VolumeSortState::default(),
             show_dangling: true,
             table_height: 0,
+            transfers,
+            restore_check: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -218,6 +339,7 @@ impl Volume {
         self.volumes = DockerVolume::list(&self.docker)
             .await
             .context("unable to retrieve list of volumes")?;
+        self.usage.refresh(&self.docker).await?;
 
         // Apply current sort after refresh
         self.sort_volumes();
@@ -317,6 +439,157 @@ impl Volume {
         bail!("no container id found");
     }
 
+    fn open_create_modal(&mut self) {
+        let message = "Create a new volume".to_string();
+        let fields = vec![
+            FormField::text("name", "Name").with_validator(validate_name),
+            FormField::text("driver", "Driver").with_value(DEFAULT_VOLUME_DRIVER),
+            FormField::text("driver_opts", "Driver options (KEY=value, ...)")
+                .with_validator(validate_key_values),
+            FormField::text("labels", "Labels (KEY=value, ...)")
+                .with_validator(validate_key_values),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Create".into(), ModalTypes::Create, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+    }
+
+    /// Creates a volume from the submitted form, then selects it in the list
+    async fn create_volume(&mut self, values: FormValues) -> Result<()> {
+        let config = VolumeCreateConfig {
+            name: values.text_opt("name"),
+            driver: values.text_opt("driver"),
+            driver_opts: values.text_opt("driver_opts"),
+            labels: values.text_opt("labels"),
+        };
+        let volume = DockerVolume::create(&self.docker, &config).await?;
+
+        self.refresh().await?;
+        if let Some(idx) = self.volumes.iter().position(|v| v.name == volume.name) {
+            self.list_state.select(Some(idx));
+        }
+        Ok(())
+    }
+
+    fn open_backup_modal(&mut self) -> Result<()> {
+        let volume = self.get_volume()?;
+        let message = format!("Back up {} to a tarball", volume.name);
+        let default_path = format!("./{}.tar", volume.name);
+
+        let fields = vec![
+            FormField::text("path", "Local file")
+                .with_value(&default_path)
+                .with_validator(validate_output_path),
+        ];
+
+        let mut modal =
+            FormModal::<ModalTypes>::new("Backup".into(), ModalTypes::Backup, self.config.clone());
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    /// Backs up the selected volume to the submitted path in the background
+    fn backup_volume(&mut self, values: FormValues) -> Result<()> {
+        let volume = self.get_volume()?.clone();
+        let path = expand_local_path(&values.text("path"));
+        let docker = self.docker.clone();
+
+        let description = format!("Backing up {} to {}", volume.name, path.display());
+        self.transfers
+            .spawn(self.tx.clone(), description, move |transfer| async move {
+                let checksum = volume.backup(&docker, &path, &transfer).await?;
+                Ok(format!(
+                    "Backed up {} to {} (sha256 {checksum})",
+                    volume.name,
+                    path.display()
+                ))
+            });
+        Ok(())
+    }
+
+    fn open_restore_modal(&mut self) -> Result<()> {
+        let volume = self.get_volume()?;
+        let message = format!("Restore {} from a tarball", volume.name);
+        let fields =
+            vec![FormField::text("path", "Local file").with_validator(validate_local_path)];
+
+        let mut modal = FormModal::<ModalTypes>::new(
+            "Restore".into(),
+            ModalTypes::Restore,
+            self.config.clone(),
+        );
+        modal.initialise(message, fields);
+        self.form_modal = Some(modal);
+        Ok(())
+    }
+
+    /// Restores the selected volume from the submitted path in the background.
+    /// A volume which already contains files is only restored once the
+    /// overwrite has been confirmed.
+    fn restore_volume(&mut self, values: FormValues) -> Result<()> {
+        let volume = self.get_volume()?.clone();
+        let path = expand_local_path(&values.text("path"));
+
+        let cb = RestoreVolume::new(
+            self.docker.clone(),
+            self.tx.clone(),
+            self.transfers.clone(),
+            volume.clone(),
+            path,
+        );
+
+        // Checking for files starts a helper container, so is done in the
+        // background; the outcome is picked up on the next tick
+        let docker = self.docker.clone();
+        let tx = self.tx.clone();
+        let restore_check = self.restore_check.clone();
+        tokio::spawn(async move {
+            let checked = match volume.is_empty(&docker).await {
+                Ok(true) => cb.call().await.err().map(Err),
+                Ok(false) => Some(Ok(cb)),
+                Err(e) => Some(Err(e)),
+            };
+            if let Some(checked) = checked {
+                *restore_check.lock().unwrap() = Some(checked);
+                let _ = tx.send(Message::Tick).await;
+            }
+        });
+        Ok(())
+    }
+
+    /// Asks the user to confirm overwriting the files of a volume being restored
+    fn confirm_restore(&mut self, cb: RestoreVolume) {
+        let message = format!(
+            "Volume {} is not empty; are you sure you wish to overwrite its files?",
+            cb.volume_name()
+        );
+        let mut modal =
+            BooleanModal::<ModalTypes>::new("Restore".into(), ModalTypes::ConfirmRestore);
+        modal.initialise(message, Some(Arc::new(FutureMutex::new(cb))));
+        self.modal = Some(modal);
+    }
+
+    /// Lists the containers using the selected volume, allowing one to be
+    /// picked and shown on the containers page
+    fn open_used_by_modal(&mut self) -> Result<()> {
+        let volume = self.get_volume()?;
+        let containers = self.usage.volume(volume);
+        if containers.is_empty() {
+            bail!("no containers use volume {}", volume.name);
+        }
+        let message = format!("Containers using volume {}", volume.name);
+        let options = used_by_options(&containers);
+
+        let mut modal =
+            SelectModal::<ModalTypes, DockerContainer>::new("Used By".into(), ModalTypes::UsedBy);
+        modal.initialise(message, options);
+        self.used_by_modal = Some(modal);
+        Ok(())
+    }
+
     fn get_context(&self) -> Result<AppContext> {
         let volume = self.get_volume()?;
 
@@ -376,10 +649,10 @@ impl Volume {
 impl Component for Volume {
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) {
         self.table_height = area.height.saturating_sub(2);
-        let rows = get_volume_rows(&self.volumes);
+        let rows = get_volume_rows(&self.volumes, &self.usage);
         let columns = get_header_row(&self.sort_state);
 
-        let widths = constraints![==30%, ==15%, ==30%, ==25%];
+        let widths = constraints![==25%, ==10%, ==30%, ==15%, ==20%];
 
         let table = Table::new(rows.clone(), widths)
             .header(columns.clone().style(Style::new().bold()))
@@ -392,10 +665,22 @@ impl Component for Volume {
         {
             m.draw(f, area);
         }
+
+        if let Some(m) = self.form_modal.as_mut()
+            && let form_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
+
+        if let Some(m) = self.used_by_modal.as_mut()
+            && let select_modal::ModalState::Open(_) = m.state
+        {
+            m.draw(f, area);
+        }
     }
 }
 
-fn get_volume_rows(volumes: &[DockerVolume]) -> Vec<Row<'static>> {
+fn get_volume_rows(volumes: &[DockerVolume], usage: &ContainerUsage) -> Vec<Row<'static>> {
     volumes
         .iter()
         .map(|c| {
@@ -404,6 +689,7 @@ fn get_volume_rows(volumes: &[DockerVolume]) -> Vec<Row<'static>> {
                 c.driver.clone(),
                 c.mountpoint.clone(),
                 c.created_at.clone().unwrap_or_default(),
+                format_used_by(&usage.volume(c)),
             ])
         })
         .collect::<Vec<Row<'static>>>()
@@ -435,6 +721,7 @@ fn get_header_row(sort_state: &VolumeSortState) -> Row<'static> {
             get_field_sort_order(sort_state, &VolumeSortField::Created)
                 .unwrap_or(SortOrder::Ascending),
         ),
+        "Used By".into(),
     ];
 
     Row::new(headers)
@@ -1,6 +1,7 @@
 use crate::docker::container::DockerContainer;
 use crate::docker::image::DockerImage;
 use crate::docker::network::DockerNetwork;
+use crate::docker::stats::DockerContainerStats;
 use crate::docker::volume::DockerVolume;
 
 use std::cmp::Ordering;
@@ -59,6 +60,16 @@ pub enum NetworkSortField {
     Driver,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsSortField {
+    Name,
+    #[default]
+    Cpu,
+    Memory,
+    NetIo,
+    BlockIo,
+}
+
 #[derive(Debug, Clone)]
 pub struct SortState<T> {
     pub field: T,
@@ -301,3 +312,212 @@ pub fn sort_networks_by_id(a: &DockerNetwork, b: &DockerNetwork, order: SortOrde
         SortOrder::Descending => cmp.reverse(),
     }
 }
+
+// Sorting functions for container stats
+pub fn sort_stats_by_name(
+    a: &DockerContainerStats,
+    b: &DockerContainerStats,
+    order: SortOrder,
+) -> Ordering {
+    let cmp = a.container.names.cmp(&b.container.names);
+    match order {
+        SortOrder::Ascending => cmp,
+        SortOrder::Descending => cmp.reverse(),
+    }
+}
+
+pub fn sort_stats_by_cpu(
+    a: &DockerContainerStats,
+    b: &DockerContainerStats,
+    order: SortOrder,
+) -> Ordering {
+    let cmp = a.stats.cpu_percent.total_cmp(&b.stats.cpu_percent);
+    match order {
+        SortOrder::Ascending => cmp,
+        SortOrder::Descending => cmp.reverse(),
+    }
+}
+
+pub fn sort_stats_by_memory(
+    a: &DockerContainerStats,
+    b: &DockerContainerStats,
+    order: SortOrder,
+) -> Ordering {
+    let cmp = a.stats.memory_usage.cmp(&b.stats.memory_usage);
+    match order {
+        SortOrder::Ascending => cmp,
+        SortOrder::Descending => cmp.reverse(),
+    }
+}
+
+pub fn sort_stats_by_net_io(
+    a: &DockerContainerStats,
+    b: &DockerContainerStats,
+    order: SortOrder,
+) -> Ordering {
+    let cmp = (a.stats.net_rx + a.stats.net_tx).cmp(&(b.stats.net_rx + b.stats.net_tx));
+    match order {
+        SortOrder::Ascending => cmp,
+        SortOrder::Descending => cmp.reverse(),
+    }
+}
+
+pub fn sort_stats_by_block_io(
+    a: &DockerContainerStats,
+    b: &DockerContainerStats,
+    order: SortOrder,
+) -> Ordering {
+    let cmp =
+        (a.stats.block_read + a.stats.block_write).cmp(&(b.stats.block_read + b.stats.block_write));
+    match order {
+        SortOrder::Ascending => cmp,
+        SortOrder::Descending => cmp.reverse(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bollard::secret::ContainerSummary;
+
+    use super::*;
+    use crate::docker::stats::DockerStats;
+
+    fn container_stats(name: &str, stats: DockerStats) -> DockerContainerStats {
+        DockerContainerStats {
+            container: DockerContainer::from(ContainerSummary {
+                id: Some(name.into()),
+                names: Some(vec![format!("/{name}")]),
+                ..Default::default()
+            }),
+            stats,
+        }
+    }
+
+    fn sample() -> Vec<DockerContainerStats> {
+        vec![
+            container_stats(
+                "web",
+                DockerStats {
+                    cpu_percent: 12.5,
+                    memory_usage: 300,
+                    net_rx: 10,
+                    net_tx: 5,
+                    block_read: 100,
+                    block_write: 0,
+                    ..Default::default()
+                },
+            ),
+            container_stats(
+                "api",
+                DockerStats {
+                    cpu_percent: 150.0,
+                    memory_usage: 100,
+                    net_rx: 1,
+                    net_tx: 1,
+                    block_read: 50,
+                    block_write: 100,
+                    ..Default::default()
+                },
+            ),
+            container_stats(
+                "db",
+                DockerStats {
+                    cpu_percent: 0.5,
+                    memory_usage: 200,
+                    net_rx: 0,
+                    net_tx: 20,
+                    block_read: 1,
+                    block_write: 1,
+                    ..Default::default()
+                },
+            ),
+        ]
+    }
+
+    fn sorted_names(
+        sort: fn(&DockerContainerStats, &DockerContainerStats, SortOrder) -> Ordering,
+        order: SortOrder,
+    ) -> Vec<String> {
+        let mut stats = sample();
+        stats.sort_by(|a, b| sort(a, b, order));
+        stats.into_iter().map(|s| s.container.names).collect()
+    }
+
+    #[test]
+    fn test_sort_stats_by_name() {
+        assert_eq!(
+            sorted_names(sort_stats_by_name, SortOrder::Ascending),
+            vec!["api", "db", "web"]
+        );
+        assert_eq!(
+            sorted_names(sort_stats_by_name, SortOrder::Descending),
+            vec!["web", "db", "api"]
+        );
+    }
+
+    #[test]
+    fn test_sort_stats_by_cpu() {
+        assert_eq!(
+            sorted_names(sort_stats_by_cpu, SortOrder::Ascending),
+            vec!["db", "web", "api"]
+        );
+        assert_eq!(
+            sorted_names(sort_stats_by_cpu, SortOrder::Descending),
+            vec!["api", "web", "db"]
+        );
+    }
+
+    #[test]
+    fn test_sort_stats_by_memory() {
+        assert_eq!(
+            sorted_names(sort_stats_by_memory, SortOrder::Ascending),
+            vec!["api", "db", "web"]
+        );
+        assert_eq!(
+            sorted_names(sort_stats_by_memory, SortOrder::Descending),
+            vec!["web", "db", "api"]
+        );
+    }
+
+    #[test]
+    fn test_sort_stats_by_net_io() {
+        assert_eq!(
+            sorted_names(sort_stats_by_net_io, SortOrder::Ascending),
+            vec!["api", "web", "db"]
+        );
+        assert_eq!(
+            sorted_names(sort_stats_by_net_io, SortOrder::Descending),
+            vec!["db", "web", "api"]
+        );
+    }
+
+    #[test]
+    fn test_sort_stats_by_block_io() {
+        assert_eq!(
+            sorted_names(sort_stats_by_block_io, SortOrder::Ascending),
+            vec!["db", "web", "api"]
+        );
+        assert_eq!(
+            sorted_names(sort_stats_by_block_io, SortOrder::Descending),
+            vec!["api", "web", "db"]
+        );
+    }
+
+    #[test]
+    fn test_stats_sort_state() {
+        let mut state = SortState::<StatsSortField>::default();
+        assert_eq!(state.field, StatsSortField::Cpu);
+        assert_eq!(state.order, SortOrder::Ascending);
+
+        state.toggle_or_set(StatsSortField::Cpu);
+        assert_eq!(
+            state.get_order_for_field(StatsSortField::Cpu),
+            Some(SortOrder::Descending)
+        );
+
+        state.toggle_or_set(StatsSortField::Memory);
+        assert!(state.is_field_sorted(StatsSortField::Memory));
+        assert_eq!(state.get_order_for_field(StatsSortField::Cpu), None);
+        assert_eq!(state.order, SortOrder::Ascending);
+    }
+}
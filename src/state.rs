@@ -18,11 +18,24 @@ pub enum CurrentPage {
     #[default]
     Containers,
     Images,
+    Pull,
+    Push,
+    History,
+    Build,
     Volumes,
     Logs,
     Attach,
+    AttachProcess,
     Network,
+    NetworkDetail,
     DescribeContainer,
+    Files,
+    Changes,
+    Stats,
+    Top,
+    System,
+    Prune,
+    Events,
     Help,
 }
 
@@ -20,6 +20,7 @@ use crate::{
         resize_notice::ResizeScreen,
     },
     config::Config,
+    docker::volume_browser,
     events::{Message, Transition, key::Key, message::MessageResponse},
     state::{self, Running},
     traits::{Component, ModalComponent},
@@ -55,6 +56,12 @@ impl App {
 
         let page = state::CurrentPage::default();
 
+        // Helper containers are removed when the volume browser is closed, but
+        // may be left behind if a previous session crashed
+        if let Err(e) = volume_browser::remove_stale_helpers(&docker).await {
+            tracing::warn!("unable to remove stale helper containers\n{e:#}");
+        }
+
         let body = PageManager::new(page.clone(), tx.clone(), docker, config.clone())
             .await
             .context("unable to create new body component")?;
@@ -78,6 +85,13 @@ impl App {
         Ok(app)
     }
 
+    /// Closes the current page before exiting
+    pub async fn close(&mut self) {
+        if let Err(e) = self.page_manager.close().await {
+            tracing::error!("An error occurred closing the app\n{e:#}");
+        }
+    }
+
     pub async fn update(&mut self, message: Key) -> MessageResponse {
         // Explicitly here and in transition, if there is an error modal, we don't
         // want to allow any event get into the application until the modal is
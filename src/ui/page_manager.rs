@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
 use bollard::Docker;
-use color_eyre::eyre::{Context, Result};
+use color_eyre::eyre::{Context, Result, bail};
 use ratatui::{
     Frame,
     layout::{Margin, Rect},
-    text::Line,
+    style::Stylize,
+    text::{Line, Span},
     widgets::{Block, Padding},
 };
 use tokio::sync::mpsc::Sender;
@@ -13,10 +14,32 @@ use tokio::sync::mpsc::Sender;
 use crate::{
     config::Config,
     context::AppContext,
+    docker::{
+        exec::ExecSessions,
+        transfer::{TransferState, Transfers},
+        util::format_bytes,
+    },
     events::{Key, Message, Transition, message::MessageResponse},
     pages::{
-        attach::Attach, containers::Containers, describe::DescribeContainer, images::Images,
-        logs::Logs, networks::Network, volumes::Volume,
+        attach::Attach,
+        attach_process::AttachProcess,
+        build::Build,
+        changes::Changes,
+        containers::Containers,
+        describe::DescribeContainer,
+        events::Events,
+        files::Files,
+        history::History,
+        images::Images,
+        logs::Logs,
+        network_detail::NetworkDetail,
+        networks::Network,
+        prune::Prune,
+        registry::{Registry, RegistryOperation},
+        stats::Stats,
+        system::System,
+        top::Top,
+        volumes::Volume,
     },
     state,
     traits::{Component, Page},
@@ -29,6 +52,8 @@ pub struct PageManager {
     page: Box<dyn Page>,
     tx: Sender<Message<Key, Transition>>,
     docker: Docker,
+    exec_sessions: ExecSessions,
+    transfers: Transfers,
 }
 
 impl PageManager {
@@ -38,7 +63,13 @@ impl PageManager {
         docker: Docker,
         config: Arc<Config>,
     ) -> Result<Self> {
-        let containers = Box::new(Containers::new(docker.clone(), tx.clone(), config.clone()));
+        let transfers = Transfers::default();
+        let containers = Box::new(Containers::new(
+            docker.clone(),
+            tx.clone(),
+            config.clone(),
+            transfers.clone(),
+        ));
 
         let mut page_manager = Self {
             config,
@@ -46,6 +77,8 @@ impl PageManager {
             page: containers,
             tx,
             docker,
+            exec_sessions: ExecSessions::default(),
+            transfers,
         };
 
         page_manager
@@ -61,6 +94,11 @@ impl PageManager {
         Ok(())
     }
 
+    /// Closes the current page, allowing it to clean up before the app exits
+    pub async fn close(&mut self) -> Result<()> {
+        self.page.close().await.context("unable to close page")
+    }
+
     pub async fn transition(&mut self, transition: Transition) -> Result<MessageResponse> {
         let result = match transition {
             Transition::ToImagePage(cx) => {
@@ -68,6 +106,23 @@ impl PageManager {
                     .await?;
                 MessageResponse::Consumed
             }
+            Transition::ToPullPage(cx) => {
+                self.set_current_page(state::CurrentPage::Pull, cx).await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToPushPage(cx) => {
+                self.set_current_page(state::CurrentPage::Push, cx).await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToHistoryPage(cx) => {
+                self.set_current_page(state::CurrentPage::History, cx)
+                    .await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToBuildPage(cx) => {
+                self.set_current_page(state::CurrentPage::Build, cx).await?;
+                MessageResponse::Consumed
+            }
             Transition::ToContainerPage(cx) => {
                 self.set_current_page(state::CurrentPage::Containers, cx)
                     .await?;
@@ -82,6 +137,20 @@ impl PageManager {
                     .await?;
                 MessageResponse::Consumed
             }
+            Transition::ToAttachProcess(cx) => {
+                self.set_current_page(state::CurrentPage::AttachProcess, cx)
+                    .await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToFilesPage(cx) => {
+                self.set_current_page(state::CurrentPage::Files, cx).await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToChangesPage(cx) => {
+                self.set_current_page(state::CurrentPage::Changes, cx)
+                    .await?;
+                MessageResponse::Consumed
+            }
             Transition::ToDescribeContainerPage(cx) => {
                 self.set_current_page(state::CurrentPage::DescribeContainer, cx)
                     .await?;
@@ -97,17 +166,63 @@ impl PageManager {
                     .await?;
                 MessageResponse::Consumed
             }
+            Transition::ToNetworkDetailPage(cx) => {
+                self.set_current_page(state::CurrentPage::NetworkDetail, cx)
+                    .await?;
+                MessageResponse::Consumed
+            }
             Transition::ToHelpPage(cx) => {
                 self.set_current_page(state::CurrentPage::Help, cx).await?;
                 MessageResponse::Consumed
             }
+            Transition::ToStatsPage(cx) => {
+                self.set_current_page(state::CurrentPage::Stats, cx).await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToTopPage(cx) => {
+                self.set_current_page(state::CurrentPage::Top, cx).await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToSystemPage(cx) => {
+                self.set_current_page(state::CurrentPage::System, cx)
+                    .await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToPrunePage(cx) => {
+                self.set_current_page(state::CurrentPage::Prune, cx).await?;
+                MessageResponse::Consumed
+            }
+            Transition::ToEventsPage(cx) => {
+                self.set_current_page(state::CurrentPage::Events, cx)
+                    .await?;
+                MessageResponse::Consumed
+            }
             _ => MessageResponse::NotConsumed,
         };
         Ok(result)
     }
 
     pub async fn update(&mut self, message: Key) -> Result<MessageResponse> {
-        self.page.update(message).await
+        let response = self.page.update(message).await?;
+
+        // Transfers send a tick when they finish, so failures are reported
+        // then rather than swallowing whichever key happened to be pressed
+        if message != Key::Null {
+            return Ok(response);
+        }
+        let failed = self
+            .transfers
+            .take_failed()
+            .into_iter()
+            .filter_map(|t| match t.state() {
+                TransferState::Failed(e) => Some(format!("{} failed: {e}", t.description)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            bail!("{}", failed.join("\n\n"));
+        }
+        Ok(response)
     }
 
     async fn set_current_page(
@@ -127,13 +242,40 @@ impl PageManager {
 
         match next_page {
             state::CurrentPage::Attach => {
-                self.page = Box::new(Attach::new(self.tx.clone(), self.config.clone()))
+                self.page = Box::new(Attach::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                    self.exec_sessions.clone(),
+                ))
+            }
+            state::CurrentPage::AttachProcess => {
+                self.page = Box::new(AttachProcess::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::Files => {
+                self.page = Box::new(Files::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::Changes => {
+                self.page = Box::new(Changes::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
             }
             state::CurrentPage::Containers => {
                 self.page = Box::new(Containers::new(
                     self.docker.clone(),
                     self.tx.clone(),
                     self.config.clone(),
+                    self.transfers.clone(),
                 ))
             }
             state::CurrentPage::Images => {
@@ -141,6 +283,37 @@ impl PageManager {
                     self.docker.clone(),
                     self.tx.clone(),
                     self.config.clone(),
+                    self.transfers.clone(),
+                ))
+            }
+            state::CurrentPage::Pull => {
+                self.page = Box::new(Registry::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                    RegistryOperation::Pull,
+                ))
+            }
+            state::CurrentPage::Push => {
+                self.page = Box::new(Registry::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                    RegistryOperation::Push,
+                ))
+            }
+            state::CurrentPage::History => {
+                self.page = Box::new(History::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::Build => {
+                self.page = Box::new(Build::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
                 ))
             }
             state::CurrentPage::Logs => {
@@ -162,6 +335,7 @@ impl PageManager {
                     self.docker.clone(),
                     self.tx.clone(),
                     self.config.clone(),
+                    self.transfers.clone(),
                 ))
             }
             state::CurrentPage::Network => {
@@ -171,7 +345,49 @@ impl PageManager {
                     self.config.clone(),
                 ))
             }
+            state::CurrentPage::NetworkDetail => {
+                self.page = Box::new(NetworkDetail::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
             state::CurrentPage::Help => self.page = Box::new(crate::pages::help::HelpPage::new()),
+            state::CurrentPage::Stats => {
+                self.page = Box::new(Stats::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::Top => {
+                self.page = Box::new(Top::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::System => {
+                self.page = Box::new(System::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::Prune => {
+                self.page = Box::new(Prune::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
+            state::CurrentPage::Events => {
+                self.page = Box::new(Events::new(
+                    self.docker.clone(),
+                    self.tx.clone(),
+                    self.config.clone(),
+                ))
+            }
         }
 
         self.page.initialise(cx).await?;
@@ -190,11 +406,36 @@ impl Component for PageManager {
 
         let title = Line::from(format!("< {} >", title_message)).centered();
 
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .border_type(ratatui::widgets::BorderType::Plain)
             .title_top(title)
             .padding(Padding::left(300));
 
+        // Transfers run in the background, so are listed regardless of the page
+        let transfers = self.transfers.visible();
+        if !transfers.is_empty() {
+            let spans = transfers.iter().enumerate().flat_map(|(idx, t)| {
+                let (text, colour) = match t.state() {
+                    TransferState::Running => (
+                        format!(
+                            "{} ({})",
+                            t.description,
+                            format_bytes(t.bytes()).unwrap_or_default()
+                        ),
+                        self.config.theme.footer(),
+                    ),
+                    TransferState::Done(message) => (message, self.config.theme.success()),
+                    TransferState::Failed(_) => (
+                        format!("{} failed", t.description),
+                        self.config.theme.error(),
+                    ),
+                };
+                let separator = if idx == 0 { " " } else { " | " };
+                [Span::from(separator), Span::from(text).fg(colour)]
+            });
+            block = block.title_bottom(Line::from_iter(spans.chain([Span::from(" ")])));
+        }
+
         f.render_widget(block, area);
 
         let inner_body_margin = Margin::new(2, 1);
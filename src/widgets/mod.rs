@@ -1,2 +1,3 @@
 pub mod modal;
+pub mod terminal_pane;
 pub mod text_input;
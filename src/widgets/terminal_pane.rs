@@ -0,0 +1,32 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{Block, Widget},
+};
+use tui_term::widget::PseudoTerminal;
+
+use crate::docker::terminal::TerminalSession;
+
+/// Draws the screen of a terminal session within a bordered block, resizing the
+/// session's TTY to fit the space available
+pub struct TerminalPaneWidget<'a> {
+    session: &'a TerminalSession,
+    title: String,
+}
+
+impl<'a> TerminalPaneWidget<'a> {
+    pub fn new(session: &'a TerminalSession, title: String) -> Self {
+        Self { session, title }
+    }
+}
+
+impl Widget for TerminalPaneWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(self.title);
+        let inner = block.inner(area);
+        self.session.resize(inner.width, inner.height);
+        self.session.with_screen(|screen| {
+            PseudoTerminal::new(screen).block(block).render(area, buf);
+        });
+    }
+}